@@ -5,14 +5,15 @@ use syn::{ReturnType, Signature};
 
 impl ImplItemMethodInfo {
     /// Generate wrapper method for the given method of the contract.
+    ///
+    /// On `wasm32` the wrapper is the exported entry point, which calls the host directly. On other
+    /// targets a native wrapper with the same body is generated instead, which runs against the
+    /// `BlockchainInterface` that is currently set in `env`, e.g. the `MockedBlockchain`.
     pub fn method_wrapper(&self) -> TokenStream2 {
         let ImplItemMethodInfo { attr_signature_info, struct_type, .. } = self;
         // Args provided by `env::input()`.
         let has_input_args = attr_signature_info.input_args().next().is_some();
 
-        let env_creation = quote! {
-            near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-        };
         let arg_struct;
         let arg_parsing;
         if has_input_args {
@@ -50,6 +51,7 @@ impl ImplItemMethodInfo {
             result_serializer,
             is_init,
             is_payable,
+            is_payable_storage,
             is_private,
             is_view,
             ..
@@ -69,17 +71,30 @@ impl ImplItemMethodInfo {
         let is_private_check = if *is_private {
             let error = format!("Method {} is private", ident.to_string());
             quote! {
-                if env::current_account_id() != env::predecessor_account_id() {
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic(#error.as_bytes());
                 }
             }
         } else {
             quote! {}
         };
+        // The storage is measured around the whole call, including the state write.
+        let (storage_guard, storage_finish) = if *is_payable_storage {
+            (
+                quote! {
+                    let __storage_guard = near_sdk_pure::StorageGuard::new();
+                },
+                quote! {
+                    __storage_guard.finish();
+                },
+            )
+        } else {
+            (TokenStream2::new(), TokenStream2::new())
+        };
         let body = if *is_init {
             quote! {
                 let contract = #struct_type::#ident(#arg_list);
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
             }
         } else {
             let contract_deser;
@@ -88,14 +103,14 @@ impl ImplItemMethodInfo {
             if let Some(receiver) = receiver {
                 let mutability = &receiver.mutability;
                 contract_deser = quote! {
-                    let #mutability contract: #struct_type = near_sdk_pure::env::state_read().unwrap_or_default();
+                    let #mutability contract: #struct_type = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 };
                 method_invocation = quote! {
                     contract.#ident(#arg_list)
                 };
                 if !is_view {
                     contract_ser = quote! {
-                        near_sdk_pure::env::state_write(&contract);
+                        near_sdk_pure::ContractState::state_write(&contract);
                     };
                 } else {
                     contract_ser = TokenStream2::new();
@@ -138,19 +153,30 @@ impl ImplItemMethodInfo {
                 #value
             }
         });
+        let wrapped_body = quote! {
+            near_sdk_pure::env::reset_context_cache();
+            #is_private_check
+            #deposit_check
+            #storage_guard
+            #arg_struct
+            #arg_parsing
+            #callback_deser
+            #callback_vec_deser
+            #body
+            #storage_finish
+        };
         quote! {
             #non_bindgen_attrs
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn #ident() {
-                #env_creation
-                #is_private_check
-                #deposit_check
-                #arg_struct
-                #arg_parsing
-                #callback_deser
-                #callback_vec_deser
-                #body
+                #wrapped_body
+            }
+            #non_bindgen_attrs
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn #ident() {
+                #wrapped_body
+                near_sdk_pure::test_utils::finish_call();
             }
         }
     }
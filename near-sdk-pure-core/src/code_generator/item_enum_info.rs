@@ -0,0 +1,102 @@
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{AttributeArgs, ItemEnum, Lit, LitStr, Meta, NestedMeta};
+
+/// Generates the code of `#[near_event(standard = "...", version = "...")]`: the serde derives that
+/// log each variant as `{"event": "variant_name", "data": ...}` and the `NearEvent` implementation
+/// with the standard and the version of the arguments.
+pub fn generate_near_event(args: &AttributeArgs, input: &ItemEnum) -> syn::Result<TokenStream2> {
+    let mut standard: Option<LitStr> = None;
+    let mut version: Option<LitStr> = None;
+    for arg in args {
+        let (target, value) = match arg {
+            NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("standard") => {
+                (&mut standard, &pair.lit)
+            }
+            NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("version") => {
+                (&mut version, &pair.lit)
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "Expected `standard = \"...\"` or `version = \"...\"`.",
+                ))
+            }
+        };
+        match value {
+            Lit::Str(value) => *target = Some(value.clone()),
+            _ => return Err(syn::Error::new_spanned(value, "Expected a string literal.")),
+        }
+    }
+    let missing = |name: &str| {
+        syn::Error::new(Span::call_site(), format!("near_event requires the `{}` argument.", name))
+    };
+    let standard = standard.ok_or_else(|| missing("standard"))?;
+    let version = version.ok_or_else(|| missing("version"))?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[derive(near_sdk_pure::serde::Serialize, near_sdk_pure::serde::Deserialize)]
+        #[serde(
+            crate = "near_sdk_pure::serde",
+            tag = "event",
+            content = "data",
+            rename_all = "snake_case"
+        )]
+        #input
+        impl #impl_generics near_sdk_pure::NearEvent for #name #ty_generics #where_clause {
+            const STANDARD: &'static str = #standard;
+            const VERSION: &'static str = #version;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse::Parser;
+    use syn::punctuated::Punctuated;
+    use syn::Token;
+
+    fn args(tokens: TokenStream2) -> AttributeArgs {
+        Punctuated::<NestedMeta, Token![,]>::parse_terminated
+            .parse2(tokens)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn near_event() {
+        let input: ItemEnum = syn::parse2(quote! {
+            pub enum NftEvent {
+                NftMint(Vec<NftMintData>),
+            }
+        })
+        .unwrap();
+        let actual =
+            generate_near_event(&args(quote! { standard = "nep171", version = "1.0.0" }), &input)
+                .unwrap();
+        let expected = quote! {
+            #[derive(near_sdk_pure::serde::Serialize, near_sdk_pure::serde::Deserialize)]
+            #[serde(
+                crate = "near_sdk_pure::serde",
+                tag = "event",
+                content = "data",
+                rename_all = "snake_case"
+            )]
+            pub enum NftEvent {
+                NftMint(Vec<NftMintData>),
+            }
+            impl near_sdk_pure::NearEvent for NftEvent {
+                const STANDARD: &'static str = "nep171";
+                const VERSION: &'static str = "1.0.0";
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+
+        let error = generate_near_event(&args(quote! { standard = "nep171" }), &input);
+        assert_eq!(error.unwrap_err().to_string(), "near_event requires the `version` argument.");
+    }
+}
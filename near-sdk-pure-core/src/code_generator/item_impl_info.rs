@@ -60,10 +60,17 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method();
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
@@ -78,10 +85,17 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method();
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
@@ -96,13 +110,24 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::attached_deposit() != 0 {
+                    near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
+                }
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
                 if near_sdk_pure::env::attached_deposit() != 0 {
                     near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
                 }
-                let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method();
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -118,7 +143,7 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
                 #[derive(near_sdk_pure :: serde :: Deserialize)]
                 #[serde(crate = "near_sdk_pure::serde")]
                 struct Input {
@@ -128,9 +153,25 @@ mod tests {
                     &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                 )
                 .expect("Failed to deserialize input from JSON.");
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(k, );
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(k, );
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
@@ -146,7 +187,27 @@ mod tests {
                 #[cfg(target_arch = "wasm32")]
                 #[no_mangle]
                 pub extern "C" fn method() {
-                    near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                    near_sdk_pure::env::reset_context_cache();
+                    if near_sdk_pure::env::attached_deposit() != 0 {
+                        near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
+                    }
+                    #[derive(near_sdk_pure :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk_pure::serde")]
+                    struct Input {
+                        k: u64,
+                        m: Bar,
+                    }
+                    let Input { k, m, }: Input = near_sdk_pure::serde_json::from_slice(
+                        &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                    contract.method(k, m, );
+                    near_sdk_pure::ContractState::state_write(&contract);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                pub fn method() {
+                    near_sdk_pure::env::reset_context_cache();
                     if near_sdk_pure::env::attached_deposit() != 0 {
                         near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
                     }
@@ -160,9 +221,10 @@ mod tests {
                         &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                     )
                     .expect("Failed to deserialize input from JSON.");
-                    let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                    let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                     contract.method(k, m, );
-                    near_sdk_pure::env::state_write(&contract);
+                    near_sdk_pure::ContractState::state_write(&contract);
+                    near_sdk_pure::test_utils::finish_call();
                 }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -179,7 +241,7 @@ mod tests {
                 #[cfg(target_arch = "wasm32")]
                 #[no_mangle]
                 pub extern "C" fn method() {
-                    near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                    near_sdk_pure::env::reset_context_cache();
                     if near_sdk_pure::env::attached_deposit() != 0 {
                         near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
                     }
@@ -193,12 +255,36 @@ mod tests {
                         &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                     )
                     .expect("Failed to deserialize input from JSON.");
-                    let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                    let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                     let result = contract.method(k, m, );
                     let result =
                         near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
                     near_sdk_pure::env::value_return(&result);
-                    near_sdk_pure::env::state_write(&contract);
+                    near_sdk_pure::ContractState::state_write(&contract);
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                pub fn method() {
+                    near_sdk_pure::env::reset_context_cache();
+                    if near_sdk_pure::env::attached_deposit() != 0 {
+                        near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
+                    }
+                    #[derive(near_sdk_pure :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk_pure::serde")]
+                    struct Input {
+                        k: u64,
+                        m: Bar,
+                    }
+                    let Input { k, m, }: Input = near_sdk_pure::serde_json::from_slice(
+                        &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                    let result = contract.method(k, m, );
+                    let result =
+                        near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                    near_sdk_pure::env::value_return(&result);
+                    near_sdk_pure::ContractState::state_write(&contract);
+                    near_sdk_pure::test_utils::finish_call();
                 }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -215,12 +301,22 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                let result = contract.method();
+                let result =
+                    near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk_pure::env::value_return(&result);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 let result = contract.method();
                 let result =
                     near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
                 near_sdk_pure::env::value_return(&result);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -236,7 +332,22 @@ mod tests {
                 #[cfg(target_arch = "wasm32")]
                 #[no_mangle]
                 pub extern "C" fn method() {
-                    near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                    near_sdk_pure::env::reset_context_cache();
+                    #[derive(near_sdk_pure :: serde :: Deserialize)]
+                    #[serde(crate = "near_sdk_pure::serde")]
+                    struct Input {
+                        k: u64,
+                    }
+                    let Input { k, }: Input = near_sdk_pure::serde_json::from_slice(
+                        &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                    )
+                    .expect("Failed to deserialize input from JSON.");
+                    let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                    contract.method(&k, );
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                pub fn method() {
+                    near_sdk_pure::env::reset_context_cache();
                     #[derive(near_sdk_pure :: serde :: Deserialize)]
                     #[serde(crate = "near_sdk_pure::serde")]
                     struct Input {
@@ -246,8 +357,9 @@ mod tests {
                         &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                     )
                     .expect("Failed to deserialize input from JSON.");
-                    let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                    let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                     contract.method(&k, );
+                    near_sdk_pure::test_utils::finish_call();
                 }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -264,7 +376,22 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { mut k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(&mut k, );
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
                 #[derive(near_sdk_pure :: serde :: Deserialize)]
                 #[serde(crate = "near_sdk_pure::serde")]
                 struct Input {
@@ -274,8 +401,9 @@ mod tests {
                     &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                 )
                 .expect("Failed to deserialize input from JSON.");
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(&mut k, );
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -293,8 +421,38 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                if env::current_account_id() != env::predecessor_account_id() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                    near_sdk_pure::env::panic("Method method is private".as_bytes());
+                }
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    y: String,
+                }
+                let Input { y, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(0u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 0u64)
+                };
+                let mut x: u64 =
+                    near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(1u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 1u64)
+                };
+                let z: Vec<u8> =
+                    near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(&mut x, y, z, );
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic("Method method is private".as_bytes());
                 }
                 #[derive(near_sdk_pure :: serde :: Deserialize)]
@@ -318,8 +476,9 @@ mod tests {
                 };
                 let z: Vec<u8> =
                     near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(&mut x, y, z, );
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -337,8 +496,8 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                if env::current_account_id() != env::predecessor_account_id() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic("Method method is private".as_bytes());
                 }
                 let data: Vec<u8> = match near_sdk_pure::env::promise_result(0u64) {
@@ -353,9 +512,31 @@ mod tests {
                 };
                 let y: String =
                     near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(&mut x, y, );
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                    near_sdk_pure::env::panic("Method method is private".as_bytes());
+                }
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(0u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 0u64)
+                };
+                let mut x: u64 =
+                    near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(1u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 1u64)
+                };
+                let y: String =
+                    near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(&mut x, y, );
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
 
         assert_eq!(expected.to_string(), actual.to_string());
@@ -373,8 +554,8 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                if env::current_account_id() != env::predecessor_account_id() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic("Method method is private".as_bytes());
                 }
                 #[derive(near_sdk_pure :: serde :: Deserialize)]
@@ -395,9 +576,37 @@ mod tests {
                         near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON")
                     })
                     .collect();
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(x, y, );
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                    near_sdk_pure::env::panic("Method method is private".as_bytes());
+                }
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    y: String,
+                }
+                let Input { y, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let x: Vec<String> = (0..near_sdk_pure::env::promise_results_count())
+                    .map(|i| {
+                        let data: Vec<u8> = match near_sdk_pure::env::promise_result(i) {
+                            near_sdk_pure::PromiseResult::Successful(x) => x,
+                            _ => panic!("Callback computation {} was not successful", i)
+                        };
+                        near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON")
+                    })
+                    .collect();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(x, y, );
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
@@ -415,7 +624,7 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
                 if near_sdk_pure::env::attached_deposit() != 0 {
                     near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
                 }
@@ -429,7 +638,26 @@ mod tests {
                 )
                 .expect("Failed to deserialize input from JSON.");
                 let contract = Hello::method(&mut k,);
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::attached_deposit() != 0 {
+                    near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
+                }
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { mut k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let contract = Hello::method(&mut k,);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -449,7 +677,22 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { mut k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let contract = Hello::method(&mut k,);
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
                 #[derive(near_sdk_pure :: serde :: Deserialize)]
                 #[serde(crate = "near_sdk_pure::serde")]
                 struct Input {
@@ -460,7 +703,8 @@ mod tests {
                 )
                 .expect("Failed to deserialize input from JSON.");
                 let contract = Hello::method(&mut k,);
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -479,7 +723,7 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
+                near_sdk_pure::env::reset_context_cache();
                 if near_sdk_pure::env::attached_deposit() != 0 {
                     near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
                 }
@@ -492,12 +736,35 @@ mod tests {
                     &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
                 )
                 .expect("Failed to deserialize input from Borsh.");
-                let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 let result = contract.method(k, m, );
                 let result = near_sdk_pure::borsh::BorshSerialize::try_to_vec(&result)
                     .expect("Failed to serialize the return value using Borsh.");
                 near_sdk_pure::env::value_return(&result);
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::attached_deposit() != 0 {
+                    near_sdk_pure::env::panic("Method method doesn't accept deposit".as_bytes());
+                }
+                #[derive(near_sdk_pure :: borsh :: BorshDeserialize)]
+                struct Input {
+                    k: u64,
+                    m: Bar,
+                }
+                let Input { k, m, }: Input = near_sdk_pure::borsh::BorshDeserialize::try_from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from Borsh.");
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                let result = contract.method(k, m, );
+                let result = near_sdk_pure::borsh::BorshSerialize::try_to_vec(&result)
+                    .expect("Failed to serialize the return value using Borsh.");
+                near_sdk_pure::env::value_return(&result);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
@@ -515,8 +782,8 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                if env::current_account_id() != env::predecessor_account_id() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic("Method method is private".as_bytes());
                 }
                 #[derive(near_sdk_pure :: borsh :: BorshDeserialize)]
@@ -539,9 +806,39 @@ mod tests {
                 };
                 let z: Vec<u8> =
                     near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
-                let contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method(&mut x, y, z, );
             }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                    near_sdk_pure::env::panic("Method method is private".as_bytes());
+                }
+                #[derive(near_sdk_pure :: borsh :: BorshDeserialize)]
+                struct Input {
+                    y: String,
+                }
+                let Input { y, }: Input = near_sdk_pure::borsh::BorshDeserialize::try_from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from Borsh.");
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(0u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 0u64)
+                };
+                let mut x: u64 = near_sdk_pure::borsh::BorshDeserialize::try_from_slice(&data)
+                    .expect("Failed to deserialize callback using Borsh");
+                let data: Vec<u8> = match near_sdk_pure::env::promise_result(1u64) {
+                    near_sdk_pure::PromiseResult::Successful(x) => x,
+                    _ => panic!("Callback computation {} was not successful", 1u64)
+                };
+                let z: Vec<u8> =
+                    near_sdk_pure::serde_json::from_slice(&data).expect("Failed to deserialize callback using JSON");
+                let contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method(&mut x, y, z, );
+                near_sdk_pure::test_utils::finish_call();
+            }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
@@ -556,15 +853,90 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                near_sdk_pure::env::reset_context_cache();
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.method();
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.method();
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
     }
 
+    #[test]
+    fn args_return_mut_payable_storage() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(storage)]
+            pub fn method(&mut self, k: u64) -> u64 { }
+        };
+        let method_info = ImplItemMethodInfo::new(&mut method, impl_type).unwrap();
+        let actual = method_info.method_wrapper();
+        let expected = quote!(
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let __storage_guard = near_sdk_pure::StorageGuard::new();
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                let result = contract.method(k,);
+                let result = near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk_pure::env::value_return(&result);
+                near_sdk_pure::ContractState::state_write(&contract);
+                __storage_guard.finish();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn method() {
+                near_sdk_pure::env::reset_context_cache();
+                let __storage_guard = near_sdk_pure::StorageGuard::new();
+                #[derive(near_sdk_pure :: serde :: Deserialize)]
+                #[serde(crate = "near_sdk_pure::serde")]
+                struct Input {
+                    k: u64,
+                }
+                let Input { k, }: Input = near_sdk_pure::serde_json::from_slice(
+                    &near_sdk_pure::env::input().expect("Expected input since method has arguments.")
+                )
+                .expect("Failed to deserialize input from JSON.");
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                let result = contract.method(k,);
+                let result = near_sdk_pure::serde_json::to_vec(&result).expect("Failed to serialize the return value using JSON.");
+                near_sdk_pure::env::value_return(&result);
+                near_sdk_pure::ContractState::state_write(&contract);
+                __storage_guard.finish();
+                near_sdk_pure::test_utils::finish_call();
+            }
+        );
+        assert_eq!(expected.to_string(), actual.to_string());
+    }
+
+    #[test]
+    fn payable_unknown_option() {
+        let impl_type: Type = syn::parse_str("Hello").unwrap();
+        let mut method: ImplItemMethod = parse_quote! {
+            #[payable(gas)]
+            pub fn method(&mut self) { }
+        };
+        let err = ImplItemMethodInfo::new(&mut method, impl_type).err().unwrap();
+        assert_eq!(err.to_string(), "Expected `#[payable]` or `#[payable(storage)]`.");
+    }
+
     #[test]
     fn private_method() {
         let impl_type: Type = syn::parse_str("Hello").unwrap();
@@ -575,16 +947,30 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn private_method() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-                if env::current_account_id() != env::predecessor_account_id() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                    near_sdk_pure::env::panic("Method private_method is private".as_bytes());
+                }
+                if near_sdk_pure::env::attached_deposit() != 0 {
+                    near_sdk_pure::env::panic("Method private_method doesn't accept deposit".as_bytes());
+                }
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
+                contract.private_method();
+                near_sdk_pure::ContractState::state_write(&contract);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn private_method() {
+                near_sdk_pure::env::reset_context_cache();
+                if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
                     near_sdk_pure::env::panic("Method private_method is private".as_bytes());
                 }
                 if near_sdk_pure::env::attached_deposit() != 0 {
                     near_sdk_pure::env::panic("Method private_method doesn't accept deposit".as_bytes());
                 }
-                let mut contract: Hello = near_sdk_pure::env::state_read().unwrap_or_default();
+                let mut contract: Hello = near_sdk_pure::ContractState::state_read().unwrap_or_default();
                 contract.private_method();
-                near_sdk_pure::env::state_write(&contract);
+                near_sdk_pure::ContractState::state_write(&contract);
+                near_sdk_pure::test_utils::finish_call();
             }
         );
         assert_eq!(expected.to_string(), actual.to_string());
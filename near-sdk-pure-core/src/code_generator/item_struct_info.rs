@@ -1,4 +1,6 @@
-use syn::ItemStruct;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_quote, AttributeArgs, Ident, ItemStruct, Lit, LitInt, Meta, NestedMeta, Path};
 
 #[cfg(not(target_arch = "wasm"))]
 pub fn generate_proxy_struct(input: &ItemStruct) -> proc_macro2::TokenStream {
@@ -17,3 +19,295 @@ pub fn generate_proxy_struct(input: &ItemStruct) -> proc_macro2::TokenStream {
 pub fn generate_proxy_struct(input: &ItemStruct) {
     quote! {}
 }
+
+/// Generates the `ContractState` implementation of the `#[near_bindgen]` struct. With
+/// `#[near_bindgen(version = N, migrate_from(StateV1 = 1, ...))]` the state is stored together
+/// with the version, so the `Migrate` implementation and the private `migrate` method that
+/// converts the stored state into the current version are generated too. The method is renamed
+/// with `migrate_method = "name"`, e.g. when the contract has a method called `migrate` already.
+pub fn generate_contract_state(
+    args: &AttributeArgs,
+    input: &ItemStruct,
+) -> syn::Result<TokenStream2> {
+    let mut version: Option<LitInt> = None;
+    let mut migrate_from: Vec<(Path, LitInt)> = vec![];
+    // The same as `near_sdk_pure::upgrade::DEFAULT_MIGRATE_METHOD`.
+    let mut migrate_method = Ident::new("migrate", Span::call_site());
+    for arg in args {
+        match arg {
+            NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("version") => {
+                version = Some(int_lit(&pair.lit)?);
+            }
+            NestedMeta::Meta(Meta::NameValue(pair)) if pair.path.is_ident("migrate_method") => {
+                migrate_method = match &pair.lit {
+                    Lit::Str(value) => value.parse()?,
+                    lit => return Err(syn::Error::new_spanned(lit, "Expected a string literal.")),
+                };
+            }
+            NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("migrate_from") => {
+                for nested in &list.nested {
+                    match nested {
+                        NestedMeta::Meta(Meta::NameValue(pair)) => {
+                            migrate_from.push((pair.path.clone(), int_lit(&pair.lit)?));
+                        }
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                nested,
+                                "Expected `StateType = version`.",
+                            ))
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "Expected `version = N`, `migrate_from(StateType = version, ...)` or `migrate_method = \"name\"`.",
+                ))
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let version = match version {
+        Some(version) => version,
+        None if migrate_from.is_empty() && input.generics.params.is_empty() => {
+            return Ok(quote! {
+                impl near_sdk_pure::ContractState for #name {}
+            });
+        }
+        None if migrate_from.is_empty() => {
+            // The struct is only the state if its type parameters can be serialized.
+            let mut generics = input.generics.clone();
+            generics.make_where_clause().predicates.push(parse_quote! {
+                #name #ty_generics: near_sdk_pure::borsh::BorshSerialize
+                    + near_sdk_pure::borsh::BorshDeserialize
+            });
+            let (_, _, where_clause) = generics.split_for_impl();
+            return Ok(quote! {
+                impl #impl_generics near_sdk_pure::ContractState for #name #ty_generics #where_clause {}
+            });
+        }
+        None => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "near_bindgen requires the `version` argument with `migrate_from`.",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "Versioned state is not supported for structs with type parameters.",
+        ));
+    }
+
+    let migrate = if migrate_from.is_empty() {
+        quote! {
+            fn migrate(_version: u32, _data: &[u8]) -> Option<Self> {
+                None
+            }
+        }
+    } else {
+        let arms = migrate_from.iter().map(|(state, version)| {
+            quote! {
+                #version => Some(Self::from(
+                    <#state as near_sdk_pure::borsh::BorshDeserialize>::try_from_slice(data)
+                        .expect("Cannot deserialize the contract state.")
+                )),
+            }
+        });
+        quote! {
+            fn migrate(version: u32, data: &[u8]) -> Option<Self> {
+                match version {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        }
+    };
+    let proxy_name = format_ident!("{}Contract", name);
+    let migrate_name = migrate_method.to_string();
+    let private_panic = format!("Method {} is private", migrate_name);
+    let deposit_panic = format!("Method {} doesn't accept deposit", migrate_name);
+    let migrate_body = quote! {
+        near_sdk_pure::env::reset_context_cache();
+        if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+            near_sdk_pure::env::panic(#private_panic.as_bytes());
+        }
+        if near_sdk_pure::env::attached_deposit() != 0 {
+            near_sdk_pure::env::panic(#deposit_panic.as_bytes());
+        }
+        near_sdk_pure::state::migrate_state::<#name>();
+    };
+    Ok(quote! {
+        impl near_sdk_pure::Migrate for #name {
+            const VERSION: u32 = #version;
+            #migrate
+        }
+        impl near_sdk_pure::ContractState for #name {
+            fn state_read() -> Option<Self> {
+                near_sdk_pure::state::versioned_state_read()
+            }
+            fn state_write(&self) {
+                near_sdk_pure::state::versioned_state_write(self)
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn #migrate_method() {
+            #migrate_body
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn #migrate_method() {
+            #migrate_body
+            near_sdk_pure::test_utils::finish_call();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        impl #proxy_name {
+            pub fn #migrate_method(&self) -> near_sdk_pure::PendingContractTx {
+                let args = near_sdk_pure::serde_json::json!({});
+                near_sdk_pure::PendingContractTx::new(&self.account_id, #migrate_name, args, false)
+            }
+        }
+    })
+}
+
+fn int_lit(lit: &Lit) -> syn::Result<LitInt> {
+    match lit {
+        Lit::Int(value) => {
+            value.base10_parse::<u32>()?;
+            Ok(value.clone())
+        }
+        _ => Err(syn::Error::new_spanned(lit, "Expected an integer literal.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse::Parser;
+    use syn::punctuated::Punctuated;
+    use syn::Token;
+
+    fn args(tokens: TokenStream2) -> AttributeArgs {
+        Punctuated::<NestedMeta, Token![,]>::parse_terminated
+            .parse2(tokens)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    fn input() -> ItemStruct {
+        syn::parse2(quote! {
+            pub struct Counter {
+                value: u64,
+            }
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn contract_state() {
+        let actual = generate_contract_state(&args(quote! {}), &input()).unwrap();
+        let expected = quote! {
+            impl near_sdk_pure::ContractState for Counter {}
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn versioned_contract_state() {
+        let actual = generate_contract_state(
+            &args(quote! { version = 3, migrate_from(CounterV1 = 1, old::CounterV2 = 2) }),
+            &input(),
+        )
+        .unwrap();
+        let migrate_body = quote! {
+            near_sdk_pure::env::reset_context_cache();
+            if near_sdk_pure::env::current_account_id() != near_sdk_pure::env::predecessor_account_id() {
+                near_sdk_pure::env::panic("Method migrate is private".as_bytes());
+            }
+            if near_sdk_pure::env::attached_deposit() != 0 {
+                near_sdk_pure::env::panic("Method migrate doesn't accept deposit".as_bytes());
+            }
+            near_sdk_pure::state::migrate_state::<Counter>();
+        };
+        let expected = quote! {
+            impl near_sdk_pure::Migrate for Counter {
+                const VERSION: u32 = 3;
+                fn migrate(version: u32, data: &[u8]) -> Option<Self> {
+                    match version {
+                        1 => Some(Self::from(
+                            <CounterV1 as near_sdk_pure::borsh::BorshDeserialize>::try_from_slice(data)
+                                .expect("Cannot deserialize the contract state.")
+                        )),
+                        2 => Some(Self::from(
+                            <old::CounterV2 as near_sdk_pure::borsh::BorshDeserialize>::try_from_slice(data)
+                                .expect("Cannot deserialize the contract state.")
+                        )),
+                        _ => None,
+                    }
+                }
+            }
+            impl near_sdk_pure::ContractState for Counter {
+                fn state_read() -> Option<Self> {
+                    near_sdk_pure::state::versioned_state_read()
+                }
+                fn state_write(&self) {
+                    near_sdk_pure::state::versioned_state_write(self)
+                }
+            }
+            #[cfg(target_arch = "wasm32")]
+            #[no_mangle]
+            pub extern "C" fn migrate() {
+                #migrate_body
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            pub fn migrate() {
+                #migrate_body
+                near_sdk_pure::test_utils::finish_call();
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            impl CounterContract {
+                pub fn migrate(&self) -> near_sdk_pure::PendingContractTx {
+                    let args = near_sdk_pure::serde_json::json!({});
+                    near_sdk_pure::PendingContractTx::new(&self.account_id, "migrate", args, false)
+                }
+            }
+        };
+        assert_eq!(actual.to_string(), expected.to_string());
+
+        let error =
+            generate_contract_state(&args(quote! { migrate_from(CounterV1 = 1) }), &input());
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "near_bindgen requires the `version` argument with `migrate_from`."
+        );
+        let error = generate_contract_state(&args(quote! { version = "2" }), &input());
+        assert_eq!(error.unwrap_err().to_string(), "Expected an integer literal.");
+    }
+
+    #[test]
+    fn renamed_migrate_method() {
+        let actual = generate_contract_state(
+            &args(quote! { version = 2, migrate_method = "migrate_state" }),
+            &input(),
+        )
+        .unwrap()
+        .to_string();
+        let export = quote! { pub extern "C" fn migrate_state() };
+        assert!(actual.contains(&export.to_string()));
+        let tx = quote! {
+            near_sdk_pure::PendingContractTx::new(&self.account_id, "migrate_state", args, false)
+        };
+        assert!(actual.contains(&tx.to_string()));
+        assert!(actual.contains("\"Method migrate_state is private\""));
+        assert!(!actual.contains("fn migrate ()"));
+
+        let error =
+            generate_contract_state(&args(quote! { version = 2, migrate_method = 1 }), &input());
+        assert_eq!(error.unwrap_err().to_string(), "Expected a string literal.");
+    }
+}
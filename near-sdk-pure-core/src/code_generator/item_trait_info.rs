@@ -14,7 +14,7 @@ impl ItemTraitInfo {
            pub mod #mod_name {
                 use super::*;
                 use near_sdk_pure::{Gas, Balance, AccountId, Promise};
-                use alloc::string::{String, ToString};
+                use alloc::string::String;
                 #result
             }
         }
@@ -54,10 +54,10 @@ mod tests {
             pub mod external_cross_contract {
                 use super::*;
                 use near_sdk_pure::{Gas, Balance, AccountId, Promise};
-                use std::string::ToString;
-                pub fn merge_sort<T: ToString>(
+                use alloc::string::String;
+                pub fn merge_sort(
                     arr: Vec<u8>,
-                    __account_id: &T,
+                    __account_id: &near_sdk_pure::AccountId,
                     __balance: near_sdk_pure::Balance,
                     __gas: near_sdk_pure::Gas
                 ) -> near_sdk_pure::Promise {
@@ -69,16 +69,16 @@ mod tests {
                     let args = Input { arr, };
                     let args = near_sdk_pure::serde_json::to_vec(&args)
                         .expect("Failed to serialize the cross contract args using JSON.");
-                    near_sdk_pure::Promise::new(__account_id.to_string()).function_call(
+                    near_sdk_pure::Promise::new(__account_id.clone()).function_call(
                         b"merge_sort".to_vec(),
                         args,
                         __balance,
                         __gas,
                     )
                 }
-                pub fn merge<T: ToString>(__account_id: &T, __balance: near_sdk_pure::Balance, __gas: near_sdk_pure::Gas) -> near_sdk_pure::Promise {
+                pub fn merge(__account_id: &near_sdk_pure::AccountId, __balance: near_sdk_pure::Balance, __gas: near_sdk_pure::Gas) -> near_sdk_pure::Promise {
                     let args = vec![];
-                    near_sdk_pure::Promise::new(__account_id.to_string()).function_call(
+                    near_sdk_pure::Promise::new(__account_id.clone()).function_call(
                         b"merge".to_vec(),
                         args,
                         __balance,
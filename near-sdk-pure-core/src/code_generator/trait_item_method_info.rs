@@ -29,11 +29,11 @@ impl TraitItemMethodInfo {
             }
         };
         quote! {
-            pub fn #ident<T: ToString>(#pat_type_list __account_id: &T, __balance: near_sdk_pure::Balance, __gas: near_sdk_pure::Gas) -> near_sdk_pure::Promise {
+            pub fn #ident(#pat_type_list __account_id: &near_sdk_pure::AccountId, __balance: near_sdk_pure::Balance, __gas: near_sdk_pure::Gas) -> near_sdk_pure::Promise {
                 #struct_decl
                 #constructor
                 #value_ser
-                near_sdk_pure::Promise::new(__account_id.to_string())
+                near_sdk_pure::Promise::new(__account_id.clone())
                 .function_call(
                     #ident_byte_str.to_vec(),
                     args,
@@ -18,6 +18,9 @@ pub struct AttrSigInfo {
     pub is_init: bool,
     /// Whether method accepting $NEAR.
     pub is_payable: bool,
+    /// Whether the storage that the method adds is paid from the attached deposit, with
+    /// `#[payable(storage)]`, see `StorageGuard`.
+    pub is_payable_storage: bool,
     /// Whether method can accept calls from self (current account)
     pub is_private: bool,
     /// The serializer that we use for `env::input()`.
@@ -64,6 +67,7 @@ impl AttrSigInfo {
         let mut args = vec![];
         let mut is_init = false;
         let mut is_payable = false;
+        let mut is_payable_storage = false;
         let mut is_private = false;
         // By the default we serialize the result with JSON.
         let mut result_serializer = SerializerType::JSON;
@@ -78,6 +82,16 @@ impl AttrSigInfo {
                 "payable" => {
                     payable_attr = Some(attr);
                     is_payable = true;
+                    if !attr.tokens.is_empty() {
+                        let ident: Ident = attr.parse_args()?;
+                        if ident != "storage" {
+                            return Err(Error::new(
+                                ident.span(),
+                                "Expected `#[payable]` or `#[payable(storage)]`.",
+                            ));
+                        }
+                        is_payable_storage = true;
+                    }
                 }
                 "private" => {
                     is_private = true;
@@ -134,6 +148,7 @@ impl AttrSigInfo {
             input_serializer: SerializerType::JSON,
             is_init,
             is_payable,
+            is_payable_storage,
             is_private,
             is_view,
             result_serializer,
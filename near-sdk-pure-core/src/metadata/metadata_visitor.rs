@@ -42,9 +42,6 @@ impl MetadataVisitor {
         if !self.errors.is_empty() {
             return Err(self.errors[0].clone());
         }
-        let env_creation = quote! {
-            near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
-        };
         let methods: Vec<TokenStream2> = self
             .impl_item_infos
             .iter()
@@ -55,7 +52,6 @@ impl MetadataVisitor {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn metadata() {
-                #env_creation
                 use borsh::*;
                 let metadata = near_sdk_pure::Metadata::new(vec![
                     #(#methods),*
@@ -98,7 +94,6 @@ mod tests {
             #[cfg(target_arch = "wasm32")]
             #[no_mangle]
             pub extern "C" fn metadata() {
-                near_sdk_pure::env::set_blockchain_interface(::alloc::boxed::Box::new(near_blockchain::NearBlockchain {}));
                 use borsh::*;
                 let metadata = near_sdk_pure::Metadata::new(vec![
                     near_sdk_pure::MethodMetadata {
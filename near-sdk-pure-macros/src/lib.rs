@@ -7,19 +7,26 @@ use near_sdk_pure_core::*;
 use proc_macro2::Span;
 use quote::quote;
 use syn::visit::Visit;
-use syn::{File, ItemImpl, ItemStruct, ItemTrait};
+use syn::{File, ItemEnum, ItemImpl, ItemStruct, ItemTrait};
 
+/// `near_bindgen` on the contract struct stores it as the contract state, and on its impl sections
+/// exports the public methods. `#[near_bindgen(version = N, migrate_from(StateV1 = 1, ...))]` on
+/// the struct stores the state together with the version and converts the states of the listed
+/// older versions on read, see `Migrate`. It also exports the private `migrate` method that
+/// converts the stored state, renamed with `migrate_method = "name"`.
 #[proc_macro_attribute]
-pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn near_bindgen(attr: TokenStream, item: TokenStream) -> TokenStream {
     if let Ok(input) = syn::parse::<ItemStruct>(item.clone()) {
-        let sys_file = rust_file(include_bytes!("../res/sys.rs"));
-        let near_environment = rust_file(include_bytes!("../res/near_blockchain.rs"));
+        let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
         let struct_proxy = generate_proxy_struct(&input);
+        let contract_state = match generate_contract_state(&args, &input) {
+            Ok(x) => x,
+            Err(err) => return TokenStream::from(err.to_compile_error()),
+        };
         TokenStream::from(quote! {
             #input
             #struct_proxy
-            #sys_file
-            #near_environment
+            #contract_state
         })
     } else if let Ok(mut input) = syn::parse::<ItemImpl>(item) {
         let item_impl_info = match ItemImplInfo::new(&mut input) {
@@ -47,11 +54,6 @@ pub fn near_bindgen(_attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
-fn rust_file(data: &[u8]) -> File {
-    let data = std::str::from_utf8(data).unwrap();
-    syn::parse_file(data).unwrap()
-}
-
 #[proc_macro_attribute]
 pub fn ext_contract(attr: TokenStream, item: TokenStream) -> TokenStream {
     if let Ok(mut input) = syn::parse::<ItemTrait>(item) {
@@ -84,6 +86,26 @@ pub fn ext_contract(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// `near_event` makes the enum a `NearEvent` of the standard and the version given as
+/// `#[near_event(standard = "nep171", version = "1.0.0")]`. Its variants are the events, logged by
+/// `emit` as `EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":...}`
+/// with the variant name in snake case and its fields serialized with serde as the data.
+#[proc_macro_attribute]
+pub fn near_event(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    if let Ok(input) = syn::parse::<ItemEnum>(item) {
+        match generate_near_event(&args, &input) {
+            Ok(x) => x.into(),
+            Err(err) => TokenStream::from(err.to_compile_error()),
+        }
+    } else {
+        TokenStream::from(
+            syn::Error::new(Span::call_site(), "near_event can only be used on enums")
+                .to_compile_error(),
+        )
+    }
+}
+
 // The below attributes a marker-attributes and therefore they are no-op.
 
 /// `callback` is a marker attribute it does not generate code by itself.
@@ -16,4 +16,6 @@ fn compilation_tests() {
     t.pass("compilation_tests/lifetime_method.rs");
     t.pass("compilation_tests/cond_compilation.rs");
     t.compile_fail("compilation_tests/payable_view.rs");
+    t.pass("compilation_tests/migrate_method.rs");
+    t.compile_fail("compilation_tests/migrate_collision.rs");
 }
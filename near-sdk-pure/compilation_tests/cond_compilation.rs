@@ -9,14 +9,16 @@ struct Incrementer {
     value: u32,
 }
 
-#[near_bindgen(init => new)]
+#[near_bindgen]
 impl Incrementer {
     #[cfg(feature = "myfeature")]
+    #[init]
     pub fn new() -> Self {
         Self {value: 0}
     }
 
     #[cfg(not(feature = "myfeature"))]
+    #[init]
     pub fn new() -> Self {
         Self {value: 1}
     }
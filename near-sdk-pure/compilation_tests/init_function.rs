@@ -1,10 +1,10 @@
 //! Smart contract with initialization function.
 
-use near_sdk_pure::near_bindgen;
+use near_sdk_pure::{near_bindgen, PanicOnDefault};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 #[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize)]
+#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
 struct Incrementer {
     value: u32,
 }
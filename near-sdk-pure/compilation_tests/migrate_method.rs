@@ -0,0 +1,21 @@
+//! Versioned contract with its own method called `migrate` and the generated migrate method
+//! renamed.
+
+use near_sdk_pure::near_bindgen;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+#[near_bindgen(version = 2, migrate_method = "migrate_state")]
+#[derive(BorshDeserialize, BorshSerialize)]
+struct Incrementer {
+    value: u32,
+}
+
+#[near_bindgen]
+impl Incrementer {
+    #[init]
+    pub fn migrate() -> Self {
+        Self { value: 0 }
+    }
+}
+
+fn main() {}
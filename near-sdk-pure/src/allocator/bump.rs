@@ -0,0 +1,92 @@
+use core::alloc::{GlobalAlloc, Layout};
+use core::ptr;
+
+use super::{align_up, Lock, Memory, WasmMemory, PAGE_SIZE};
+
+/// Allocator that hands out the memory in order and never frees it. A contract call is short and
+/// its memory is dropped with the instance, so the freed memory is rarely worth reusing, and this
+/// is the smallest and fastest allocator.
+pub struct BumpAllocator<M = WasmMemory> {
+    memory: M,
+    /// The next free address and the end of the memory taken so far.
+    state: Lock<(usize, usize)>,
+}
+
+impl<M> BumpAllocator<M> {
+    pub const fn new(memory: M) -> Self {
+        Self { memory, state: Lock::new((0, 0)) }
+    }
+}
+
+unsafe impl<M: Memory> GlobalAlloc for BumpAllocator<M> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.state.with(|(next, end)| {
+            let fits = |next: usize, end: usize| {
+                let start = align_up(next, layout.align())?;
+                let new_next = start.checked_add(layout.size())?;
+                if new_next <= end {
+                    Some((start, new_next))
+                } else {
+                    None
+                }
+            };
+            if fits(*next, *end).is_none() {
+                // Enough pages for the allocation even if they do not follow the current end.
+                let pages = match layout.size().checked_add(layout.align()) {
+                    Some(size) => size.div_ceil(PAGE_SIZE),
+                    None => return ptr::null_mut(),
+                };
+                let start = match self.memory.grow(pages) {
+                    Some(start) => start,
+                    None => return ptr::null_mut(),
+                };
+                if start != *end {
+                    *next = start;
+                }
+                *end = start + pages * PAGE_SIZE;
+            }
+            match fits(*next, *end) {
+                Some((start, new_next)) => {
+                    *next = new_next;
+                    start as *mut u8
+                }
+                None => ptr::null_mut(),
+            }
+        })
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::TestMemory;
+
+    #[test]
+    fn test_bump_allocator() {
+        let allocator = BumpAllocator::new(TestMemory::new(4));
+        unsafe {
+            let a = allocator.alloc(Layout::from_size_align(3, 1).unwrap());
+            let b = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(b as usize % 8, 0);
+            assert!(b as usize >= a as usize + 3);
+            // The freed memory is not reused.
+            allocator.dealloc(b, Layout::from_size_align(8, 8).unwrap());
+            let c = allocator.alloc(Layout::from_size_align(8, 8).unwrap());
+            assert_eq!(c as usize, b as usize + 8);
+            ptr::write_bytes(c, 0xab, 8);
+
+            // The allocations that do not fit into the current page take the following ones.
+            let large = allocator.alloc(Layout::from_size_align(PAGE_SIZE + 1, 4096).unwrap());
+            assert_eq!(large as usize % 4096, 0);
+            assert!(large as usize > c as usize);
+            ptr::write_bytes(large, 0xcd, PAGE_SIZE + 1);
+            assert_eq!(*c.add(7), 0xab);
+
+            // The memory has four pages.
+            assert!(allocator.alloc(Layout::from_size_align(2 * PAGE_SIZE, 1).unwrap()).is_null());
+            assert!(!allocator.alloc(Layout::from_size_align(PAGE_SIZE / 2, 1).unwrap()).is_null());
+        }
+    }
+}
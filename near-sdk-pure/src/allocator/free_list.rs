@@ -0,0 +1,193 @@
+use core::alloc::{GlobalAlloc, Layout};
+use core::mem::size_of;
+use core::ptr;
+
+use super::{align_up, Lock, Memory, WasmMemory, PAGE_SIZE};
+
+/// Granularity of the blocks, large enough to hold a `Node` in every free block.
+const BLOCK_SIZE: usize = 16;
+
+/// Header of a free block, stored at its start.
+struct Node {
+    size: usize,
+    /// Address of the next free block, or zero for the last one.
+    next: usize,
+}
+
+/// First-fit allocator that keeps the free blocks in a list ordered by the address and merges the
+/// adjacent ones, so the memory freed by a call is reused. The blocks are multiples of 16 bytes
+/// and have no header while allocated, as `dealloc` is given the layout.
+pub struct FreeListAllocator<M = WasmMemory> {
+    memory: M,
+    /// Address of the first free block, or zero if there are none.
+    head: Lock<usize>,
+}
+
+impl<M> FreeListAllocator<M> {
+    pub const fn new(memory: M) -> Self {
+        Self { memory, head: Lock::new(0) }
+    }
+}
+
+const _: () = assert!(size_of::<Node>() <= BLOCK_SIZE);
+
+/// Size and alignment of the block that holds the allocation, `None` on overflow.
+fn block_layout(layout: &Layout) -> Option<(usize, usize)> {
+    Some((align_up(layout.size().max(1), BLOCK_SIZE)?, layout.align().max(BLOCK_SIZE)))
+}
+
+unsafe fn node<'a>(address: usize) -> &'a mut Node {
+    &mut *(address as *mut Node)
+}
+
+/// Adds the block to the list, merging it with the adjacent free blocks.
+unsafe fn insert(head: &mut usize, address: usize, size: usize) {
+    let mut previous = 0;
+    let mut next = *head;
+    while next != 0 && next < address {
+        previous = next;
+        next = node(next).next;
+    }
+    let block = node(address);
+    *block = Node { size, next };
+    if next != 0 && address + size == next {
+        let merged = node(next);
+        block.size += merged.size;
+        block.next = merged.next;
+    }
+    if previous == 0 {
+        *head = address;
+    } else if previous + node(previous).size == address {
+        let (size, next) = (block.size, block.next);
+        let previous = node(previous);
+        previous.size += size;
+        previous.next = next;
+    } else {
+        node(previous).next = address;
+    }
+}
+
+/// Removes the first block that can hold the allocation from the list and returns its start,
+/// putting back the parts of the block before and after the allocation.
+unsafe fn take(head: &mut usize, size: usize, align: usize) -> Option<usize> {
+    let mut previous = 0;
+    let mut current = *head;
+    while current != 0 {
+        let Node { size: block_size, next } = *node(current);
+        let start = align_up(current, align)?;
+        if start.checked_add(size).is_some_and(|end| end <= current + block_size) {
+            if previous == 0 {
+                *head = next;
+            } else {
+                node(previous).next = next;
+            }
+            if start > current {
+                insert(head, current, start - current);
+            }
+            if start + size < current + block_size {
+                insert(head, start + size, current + block_size - start - size);
+            }
+            return Some(start);
+        }
+        previous = current;
+        current = next;
+    }
+    None
+}
+
+unsafe impl<M: Memory> GlobalAlloc for FreeListAllocator<M> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let (size, align) = match block_layout(&layout) {
+            Some(block) => block,
+            None => return ptr::null_mut(),
+        };
+        self.head.with(|head| {
+            if let Some(start) = take(head, size, align) {
+                return start as *mut u8;
+            }
+            // Enough pages for the allocation even if they are not merged with the last block.
+            let pages = match size.checked_add(align) {
+                Some(size) => size.div_ceil(PAGE_SIZE),
+                None => return ptr::null_mut(),
+            };
+            match self.memory.grow(pages) {
+                Some(start) => {
+                    insert(head, start, pages * PAGE_SIZE);
+                    take(head, size, align).map_or(ptr::null_mut(), |start| start as *mut u8)
+                }
+                None => ptr::null_mut(),
+            }
+        })
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if let Some((size, _)) = block_layout(&layout) {
+            self.head.with(|head| insert(head, ptr as usize, size));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::TestMemory;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_reuse_and_merge() {
+        let allocator = FreeListAllocator::new(TestMemory::new(2));
+        let layout = Layout::from_size_align(100, 8).unwrap();
+        unsafe {
+            let a = allocator.alloc(layout);
+            let b = allocator.alloc(layout);
+            let c = allocator.alloc(layout);
+            assert_eq!(b as usize, a as usize + 112);
+            assert_eq!(c as usize, b as usize + 112);
+
+            // The freed block is reused.
+            allocator.dealloc(b, layout);
+            assert_eq!(allocator.alloc(Layout::from_size_align(16, 1).unwrap()), b);
+            allocator.dealloc(b, Layout::from_size_align(16, 1).unwrap());
+
+            // The adjacent free blocks merge, whatever order they are freed in.
+            allocator.dealloc(a, layout);
+            allocator.dealloc(c, layout);
+            assert_eq!(allocator.alloc(Layout::from_size_align(PAGE_SIZE, 1).unwrap()), a);
+            allocator.dealloc(a, Layout::from_size_align(PAGE_SIZE, 1).unwrap());
+
+            let aligned = allocator.alloc(Layout::from_size_align(10, 4096).unwrap());
+            assert_eq!(aligned as usize % 4096, 0);
+            // The memory has two pages.
+            assert!(allocator.alloc(Layout::from_size_align(2 * PAGE_SIZE, 1).unwrap()).is_null());
+        }
+    }
+
+    #[test]
+    fn test_random_allocations() {
+        let allocator = FreeListAllocator::new(TestMemory::new(64));
+        let mut live: Vec<(*mut u8, Layout, u8)> = Vec::new();
+        let mut seed = 1u64;
+        let mut random = |bound: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % bound
+        };
+        for i in 0..3000 {
+            if live.is_empty() || random(3) != 0 {
+                let size = 1 + random(1000) as usize;
+                let layout = Layout::from_size_align(size, 1 << random(7)).unwrap();
+                let ptr = unsafe { allocator.alloc(layout) };
+                assert!(!ptr.is_null());
+                assert_eq!(ptr as usize % layout.align(), 0);
+                unsafe { ptr::write_bytes(ptr, i as u8, size) };
+                live.push((ptr, layout, i as u8));
+            } else {
+                let (ptr, layout, value) = live.swap_remove(random(live.len() as u64) as usize);
+                // Nothing else was written over the allocation.
+                for offset in 0..layout.size() {
+                    assert_eq!(unsafe { *ptr.add(offset) }, value);
+                }
+                unsafe { allocator.dealloc(ptr, layout) };
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Allocators for the `no_std` contracts that can replace `wee_alloc` through `setup_alloc!`. They
+//! take the memory from the host in pages, like the `memory.grow` instruction of `wasm32` does.
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "bump-allocator")]
+mod bump;
+#[cfg(feature = "bump-allocator")]
+pub use bump::BumpAllocator;
+
+#[cfg(feature = "free-list-allocator")]
+mod free_list;
+#[cfg(feature = "free-list-allocator")]
+pub use free_list::FreeListAllocator;
+
+/// Size of a page of the `wasm32` memory.
+pub const PAGE_SIZE: usize = 64 * 1024;
+
+/// Memory that the allocators grow by pages.
+pub trait Memory {
+    /// Grows the memory by `pages` pages, returning the address of the first new page, or `None`
+    /// if the memory cannot grow.
+    fn grow(&self, pages: usize) -> Option<usize>;
+}
+
+/// The linear memory of the `wasm32` module. It cannot grow on the other targets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WasmMemory;
+
+impl Memory for WasmMemory {
+    #[cfg(target_arch = "wasm32")]
+    fn grow(&self, pages: usize) -> Option<usize> {
+        match core::arch::wasm32::memory_grow(0, pages) {
+            usize::MAX => None,
+            previous_pages => Some(previous_pages * PAGE_SIZE),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn grow(&self, _pages: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// Spin lock around the state of an allocator. The contracts run on a single thread, so it is
+/// never contended there, but it keeps the allocators sound on the host.
+struct Lock<T> {
+    locked: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for Lock<T> {}
+
+impl<T> Lock<T> {
+    const fn new(value: T) -> Self {
+        Self { locked: AtomicBool::new(false), value: UnsafeCell::new(value) }
+    }
+
+    fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = f(unsafe { &mut *self.value.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+/// Rounds `value` up to a multiple of `align`, which is a power of two. `None` on overflow.
+fn align_up(value: usize, align: usize) -> Option<usize> {
+    Some(value.checked_add(align - 1)? & !(align - 1))
+}
+
+/// Memory of a fixed number of pages in a host buffer, for testing the allocators.
+#[cfg(test)]
+pub(crate) struct TestMemory {
+    _buffer: alloc::vec::Vec<u8>,
+    start: usize,
+    pages: usize,
+    used: core::cell::Cell<usize>,
+}
+
+#[cfg(test)]
+impl TestMemory {
+    pub fn new(pages: usize) -> Self {
+        let buffer = alloc::vec![0u8; (pages + 1) * PAGE_SIZE];
+        let start = align_up(buffer.as_ptr() as usize, PAGE_SIZE).unwrap();
+        Self { _buffer: buffer, start, pages, used: core::cell::Cell::new(0) }
+    }
+}
+
+#[cfg(test)]
+impl Memory for TestMemory {
+    fn grow(&self, pages: usize) -> Option<usize> {
+        let used = self.used.get();
+        if used + pages > self.pages {
+            return None;
+        }
+        self.used.set(used + pages);
+        Some(self.start + used * PAGE_SIZE)
+    }
+}
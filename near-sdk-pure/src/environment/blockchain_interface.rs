@@ -26,6 +26,11 @@ pub trait BlockchainInterface {
     unsafe fn attached_deposit(&self, balance_ptr: u64);
     unsafe fn prepaid_gas(&self) -> u64;
     unsafe fn used_gas(&self) -> u64;
+    /// The balance locked for every byte of the storage usage. The runtime has no host function
+    /// for it, so it is the protocol constant unless the interface mocks another fee schedule.
+    fn storage_byte_cost(&self) -> u128 {
+        crate::environment::env::STORAGE_PRICE_PER_BYTE
+    }
     // ############
     // # Math API #
     // ############
@@ -33,6 +38,35 @@ pub trait BlockchainInterface {
     unsafe fn sha256(&self, value_len: u64, value_ptr: u64, register_id: u64);
     unsafe fn keccak256(&self, value_len: u64, value_ptr: u64, register_id: u64);
     unsafe fn keccak512(&self, value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "ripemd160")]
+    unsafe fn ripemd160(&self, value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "ecrecover")]
+    unsafe fn ecrecover(
+        &self,
+        hash_len: u64,
+        hash_ptr: u64,
+        sig_len: u64,
+        sig_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64,
+    ) -> u64;
+    #[cfg(feature = "ed25519-verify")]
+    unsafe fn ed25519_verify(
+        &self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64;
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_g1_multiexp(&self, value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_g1_sum(&self, value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_pairing_check(&self, value_len: u64, value_ptr: u64) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -95,6 +129,18 @@ pub trait BlockchainInterface {
         amount_ptr: u64,
         gas: u64,
     );
+    #[cfg(feature = "function-call-weight")]
+    unsafe fn promise_batch_action_function_call_weight(
+        &self,
+        promise_index: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+    );
     unsafe fn promise_batch_action_transfer(&self, promise_index: u64, amount_ptr: u64);
     unsafe fn promise_batch_action_stake(
         &self,
@@ -159,4 +205,12 @@ pub trait BlockchainInterface {
     // ###############
     unsafe fn validator_stake(&self, account_id_len: u64, account_id_ptr: u64, stake_ptr: u64);
     unsafe fn validator_total_stake(&self, stake_ptr: u64);
+    // ###########
+    // # Testing #
+    // ###########
+    /// Returns the interface as a `MockedBlockchain` if it is one, so that tests can inspect it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn as_mocked_blockchain(&self) -> Option<&crate::test_utils::MockedBlockchain> {
+        None
+    }
 }
@@ -0,0 +1,83 @@
+//! Values of the call context that do not change during the call, kept by `env` after the first
+//! read from the host. The cache is reset by the entry points generated by `#[near_bindgen]` and
+//! whenever the blockchain interface is replaced, so the tests that change the context between
+//! calls see the new values.
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use crate::types::{AccountId, Balance, BlockHeight, Gas, PublicKey};
+
+pub(crate) struct ContextCache {
+    pub current_account_id: Option<AccountId>,
+    pub signer_account_id: Option<AccountId>,
+    pub signer_account_pk: Option<PublicKey>,
+    pub predecessor_account_id: Option<AccountId>,
+    pub block_index: Option<BlockHeight>,
+    pub block_timestamp: Option<u64>,
+    pub epoch_height: Option<u64>,
+    pub attached_deposit: Option<Balance>,
+    pub prepaid_gas: Option<Gas>,
+    pub random_seed: Option<Vec<u8>>,
+}
+
+impl ContextCache {
+    const fn new() -> Self {
+        Self {
+            current_account_id: None,
+            signer_account_id: None,
+            signer_account_pk: None,
+            predecessor_account_id: None,
+            block_index: None,
+            block_timestamp: None,
+            epoch_height: None,
+            attached_deposit: None,
+            prepaid_gas: None,
+            random_seed: None,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+std::thread_local! {
+    /// The cache of the thread, since every thread has its own blockchain interface.
+    static CONTEXT_CACHE: RefCell<ContextCache> = const { RefCell::new(ContextCache::new()) };
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn with_cache<R>(f: impl FnOnce(&mut ContextCache) -> R) -> R {
+    CONTEXT_CACHE.with(|cache| f(&mut cache.borrow_mut()))
+}
+
+/// The cache of the contract, which runs in a single thread on `wasm32`.
+#[cfg(target_arch = "wasm32")]
+struct WasmContextCache(RefCell<ContextCache>);
+
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for WasmContextCache {}
+
+#[cfg(target_arch = "wasm32")]
+static CONTEXT_CACHE: WasmContextCache = WasmContextCache(RefCell::new(ContextCache::new()));
+
+#[cfg(target_arch = "wasm32")]
+fn with_cache<R>(f: impl FnOnce(&mut ContextCache) -> R) -> R {
+    f(&mut CONTEXT_CACHE.0.borrow_mut())
+}
+
+/// Clears the cached values.
+pub(crate) fn reset() {
+    with_cache(|cache| *cache = ContextCache::new());
+}
+
+/// Returns the cached value of the `field`, reading and caching it with `read` if it is not cached
+/// yet. The cache is not borrowed while `read` runs, so `read` may use the environment.
+pub(crate) fn cached<T: Clone, E>(
+    field: fn(&mut ContextCache) -> &mut Option<T>,
+    read: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    if let Some(value) = with_cache(|cache| field(cache).clone()) {
+        return Ok(value);
+    }
+    let value = read()?;
+    with_cache(|cache| *field(cache) = Some(value.clone()));
+    Ok(value)
+}
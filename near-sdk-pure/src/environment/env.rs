@@ -2,43 +2,57 @@
 //! low-level `BlockchainInterface`. Unless you know what you are doing prefer using `env::*`
 //! whenever possible. In case of cross-contract calls prefer using even higher-level API available
 //! through `callback_args`, `callback_args_vec`, `ext_contract`, `Promise`, and `PromiseOrValue`.
-
+//!
+//! Every function that can fail has a `try_*` variant that returns an `EnvError` instead of
+//! panicking.
+
+#[cfg(any(not(target_arch = "wasm32"), feature = "dynamic-dispatch"))]
+use alloc::boxed::Box;
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::rc::Rc;
+use alloc::{string::String, vec, vec::Vec};
 use core::borrow::Borrow;
+#[cfg(any(not(target_arch = "wasm32"), feature = "dynamic-dispatch"))]
 use core::cell::RefCell;
-use core::mem::size_of;
-use lazy_static::lazy_static;
-use alloc::{vec, vec::Vec, boxed::Box, string::String};
+use core::fmt;
+use core::mem::{size_of, size_of_val};
 
+#[cfg(feature = "function-call-weight")]
+use crate::types::GasWeight;
 use crate::types::{
     AccountId, Balance, BlockHeight, Gas, PromiseIndex, PromiseResult, PublicKey, StorageUsage,
 };
+#[cfg(feature = "alt-bn128")]
+use crate::types::{G1Point, G2Point, Scalar};
 
 use crate::environment::blockchain_interface::BlockchainInterface;
-
-
-/// Low-level blockchain interface wrapped by the environment. Prefer using `env::*`
-/// for interacting with the real and fake blockchains.
-
-pub struct BlockchainInterfaceWrapper(RefCell<Option<Box<dyn BlockchainInterface>>>);
-impl BlockchainInterfaceWrapper {
-    pub fn with<F, R>(&self, f: F) -> R
-    where
-        F: FnOnce(&RefCell<Option<Box<dyn BlockchainInterface>>>) -> R,
-    {
-        f(&self.0)
-    }
+use crate::environment::context_cache::{self, cached};
+#[cfg(all(target_arch = "wasm32", not(feature = "dynamic-dispatch")))]
+use crate::environment::NearBlockchain;
+
+#[cfg(not(target_arch = "wasm32"))]
+std::thread_local! {
+    /// Low-level blockchain interface wrapped by the environment, set separately for every thread
+    /// so that the tests running in parallel do not share it. It is reference counted, so that the
+    /// interface stays alive and the environment can be used while one of its methods is running.
+    static BLOCKCHAIN_INTERFACE: RefCell<Option<Rc<Box<dyn BlockchainInterface>>>> =
+        RefCell::new(None);
 }
 
-unsafe impl core::marker::Sync for BlockchainInterfaceWrapper {}
-unsafe impl core::marker::Send for BlockchainInterfaceWrapper {}
-
+/// Low-level blockchain interface set by the contract with the `dynamic-dispatch` feature. The
+/// contracts compiled to `wasm32` run in a single thread.
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+struct BlockchainInterfaceCell(RefCell<Option<Box<dyn BlockchainInterface>>>);
 
-lazy_static! {
-    pub static ref BLOCKCHAIN_INTERFACE: BlockchainInterfaceWrapper = BlockchainInterfaceWrapper(RefCell::new(None));
-}
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+unsafe impl Sync for BlockchainInterfaceCell {}
 
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+static BLOCKCHAIN_INTERFACE: BlockchainInterfaceCell = BlockchainInterfaceCell(RefCell::new(None));
 
 const BLOCKCHAIN_INTERFACE_NOT_SET_ERR: &str = "Blockchain interface not set.";
+#[cfg(not(target_arch = "wasm32"))]
+const BLOCKCHAIN_INTERFACE_IN_USE_ERR: &str = "Blockchain interface is in use.";
 
 const REGISTER_EXPECTED_ERR: &str =
     "Register was expected to have data because we just wrote it into it.";
@@ -52,132 +66,378 @@ const ATOMIC_OP_REGISTER: u64 = 0;
 const EVICTED_REGISTER: u64 = core::u64::MAX - 1;
 
 /// Key used to store the state of the contract.
-const STATE_KEY: &[u8] = b"STATE";
+pub(crate) const STATE_KEY: &[u8] = b"STATE";
+
+/// The balance locked for every byte of the storage, see `storage_byte_cost`.
+pub(crate) const STORAGE_PRICE_PER_BYTE: Balance = 10_000_000_000_000_000_000;
 
 /// The minimum length of a valid account ID.
 const MIN_ACCOUNT_ID_LEN: u64 = 2;
 /// The maximum length of a valid account ID.
 const MAX_ACCOUNT_ID_LEN: u64 = 64;
 
+/// Error returned by the `try_*` functions of the environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvError {
+    /// The blockchain interface is not set for the current thread.
+    InterfaceNotSet,
+    /// The register with the given id has no data, although the host has just written into it.
+    RegisterEmpty(u64),
+    /// The host function returned a code that it is not expected to return.
+    InvalidReturnCode(u64),
+    /// The account ID is not valid, see `is_valid_account_id`.
+    InvalidAccountId(Vec<u8>),
+    /// The promise with the given result index is not complete yet.
+    PromiseResultNotReady(u64),
+    /// The promise with the given result index has failed.
+    PromiseFailed(u64),
+    /// The host has written data of the given length, which is not the length of the value that
+    /// the host function returns.
+    InvalidRegisterLength(u64),
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::InterfaceNotSet => f.write_str(BLOCKCHAIN_INTERFACE_NOT_SET_ERR),
+            EnvError::RegisterEmpty(_) => f.write_str(REGISTER_EXPECTED_ERR),
+            EnvError::InvalidReturnCode(code) => write!(f, "{} {}", RETURN_CODE_ERR, code),
+            EnvError::InvalidAccountId(account_id) => {
+                write!(f, "The account ID {:?} is invalid.", String::from_utf8_lossy(account_id))
+            }
+            EnvError::PromiseResultNotReady(result_idx) => {
+                write!(f, "The promise result {} is not ready.", result_idx)
+            }
+            EnvError::PromiseFailed(result_idx) => {
+                write!(f, "The promise result {} is failed.", result_idx)
+            }
+            EnvError::InvalidRegisterLength(len) => {
+                write!(f, "The register has an unexpected length of {} bytes.", len)
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for EnvError {}
+
+/// Returns the value of the `try_*` function, panicking with the error otherwise.
+fn unwrap<T>(result: Result<T, EnvError>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+/// Interprets the return code of a host function that returns `0` or `1` as a boolean.
+fn bool_return_code(code: u64) -> Result<bool, EnvError> {
+    match code {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(EnvError::InvalidReturnCode(code)),
+    }
+}
+
+/// Reads the account ID that the host has written into the register.
+fn account_id_from_register(data: Vec<u8>) -> Result<AccountId, EnvError> {
+    if !is_valid_account_id(&data) {
+        return Err(EnvError::InvalidAccountId(data));
+    }
+    String::from_utf8(data)
+        .map(AccountId::new_unchecked)
+        .map_err(|err| EnvError::InvalidAccountId(err.into_bytes()))
+}
+
+/// Reads the register that the host has just written into.
+fn expect_register(register_id: u64) -> Result<Vec<u8>, EnvError> {
+    try_read_register(register_id)?.ok_or(EnvError::RegisterEmpty(register_id))
+}
+
+/// Reads the G1 point that an alt_bn128 host function has written into the atomic register.
+#[cfg(feature = "alt-bn128")]
+fn g1_point_from_register() -> Result<G1Point, EnvError> {
+    let bytes = expect_register(ATOMIC_OP_REGISTER)?;
+    if bytes.len() != 64 {
+        return Err(EnvError::InvalidRegisterLength(bytes.len() as u64));
+    }
+    let mut point = G1Point::default();
+    point.x.copy_from_slice(&bytes[..32]);
+    point.y.copy_from_slice(&bytes[32..64]);
+    Ok(point)
+}
+
 /// A simple macro helper to read blob value coming from host's method.
 macro_rules! try_method_into_register {
     ( $method:ident ) => {{
-        BLOCKCHAIN_INTERFACE.with(|b| unsafe {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .$method(ATOMIC_OP_REGISTER);
-        });
-        read_register(ATOMIC_OP_REGISTER)
+        try_with_interface(|b| unsafe {
+            b.$method(ATOMIC_OP_REGISTER);
+        })?;
+        try_read_register(ATOMIC_OP_REGISTER)
     }};
 }
 
 /// Same as `try_method_into_register` but expects the data.
 macro_rules! method_into_register {
     ( $method:ident ) => {{
-        try_method_into_register!($method).expect(REGISTER_EXPECTED_ERR)
+        try_method_into_register!($method)?.ok_or(EnvError::RegisterEmpty(ATOMIC_OP_REGISTER))
     }};
 }
 
-
+/// Sets the blockchain interface of the current thread.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn set_blockchain_interface(blockchain_interface: Box<dyn BlockchainInterface>) {
+    context_cache::reset();
     BLOCKCHAIN_INTERFACE.with(|b| {
-        *b.borrow_mut() = Some(blockchain_interface);
+        *b.borrow_mut() = Some(Rc::new(blockchain_interface));
     })
 }
 
+/// Removes the blockchain interface of the current thread and returns it.
+///
+/// # Panics
+///
+/// Panics if one of the methods of the interface is running.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn take_blockchain_interface() -> Option<Box<dyn BlockchainInterface>> {
+    context_cache::reset();
+    BLOCKCHAIN_INTERFACE.with(|b| b.replace(None)).map(|b| {
+        Rc::try_unwrap(b).unwrap_or_else(|_| panic!("{}", BLOCKCHAIN_INTERFACE_IN_USE_ERR))
+    })
+}
+
+/// Runs `f` with the given blockchain interface set for the current thread. The previous interface
+/// is restored afterwards, even if `f` panics.
+///
+/// ```
+/// use near_sdk_pure::env;
+/// use near_sdk_pure::test_utils::{MockedBlockchain, VMContextBuilder};
+///
+/// let context = VMContextBuilder::new().block_index(10).build();
+/// let blockchain = MockedBlockchain::new(context, Default::default(), vec![]);
+/// assert_eq!(env::with_blockchain(Box::new(blockchain), env::block_index), 10);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+pub fn with_blockchain<R, F: FnOnce() -> R>(
+    blockchain_interface: Box<dyn BlockchainInterface>,
+    f: F,
+) -> R {
+    struct Restore(Option<Rc<Box<dyn BlockchainInterface>>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            context_cache::reset();
+            BLOCKCHAIN_INTERFACE.with(|b| *b.borrow_mut() = previous);
+        }
+    }
+
+    context_cache::reset();
+    let previous = BLOCKCHAIN_INTERFACE.with(|b| b.replace(Some(Rc::new(blockchain_interface))));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Calls `f` with the blockchain interface of the current thread. The interface is not borrowed
+/// while `f` runs, so `f` may use the environment, including replacing the interface.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn try_with_interface<R, F: FnOnce(&dyn BlockchainInterface) -> R>(
+    f: F,
+) -> Result<R, EnvError> {
+    let blockchain_interface =
+        BLOCKCHAIN_INTERFACE.with(|b| b.borrow().clone()).ok_or(EnvError::InterfaceNotSet)?;
+    Ok(f(blockchain_interface.as_ref().as_ref()))
+}
+
+/// Same as `try_with_interface`, but panics if the interface is not set.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn with_interface<R, F: FnOnce(&dyn BlockchainInterface) -> R>(f: F) -> R {
+    unwrap(try_with_interface(f))
+}
 
+/// Calls `f` with the blockchain interface of the contract, which on `wasm32` is always the host,
+/// so that the host functions are called directly.
+#[cfg(all(target_arch = "wasm32", not(feature = "dynamic-dispatch")))]
+#[inline(always)]
+pub(crate) fn try_with_interface<R, F: FnOnce(&NearBlockchain) -> R>(f: F) -> Result<R, EnvError> {
+    Ok(f(&NearBlockchain))
+}
+
+/// Same as `try_with_interface`, which never fails on `wasm32`.
+#[cfg(all(target_arch = "wasm32", not(feature = "dynamic-dispatch")))]
+#[inline(always)]
+pub(crate) fn with_interface<R, F: FnOnce(&NearBlockchain) -> R>(f: F) -> R {
+    f(&NearBlockchain)
+}
+
+/// Sets the blockchain interface of the contract, usually to `NearBlockchain` at the entry point.
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+pub fn set_blockchain_interface(blockchain_interface: Box<dyn BlockchainInterface>) {
+    context_cache::reset();
+    *BLOCKCHAIN_INTERFACE.0.borrow_mut() = Some(blockchain_interface);
+}
+
+/// Removes the blockchain interface of the contract and returns it.
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
 pub fn take_blockchain_interface() -> Option<Box<dyn BlockchainInterface>> {
-    BLOCKCHAIN_INTERFACE.with(|b| b.replace(None))
+    context_cache::reset();
+    BLOCKCHAIN_INTERFACE.0.replace(None)
 }
 
+/// Calls `f` with the blockchain interface set by the contract.
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+pub(crate) fn try_with_interface<R, F: FnOnce(&dyn BlockchainInterface) -> R>(
+    f: F,
+) -> Result<R, EnvError> {
+    let blockchain_interface = BLOCKCHAIN_INTERFACE.0.borrow();
+    Ok(f(blockchain_interface.as_deref().ok_or(EnvError::InterfaceNotSet)?))
+}
+
+/// Same as `try_with_interface`, but panics if the interface is not set.
+#[cfg(all(target_arch = "wasm32", feature = "dynamic-dispatch"))]
+pub(crate) fn with_interface<R, F: FnOnce(&dyn BlockchainInterface) -> R>(f: F) -> R {
+    unwrap(try_with_interface(f))
+}
 
 /// Reads the content of the `register_id`. If register is not used returns `None`.
 pub fn read_register(register_id: u64) -> Option<Vec<u8>> {
-    let len = register_len(register_id)?;
-    let res = vec![0u8; len as usize];
-    BLOCKCHAIN_INTERFACE.with(|b| unsafe {
-        b.borrow()
-            .as_ref()
-            .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-            .read_register(register_id, res.as_ptr() as _)
-    });
-    Some(res)
+    unwrap(try_read_register(register_id))
+}
+
+/// Same as `read_register`, but returns an error instead of panicking.
+pub fn try_read_register(register_id: u64) -> Result<Option<Vec<u8>>, EnvError> {
+    let len = match try_register_len(register_id)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    let mut res = vec![0u8; len as usize];
+    try_with_interface(|b| unsafe { b.read_register(register_id, res.as_mut_ptr() as _) })?;
+    Ok(Some(res))
 }
 
 /// Returns the size of the register. If register is not used returns `None`.
 pub fn register_len(register_id: u64) -> Option<u64> {
-    let len = BLOCKCHAIN_INTERFACE.with(|b| unsafe {
-        b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).register_len(register_id)
-    });
+    unwrap(try_register_len(register_id))
+}
+
+/// Same as `register_len`, but returns an error instead of panicking.
+pub fn try_register_len(register_id: u64) -> Result<Option<u64>, EnvError> {
+    let len = try_with_interface(|b| unsafe { b.register_len(register_id) })?;
     if len == core::u64::MAX {
-        None
+        Ok(None)
     } else {
-        Some(len)
+        Ok(Some(len))
     }
 }
 
+/// Clears the values of the call context that `env` caches after the first read, like
+/// `current_account_id` and `attached_deposit`. The entry points generated by `#[near_bindgen]`
+/// call it first, and replacing the blockchain interface does it too.
+pub fn reset_context_cache() {
+    context_cache::reset()
+}
+
 // ###############
 // # Context API #
 // ###############
 /// The id of the account that owns the current contract.
 pub fn current_account_id() -> AccountId {
-    String::from_utf8(method_into_register!(current_account_id)).unwrap()
+    unwrap(try_current_account_id())
+}
+
+/// Same as `current_account_id`, but returns an error instead of panicking.
+pub fn try_current_account_id() -> Result<AccountId, EnvError> {
+    cached(
+        |c| &mut c.current_account_id,
+        || account_id_from_register(method_into_register!(current_account_id)?),
+    )
 }
 
 /// The id of the account that either signed the original transaction or issued the initial
 /// cross-contract call.
 pub fn signer_account_id() -> AccountId {
-    String::from_utf8(method_into_register!(signer_account_id)).unwrap()
+    unwrap(try_signer_account_id())
+}
+
+/// Same as `signer_account_id`, but returns an error instead of panicking.
+pub fn try_signer_account_id() -> Result<AccountId, EnvError> {
+    cached(
+        |c| &mut c.signer_account_id,
+        || account_id_from_register(method_into_register!(signer_account_id)?),
+    )
 }
 
 /// The public key of the account that did the signing.
 pub fn signer_account_pk() -> PublicKey {
-    method_into_register!(signer_account_pk)
+    unwrap(try_signer_account_pk())
+}
+
+/// Same as `signer_account_pk`, but returns an error instead of panicking.
+pub fn try_signer_account_pk() -> Result<PublicKey, EnvError> {
+    cached(|c| &mut c.signer_account_pk, || method_into_register!(signer_account_pk))
 }
 
 /// The id of the account that was the previous contract in the chain of cross-contract calls.
 /// If this is the first contract, it is equal to `signer_account_id`.
-pub fn predecessor_account_id() -> String {
-    String::from_utf8(method_into_register!(predecessor_account_id)).unwrap()
+pub fn predecessor_account_id() -> AccountId {
+    unwrap(try_predecessor_account_id())
+}
+
+/// Same as `predecessor_account_id`, but returns an error instead of panicking.
+pub fn try_predecessor_account_id() -> Result<AccountId, EnvError> {
+    cached(
+        |c| &mut c.predecessor_account_id,
+        || account_id_from_register(method_into_register!(predecessor_account_id)?),
+    )
 }
 
 /// The input to the contract call serialized as bytes. If input is not provided returns `None`.
 pub fn input() -> Option<Vec<u8>> {
+    unwrap(try_input())
+}
+
+/// Same as `input`, but returns an error instead of panicking.
+pub fn try_input() -> Result<Option<Vec<u8>>, EnvError> {
     try_method_into_register!(input)
 }
 
 /// Current block index.
 pub fn block_index() -> BlockHeight {
-    unsafe {
-        BLOCKCHAIN_INTERFACE
-            .with(|b| b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).block_index())
-    }
+    unwrap(try_block_index())
+}
+
+/// Same as `block_index`, but returns an error instead of panicking.
+pub fn try_block_index() -> Result<BlockHeight, EnvError> {
+    cached(|c| &mut c.block_index, || unsafe { try_with_interface(|b| b.block_index()) })
 }
 
 /// Current block timestamp, i.e, number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC.
 pub fn block_timestamp() -> u64 {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).block_timestamp()
-        })
-    }
+    unwrap(try_block_timestamp())
+}
+
+/// Same as `block_timestamp`, but returns an error instead of panicking.
+pub fn try_block_timestamp() -> Result<u64, EnvError> {
+    cached(|c| &mut c.block_timestamp, || unsafe { try_with_interface(|b| b.block_timestamp()) })
 }
 
 /// Current epoch height.
 pub fn epoch_height() -> u64 {
-    unsafe {
-        BLOCKCHAIN_INTERFACE
-            .with(|b| b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).epoch_height())
-    }
+    unwrap(try_epoch_height())
+}
+
+/// Same as `epoch_height`, but returns an error instead of panicking.
+pub fn try_epoch_height() -> Result<u64, EnvError> {
+    cached(|c| &mut c.epoch_height, || unsafe { try_with_interface(|b| b.epoch_height()) })
 }
 
 /// Current total storage usage of this smart contract that this account would be paying for.
 pub fn storage_usage() -> StorageUsage {
-    unsafe {
-        BLOCKCHAIN_INTERFACE
-            .with(|b| b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).storage_usage())
-    }
+    unwrap(try_storage_usage())
+}
+
+/// Same as `storage_usage`, but returns an error instead of panicking.
+pub fn try_storage_usage() -> Result<StorageUsage, EnvError> {
+    unsafe { try_with_interface(|b| b.storage_usage()) }
 }
 
 // #################
@@ -186,61 +446,86 @@ pub fn storage_usage() -> StorageUsage {
 /// The balance attached to the given account. This includes the attached_deposit that was
 /// attached to the transaction
 pub fn account_balance() -> Balance {
-    let data = [0u8; size_of::<Balance>()];
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .account_balance(data.as_ptr() as u64)
-        })
-    };
-    Balance::from_le_bytes(data)
+    unwrap(try_account_balance())
+}
+
+/// Same as `account_balance`, but returns an error instead of panicking.
+pub fn try_account_balance() -> Result<Balance, EnvError> {
+    let mut data = [0u8; size_of::<Balance>()];
+    unsafe { try_with_interface(|b| b.account_balance(data.as_mut_ptr() as u64))? };
+    Ok(Balance::from_le_bytes(data))
 }
 
 /// The balance locked for potential validator staking.
 pub fn account_locked_balance() -> Balance {
-    let data = [0u8; size_of::<Balance>()];
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .account_locked_balance(data.as_ptr() as u64)
-        })
-    };
-    Balance::from_le_bytes(data)
+    unwrap(try_account_locked_balance())
+}
+
+/// Same as `account_locked_balance`, but returns an error instead of panicking.
+pub fn try_account_locked_balance() -> Result<Balance, EnvError> {
+    let mut data = [0u8; size_of::<Balance>()];
+    unsafe { try_with_interface(|b| b.account_locked_balance(data.as_mut_ptr() as u64))? };
+    Ok(Balance::from_le_bytes(data))
 }
 
 /// The balance that was attached to the call that will be immediately deposited before the
 /// contract execution starts
 pub fn attached_deposit() -> Balance {
-    let data = [0u8; size_of::<Balance>()];
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .attached_deposit(data.as_ptr() as u64)
-        })
-    };
-    Balance::from_le_bytes(data)
+    unwrap(try_attached_deposit())
+}
+
+/// Same as `attached_deposit`, but returns an error instead of panicking.
+pub fn try_attached_deposit() -> Result<Balance, EnvError> {
+    cached(
+        |c| &mut c.attached_deposit,
+        || {
+            let mut data = [0u8; size_of::<Balance>()];
+            unsafe { try_with_interface(|b| b.attached_deposit(data.as_mut_ptr() as u64))? };
+            Ok(Balance::from_le_bytes(data))
+        },
+    )
+}
+
+/// The balance that the account has to hold for every byte of its storage usage, 10^19
+/// yoctoNEAR, i.e. 1 NEAR per 100kb. The `MockedBlockchain` uses the `storage_amount_per_byte` of
+/// its `RuntimeFeesConfig` instead.
+pub fn storage_byte_cost() -> Balance {
+    unwrap(try_storage_byte_cost())
+}
+
+/// Same as `storage_byte_cost`, but returns an error instead of panicking.
+pub fn try_storage_byte_cost() -> Result<Balance, EnvError> {
+    try_with_interface(|b| b.storage_byte_cost())
 }
 
 /// The amount of gas attached to the call that can be used to pay for the gas fees.
 pub fn prepaid_gas() -> Gas {
-    unsafe {
-        BLOCKCHAIN_INTERFACE
-            .with(|b| b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).prepaid_gas())
-    }
+    unwrap(try_prepaid_gas())
+}
+
+/// Same as `prepaid_gas`, but returns an error instead of panicking.
+pub fn try_prepaid_gas() -> Result<Gas, EnvError> {
+    cached(|c| &mut c.prepaid_gas, || unsafe { try_with_interface(|b| b.prepaid_gas()).map(Gas) })
 }
 
 /// The gas that was already burnt during the contract execution (cannot exceed `prepaid_gas`)
 pub fn used_gas() -> Gas {
-    unsafe {
-        BLOCKCHAIN_INTERFACE
-            .with(|b| b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).used_gas())
-    }
+    unwrap(try_used_gas())
+}
+
+/// Same as `used_gas`, but returns an error instead of panicking.
+pub fn try_used_gas() -> Result<Gas, EnvError> {
+    unsafe { try_with_interface(|b| b.used_gas()).map(Gas) }
+}
+
+/// The gas that the call can still use, `prepaid_gas() - used_gas()`.
+pub fn remaining_gas() -> Gas {
+    unwrap(try_remaining_gas())
+}
+
+/// Same as `remaining_gas`, but returns an error instead of panicking.
+pub fn try_remaining_gas() -> Result<Gas, EnvError> {
+    Ok(try_prepaid_gas()?.saturating_sub(try_used_gas()?))
 }
 
 // ############
@@ -248,49 +533,211 @@ pub fn used_gas() -> Gas {
 // ############
 /// Get random seed from the register.
 pub fn random_seed() -> Vec<u8> {
-    method_into_register!(random_seed)
+    unwrap(try_random_seed())
+}
+
+/// Same as `random_seed`, but returns an error instead of panicking.
+pub fn try_random_seed() -> Result<Vec<u8>, EnvError> {
+    cached(|c| &mut c.random_seed, || method_into_register!(random_seed))
 }
 
 /// Hashes the random sequence of bytes using sha256.
 pub fn sha256(value: &[u8]) -> Vec<u8> {
+    unwrap(try_sha256(value))
+}
+
+/// Same as `sha256`, but returns an error instead of panicking.
+pub fn try_sha256(value: &[u8]) -> Result<Vec<u8>, EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).sha256(
-                value.len() as _,
-                value.as_ptr() as _,
-                ATOMIC_OP_REGISTER,
-            )
-        });
+        try_with_interface(|b| {
+            b.sha256(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
     };
-    read_register(ATOMIC_OP_REGISTER).expect(REGISTER_EXPECTED_ERR)
+    expect_register(ATOMIC_OP_REGISTER)
 }
 
 /// Hashes the random sequence of bytes using keccak256.
 pub fn keccak256(value: &[u8]) -> Vec<u8> {
+    unwrap(try_keccak256(value))
+}
+
+/// Same as `keccak256`, but returns an error instead of panicking.
+pub fn try_keccak256(value: &[u8]) -> Result<Vec<u8>, EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).keccak256(
-                value.len() as _,
-                value.as_ptr() as _,
-                ATOMIC_OP_REGISTER,
-            )
-        });
+        try_with_interface(|b| {
+            b.keccak256(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
     };
-    read_register(ATOMIC_OP_REGISTER).expect(REGISTER_EXPECTED_ERR)
+    expect_register(ATOMIC_OP_REGISTER)
 }
 
 /// Hashes the random sequence of bytes using keccak512.
 pub fn keccak512(value: &[u8]) -> Vec<u8> {
+    unwrap(try_keccak512(value))
+}
+
+/// Same as `keccak512`, but returns an error instead of panicking.
+pub fn try_keccak512(value: &[u8]) -> Result<Vec<u8>, EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).keccak512(
-                value.len() as _,
-                value.as_ptr() as _,
+        try_with_interface(|b| {
+            b.keccak512(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
+    };
+    expect_register(ATOMIC_OP_REGISTER)
+}
+
+/// Hashes the random sequence of bytes using RIPEMD-160.
+#[cfg(feature = "ripemd160")]
+pub fn ripemd160(value: &[u8]) -> Vec<u8> {
+    unwrap(try_ripemd160(value))
+}
+
+/// Same as `ripemd160`, but returns an error instead of panicking.
+#[cfg(feature = "ripemd160")]
+pub fn try_ripemd160(value: &[u8]) -> Result<Vec<u8>, EnvError> {
+    unsafe {
+        try_with_interface(|b| {
+            b.ripemd160(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
+    };
+    expect_register(ATOMIC_OP_REGISTER)
+}
+
+/// Recovers the secp256k1 public key that signed the 32-byte hash, given the signature `r || s`
+/// and the recovery id `v` in `0..=3`. With `malleability_flag`, the signatures with the upper
+/// `s` values are rejected. Returns the 64-byte public key without the prefix, or `None` if the
+/// signature is not valid.
+#[cfg(feature = "ecrecover")]
+pub fn ecrecover(
+    hash: &[u8; 32],
+    signature: &[u8; 64],
+    v: u8,
+    malleability_flag: bool,
+) -> Option<Vec<u8>> {
+    unwrap(try_ecrecover(hash, signature, v, malleability_flag))
+}
+
+/// Same as `ecrecover`, but returns an error instead of panicking.
+#[cfg(feature = "ecrecover")]
+pub fn try_ecrecover(
+    hash: &[u8; 32],
+    signature: &[u8; 64],
+    v: u8,
+    malleability_flag: bool,
+) -> Result<Option<Vec<u8>>, EnvError> {
+    match unsafe {
+        try_with_interface(|b| {
+            b.ecrecover(
+                hash.len() as _,
+                hash.as_ptr() as _,
+                signature.len() as _,
+                signature.as_ptr() as _,
+                v as _,
+                malleability_flag as _,
                 ATOMIC_OP_REGISTER,
             )
-        });
+        })?
+    } {
+        0 => Ok(None),
+        1 => Ok(Some(expect_register(ATOMIC_OP_REGISTER)?)),
+        code => Err(EnvError::InvalidReturnCode(code)),
+    }
+}
+
+/// Verifies the ed25519 signature of the message with the public key.
+#[cfg(feature = "ed25519-verify")]
+pub fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    unwrap(try_ed25519_verify(signature, message, public_key))
+}
+
+/// Same as `ed25519_verify`, but returns an error instead of panicking.
+#[cfg(feature = "ed25519-verify")]
+pub fn try_ed25519_verify(
+    signature: &[u8; 64],
+    message: &[u8],
+    public_key: &[u8; 32],
+) -> Result<bool, EnvError> {
+    bool_return_code(unsafe {
+        try_with_interface(|b| {
+            b.ed25519_verify(
+                signature.len() as _,
+                signature.as_ptr() as _,
+                message.len() as _,
+                message.as_ptr() as _,
+                public_key.len() as _,
+                public_key.as_ptr() as _,
+            )
+        })?
+    })
+}
+
+/// Computes the sum of the alt_bn128 G1 points multiplied by the scalars.
+#[cfg(feature = "alt-bn128")]
+pub fn alt_bn128_g1_multiexp(items: &[(G1Point, Scalar)]) -> G1Point {
+    unwrap(try_alt_bn128_g1_multiexp(items))
+}
+
+/// Same as `alt_bn128_g1_multiexp`, but returns an error instead of panicking.
+#[cfg(feature = "alt-bn128")]
+pub fn try_alt_bn128_g1_multiexp(items: &[(G1Point, Scalar)]) -> Result<G1Point, EnvError> {
+    let mut value = Vec::with_capacity(items.len() * 96);
+    for (point, scalar) in items {
+        value.extend_from_slice(&point.x);
+        value.extend_from_slice(&point.y);
+        value.extend_from_slice(scalar);
+    }
+    unsafe {
+        try_with_interface(|b| {
+            b.alt_bn128_g1_multiexp(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
+    };
+    g1_point_from_register()
+}
+
+/// Computes the sum of the alt_bn128 G1 points, negating the points paired with `true`.
+#[cfg(feature = "alt-bn128")]
+pub fn alt_bn128_g1_sum(items: &[(bool, G1Point)]) -> G1Point {
+    unwrap(try_alt_bn128_g1_sum(items))
+}
+
+/// Same as `alt_bn128_g1_sum`, but returns an error instead of panicking.
+#[cfg(feature = "alt-bn128")]
+pub fn try_alt_bn128_g1_sum(items: &[(bool, G1Point)]) -> Result<G1Point, EnvError> {
+    let mut value = Vec::with_capacity(items.len() * 65);
+    for (negate, point) in items {
+        value.push(*negate as u8);
+        value.extend_from_slice(&point.x);
+        value.extend_from_slice(&point.y);
+    }
+    unsafe {
+        try_with_interface(|b| {
+            b.alt_bn128_g1_sum(value.len() as _, value.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?;
     };
-    read_register(ATOMIC_OP_REGISTER).expect(REGISTER_EXPECTED_ERR)
+    g1_point_from_register()
+}
+
+/// Checks that the product of the alt_bn128 pairings of the G1 and G2 points is one, which is how
+/// the pairing equations of the proof systems like Groth16 are verified.
+#[cfg(feature = "alt-bn128")]
+pub fn alt_bn128_pairing_check(items: &[(G1Point, G2Point)]) -> bool {
+    unwrap(try_alt_bn128_pairing_check(items))
+}
+
+/// Same as `alt_bn128_pairing_check`, but returns an error instead of panicking.
+#[cfg(feature = "alt-bn128")]
+pub fn try_alt_bn128_pairing_check(items: &[(G1Point, G2Point)]) -> Result<bool, EnvError> {
+    let mut value = Vec::with_capacity(items.len() * 192);
+    for (g1, g2) in items {
+        value.extend_from_slice(&g1.x);
+        value.extend_from_slice(&g1.y);
+        for c in g2.x.iter().chain(g2.y.iter()) {
+            value.extend_from_slice(c);
+        }
+    }
+    bool_return_code(unsafe {
+        try_with_interface(|b| b.alt_bn128_pairing_check(value.len() as _, value.as_ptr() as _))?
+    })
 }
 
 // ################
@@ -305,10 +752,21 @@ pub fn promise_create(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    unwrap(try_promise_create(account_id, method_name, arguments, amount, gas))
+}
+
+/// Same as `promise_create`, but returns an error instead of panicking.
+pub fn try_promise_create(
+    account_id: AccountId,
+    method_name: &[u8],
+    arguments: &[u8],
+    amount: Balance,
+    gas: Gas,
+) -> Result<PromiseIndex, EnvError> {
     let account_id = account_id.as_bytes();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_create(
+        try_with_interface(|b| {
+            b.promise_create(
                 account_id.len() as _,
                 account_id.as_ptr() as _,
                 method_name.len() as _,
@@ -316,7 +774,7 @@ pub fn promise_create(
                 arguments.len() as _,
                 arguments.as_ptr() as _,
                 &amount as *const Balance as _,
-                gas,
+                gas.0,
             )
         })
     }
@@ -331,10 +789,22 @@ pub fn promise_then(
     amount: Balance,
     gas: Gas,
 ) -> PromiseIndex {
+    unwrap(try_promise_then(promise_idx, account_id, method_name, arguments, amount, gas))
+}
+
+/// Same as `promise_then`, but returns an error instead of panicking.
+pub fn try_promise_then(
+    promise_idx: PromiseIndex,
+    account_id: AccountId,
+    method_name: &[u8],
+    arguments: &[u8],
+    amount: Balance,
+    gas: Gas,
+) -> Result<PromiseIndex, EnvError> {
     let account_id = account_id.as_bytes();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_then(
+        try_with_interface(|b| {
+            b.promise_then(
                 promise_idx,
                 account_id.len() as _,
                 account_id.as_ptr() as _,
@@ -343,7 +813,7 @@ pub fn promise_then(
                 arguments.len() as _,
                 arguments.as_ptr() as _,
                 &amount as *const Balance as _,
-                gas,
+                gas.0,
             )
         })
     }
@@ -351,29 +821,31 @@ pub fn promise_then(
 
 /// Creates a new promise which completes when time all promises passed as arguments complete.
 pub fn promise_and(promise_indices: &[PromiseIndex]) -> PromiseIndex {
+    unwrap(try_promise_and(promise_indices))
+}
+
+/// Same as `promise_and`, but returns an error instead of panicking.
+pub fn try_promise_and(promise_indices: &[PromiseIndex]) -> Result<PromiseIndex, EnvError> {
     let mut data = vec![0u8; promise_indices.len() * size_of::<PromiseIndex>()];
     for i in 0..promise_indices.len() {
         data[i * size_of::<PromiseIndex>()..(i + 1) * size_of::<PromiseIndex>()]
             .copy_from_slice(&promise_indices[i].to_le_bytes());
     }
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_and(data.as_ptr() as _, promise_indices.len() as _)
-        })
-    }
+    unsafe { try_with_interface(|b| b.promise_and(data.as_ptr() as _, promise_indices.len() as _)) }
 }
 
 pub fn promise_batch_create<A: Borrow<AccountId>>(account_id: A) -> PromiseIndex {
+    unwrap(try_promise_batch_create(account_id))
+}
+
+/// Same as `promise_batch_create`, but returns an error instead of panicking.
+pub fn try_promise_batch_create<A: Borrow<AccountId>>(
+    account_id: A,
+) -> Result<PromiseIndex, EnvError> {
     let account_id = account_id.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_create(account_id.len() as _, account_id.as_ptr() as _)
+        try_with_interface(|b| {
+            b.promise_batch_create(account_id.len() as _, account_id.as_ptr() as _)
         })
     }
 }
@@ -382,76 +854,152 @@ pub fn promise_batch_then<A: Borrow<AccountId>>(
     promise_index: PromiseIndex,
     account_id: A,
 ) -> PromiseIndex {
+    unwrap(try_promise_batch_then(promise_index, account_id))
+}
+
+/// Same as `promise_batch_then`, but returns an error instead of panicking.
+pub fn try_promise_batch_then<A: Borrow<AccountId>>(
+    promise_index: PromiseIndex,
+    account_id: A,
+) -> Result<PromiseIndex, EnvError> {
     let account_id = account_id.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_batch_then(
-                promise_index,
-                account_id.len() as _,
-                account_id.as_ptr() as _,
-            )
+        try_with_interface(|b| {
+            b.promise_batch_then(promise_index, account_id.len() as _, account_id.as_ptr() as _)
         })
     }
 }
 
 pub fn promise_batch_action_create_account(promise_index: PromiseIndex) {
+    unwrap(try_promise_batch_action_create_account(promise_index))
+}
+
+/// Same as `promise_batch_action_create_account`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_create_account(
+    promise_index: PromiseIndex,
+) -> Result<(), EnvError> {
+    unsafe { try_with_interface(|b| b.promise_batch_action_create_account(promise_index)) }
+}
+
+pub fn promise_batch_action_deploy_contract(promise_index: u64, code: &[u8]) {
+    unwrap(try_promise_batch_action_deploy_contract(promise_index, code))
+}
+
+/// Same as `promise_batch_action_deploy_contract`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_deploy_contract(
+    promise_index: u64,
+    code: &[u8],
+) -> Result<(), EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_create_account(promise_index)
+        try_with_interface(|b| {
+            b.promise_batch_action_deploy_contract(
+                promise_index,
+                code.len() as _,
+                code.as_ptr() as _,
+            )
         })
     }
 }
 
-pub fn promise_batch_action_deploy_contract(promise_index: u64, code: &[u8]) {
+pub fn promise_batch_action_function_call(
+    promise_index: PromiseIndex,
+    method_name: &[u8],
+    arguments: &[u8],
+    amount: Balance,
+    gas: Gas,
+) {
+    unwrap(try_promise_batch_action_function_call(
+        promise_index,
+        method_name,
+        arguments,
+        amount,
+        gas,
+    ))
+}
+
+/// Same as `promise_batch_action_function_call`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_function_call(
+    promise_index: PromiseIndex,
+    method_name: &[u8],
+    arguments: &[u8],
+    amount: Balance,
+    gas: Gas,
+) -> Result<(), EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_deploy_contract(
-                    promise_index,
-                    code.len() as _,
-                    code.as_ptr() as _,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_function_call(
+                promise_index,
+                method_name.len() as _,
+                method_name.as_ptr() as _,
+                arguments.len() as _,
+                arguments.as_ptr() as _,
+                &amount as *const Balance as _,
+                gas.0,
+            )
         })
     }
 }
 
-pub fn promise_batch_action_function_call(
+/// Appends a function call that gets, in addition to `gas`, the share of the gas that is left
+/// unused at the end of the current call, proportional to its `weight` among the weighted calls.
+#[cfg(feature = "function-call-weight")]
+pub fn promise_batch_action_function_call_weight(
     promise_index: PromiseIndex,
     method_name: &[u8],
     arguments: &[u8],
     amount: Balance,
     gas: Gas,
+    weight: GasWeight,
 ) {
+    unwrap(try_promise_batch_action_function_call_weight(
+        promise_index,
+        method_name,
+        arguments,
+        amount,
+        gas,
+        weight,
+    ))
+}
+
+/// Same as `promise_batch_action_function_call_weight`, but returns an error instead of
+/// panicking.
+#[cfg(feature = "function-call-weight")]
+pub fn try_promise_batch_action_function_call_weight(
+    promise_index: PromiseIndex,
+    method_name: &[u8],
+    arguments: &[u8],
+    amount: Balance,
+    gas: Gas,
+    weight: GasWeight,
+) -> Result<(), EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_function_call(
-                    promise_index,
-                    method_name.len() as _,
-                    method_name.as_ptr() as _,
-                    arguments.len() as _,
-                    arguments.as_ptr() as _,
-                    &amount as *const Balance as _,
-                    gas,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_function_call_weight(
+                promise_index,
+                method_name.len() as _,
+                method_name.as_ptr() as _,
+                arguments.len() as _,
+                arguments.as_ptr() as _,
+                &amount as *const Balance as _,
+                gas.0,
+                weight.0,
+            )
         })
     }
 }
 
 pub fn promise_batch_action_transfer(promise_index: PromiseIndex, amount: Balance) {
+    unwrap(try_promise_batch_action_transfer(promise_index, amount))
+}
+
+/// Same as `promise_batch_action_transfer`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_transfer(
+    promise_index: PromiseIndex,
+    amount: Balance,
+) -> Result<(), EnvError> {
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_transfer(promise_index, &amount as *const Balance as _)
+        try_with_interface(|b| {
+            b.promise_batch_action_transfer(promise_index, &amount as *const Balance as _)
         })
     }
 }
@@ -461,10 +1009,19 @@ pub fn promise_batch_action_stake<P: Borrow<PublicKey>>(
     amount: Balance,
     public_key: P,
 ) {
+    unwrap(try_promise_batch_action_stake(promise_index, amount, public_key))
+}
+
+/// Same as `promise_batch_action_stake`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_stake<P: Borrow<PublicKey>>(
+    promise_index: PromiseIndex,
+    amount: Balance,
+    public_key: P,
+) -> Result<(), EnvError> {
     let public_key = public_key.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_batch_action_stake(
+        try_with_interface(|b| {
+            b.promise_batch_action_stake(
                 promise_index,
                 &amount as *const Balance as _,
                 public_key.len() as _,
@@ -478,18 +1035,25 @@ pub fn promise_batch_action_add_key_with_full_access<P: Borrow<PublicKey>>(
     public_key: P,
     nonce: u64,
 ) {
+    unwrap(try_promise_batch_action_add_key_with_full_access(promise_index, public_key, nonce))
+}
+
+/// Same as `promise_batch_action_add_key_with_full_access`, but returns an error instead of
+/// panicking.
+pub fn try_promise_batch_action_add_key_with_full_access<P: Borrow<PublicKey>>(
+    promise_index: PromiseIndex,
+    public_key: P,
+    nonce: u64,
+) -> Result<(), EnvError> {
     let public_key = public_key.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_add_key_with_full_access(
-                    promise_index,
-                    public_key.len() as _,
-                    public_key.as_ptr() as _,
-                    nonce,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_add_key_with_full_access(
+                promise_index,
+                public_key.len() as _,
+                public_key.as_ptr() as _,
+                nonce,
+            )
         })
     }
 }
@@ -504,24 +1068,44 @@ pub fn promise_batch_action_add_key_with_function_call<
     receiver_id: A,
     method_names: &[u8],
 ) {
+    unwrap(try_promise_batch_action_add_key_with_function_call(
+        promise_index,
+        public_key,
+        nonce,
+        allowance,
+        receiver_id,
+        method_names,
+    ))
+}
+
+/// Same as `promise_batch_action_add_key_with_function_call`, but returns an error instead of
+/// panicking.
+pub fn try_promise_batch_action_add_key_with_function_call<
+    P: Borrow<PublicKey>,
+    A: Borrow<AccountId>,
+>(
+    promise_index: PromiseIndex,
+    public_key: P,
+    nonce: u64,
+    allowance: Balance,
+    receiver_id: A,
+    method_names: &[u8],
+) -> Result<(), EnvError> {
     let public_key = public_key.borrow();
     let receiver_id = receiver_id.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_add_key_with_function_call(
-                    promise_index,
-                    public_key.len() as _,
-                    public_key.as_ptr() as _,
-                    nonce,
-                    &allowance as *const Balance as _,
-                    receiver_id.len() as _,
-                    receiver_id.as_ptr() as _,
-                    method_names.len() as _,
-                    method_names.as_ptr() as _,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_add_key_with_function_call(
+                promise_index,
+                public_key.len() as _,
+                public_key.as_ptr() as _,
+                nonce,
+                &allowance as *const Balance as _,
+                receiver_id.len() as _,
+                receiver_id.as_ptr() as _,
+                method_names.len() as _,
+                method_names.as_ptr() as _,
+            )
         })
     }
 }
@@ -529,17 +1113,22 @@ pub fn promise_batch_action_delete_key<P: Borrow<PublicKey>>(
     promise_index: PromiseIndex,
     public_key: P,
 ) {
+    unwrap(try_promise_batch_action_delete_key(promise_index, public_key))
+}
+
+/// Same as `promise_batch_action_delete_key`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_delete_key<P: Borrow<PublicKey>>(
+    promise_index: PromiseIndex,
+    public_key: P,
+) -> Result<(), EnvError> {
     let public_key = public_key.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_delete_key(
-                    promise_index,
-                    public_key.len() as _,
-                    public_key.as_ptr() as _,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_delete_key(
+                promise_index,
+                public_key.len() as _,
+                public_key.as_ptr() as _,
+            )
         })
     }
 }
@@ -548,17 +1137,22 @@ pub fn promise_batch_action_delete_account<A: Borrow<AccountId>>(
     promise_index: PromiseIndex,
     beneficiary_id: A,
 ) {
+    unwrap(try_promise_batch_action_delete_account(promise_index, beneficiary_id))
+}
+
+/// Same as `promise_batch_action_delete_account`, but returns an error instead of panicking.
+pub fn try_promise_batch_action_delete_account<A: Borrow<AccountId>>(
+    promise_index: PromiseIndex,
+    beneficiary_id: A,
+) -> Result<(), EnvError> {
     let beneficiary_id = beneficiary_id.borrow();
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_batch_action_delete_account(
-                    promise_index,
-                    beneficiary_id.len() as _,
-                    beneficiary_id.as_ptr() as _,
-                )
+        try_with_interface(|b| {
+            b.promise_batch_action_delete_account(
+                promise_index,
+                beneficiary_id.len() as _,
+                beneficiary_id.as_ptr() as _,
+            )
         })
     }
 }
@@ -567,41 +1161,49 @@ pub fn promise_batch_action_delete_account<A: Borrow<AccountId>>(
 /// promises that caused the callback. This function returns the number of complete and
 /// incomplete callbacks.
 pub fn promise_results_count() -> u64 {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_results_count()
-        })
+    unwrap(try_promise_results_count())
+}
+
+/// Same as `promise_results_count`, but returns an error instead of panicking.
+pub fn try_promise_results_count() -> Result<u64, EnvError> {
+    unsafe { try_with_interface(|b| b.promise_results_count()) }
+}
+
+/// Reads the result of the promise, which may be not ready or failed.
+fn read_promise_result(result_idx: u64) -> Result<PromiseResult, EnvError> {
+    match unsafe { try_with_interface(|b| b.promise_result(result_idx, ATOMIC_OP_REGISTER))? } {
+        0 => Ok(PromiseResult::NotReady),
+        1 => Ok(PromiseResult::Successful(expect_register(ATOMIC_OP_REGISTER)?)),
+        2 => Ok(PromiseResult::Failed),
+        code => Err(EnvError::InvalidReturnCode(code)),
     }
 }
+
 /// If the current function is invoked by a callback we can access the execution results of the
 /// promises that caused the callback.
 pub fn promise_result(result_idx: u64) -> PromiseResult {
-    match unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .promise_result(result_idx, ATOMIC_OP_REGISTER)
-        })
-    } {
-        0 => PromiseResult::NotReady,
-        1 => {
-            let data = read_register(ATOMIC_OP_REGISTER)
-                .expect("Promise result should've returned into register.");
-            PromiseResult::Successful(data)
-        }
-        2 => PromiseResult::Failed,
-        _ => panic!(RETURN_CODE_ERR),
+    unwrap(read_promise_result(result_idx))
+}
+
+/// Returns the data of the successful promise result. Unlike `promise_result`, the result that is
+/// not ready or failed is returned as an error.
+pub fn try_promise_result(result_idx: u64) -> Result<Vec<u8>, EnvError> {
+    match read_promise_result(result_idx)? {
+        PromiseResult::NotReady => Err(EnvError::PromiseResultNotReady(result_idx)),
+        PromiseResult::Successful(data) => Ok(data),
+        PromiseResult::Failed => Err(EnvError::PromiseFailed(result_idx)),
     }
 }
+
 /// Consider the execution result of promise under `promise_idx` as execution result of this
 /// function.
 pub fn promise_return(promise_idx: PromiseIndex) {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).promise_return(promise_idx)
-        })
-    }
+    unwrap(try_promise_return(promise_idx))
+}
+
+/// Same as `promise_return`, but returns an error instead of panicking.
+pub fn try_promise_return(promise_idx: PromiseIndex) -> Result<(), EnvError> {
+    unsafe { try_with_interface(|b| b.promise_return(promise_idx)) }
 }
 
 // ###############
@@ -610,31 +1212,34 @@ pub fn promise_return(promise_idx: PromiseIndex) {
 
 /// For a given account return its current stake. If the account is not a validator, returns 0.
 pub fn validator_stake(account_id: &AccountId) -> Balance {
-    let data = [0u8; size_of::<Balance>()];
+    unwrap(try_validator_stake(account_id))
+}
+
+/// Same as `validator_stake`, but returns an error instead of panicking.
+pub fn try_validator_stake(account_id: &AccountId) -> Result<Balance, EnvError> {
+    let mut data = [0u8; size_of::<Balance>()];
     unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).validator_stake(
+        try_with_interface(|b| {
+            b.validator_stake(
                 account_id.len() as _,
                 account_id.as_ptr() as _,
-                data.as_ptr() as u64,
+                data.as_mut_ptr() as u64,
             )
-        })
+        })?
     };
-    Balance::from_le_bytes(data)
+    Ok(Balance::from_le_bytes(data))
 }
 
 /// Returns the total stake of validators in the current epoch.
 pub fn validator_total_stake() -> Balance {
-    let data = [0u8; size_of::<Balance>()];
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .validator_total_stake(data.as_ptr() as u64)
-        })
-    };
-    Balance::from_le_bytes(data)
+    unwrap(try_validator_total_stake())
+}
+
+/// Same as `validator_total_stake`, but returns an error instead of panicking.
+pub fn try_validator_total_stake() -> Result<Balance, EnvError> {
+    let mut data = [0u8; size_of::<Balance>()];
+    unsafe { try_with_interface(|b| b.validator_total_stake(data.as_mut_ptr() as u64))? };
+    Ok(Balance::from_le_bytes(data))
 }
 
 // #####################
@@ -642,37 +1247,32 @@ pub fn validator_total_stake() -> Balance {
 // #####################
 /// Sets the blob of data as the return value of the contract.
 pub fn value_return(value: &[u8]) {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .value_return(value.len() as _, value.as_ptr() as _)
-        })
-    }
+    unwrap(try_value_return(value))
+}
+/// Same as `value_return`, but returns an error instead of panicking.
+pub fn try_value_return(value: &[u8]) -> Result<(), EnvError> {
+    unsafe { try_with_interface(|b| b.value_return(value.len() as _, value.as_ptr() as _)) }
 }
 /// Terminates the execution of the program with the UTF-8 encoded message.
 pub fn panic(message: &[u8]) -> ! {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .panic_utf8(message.len() as _, message.as_ptr() as _)
-        })
-    }
+    unsafe { with_interface(|b| b.panic_utf8(message.len() as _, message.as_ptr() as _)) }
     unreachable!()
 }
 /// Log the UTF-8 encodable message.
 pub fn log(message: &[u8]) {
-    unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .log_utf8(message.len() as _, message.as_ptr() as _)
-        })
-    }
+    unwrap(try_log(message))
+}
+/// Same as `log`, but returns an error instead of panicking.
+pub fn try_log(message: &[u8]) -> Result<(), EnvError> {
+    unsafe { try_with_interface(|b| b.log_utf8(message.len() as _, message.as_ptr() as _)) }
+}
+/// Log the UTF-16 encoded message.
+pub fn log_utf16(message: &[u16]) {
+    unwrap(try_log_utf16(message))
+}
+/// Same as `log_utf16`, but returns an error instead of panicking.
+pub fn try_log_utf16(message: &[u16]) -> Result<(), EnvError> {
+    unsafe { try_with_interface(|b| b.log_utf16(size_of_val(message) as _, message.as_ptr() as _)) }
 }
 
 // ###############
@@ -681,73 +1281,68 @@ pub fn log(message: &[u8]) {
 /// Writes key-value into storage.
 /// If another key-value existed in the storage with the same key it returns `true`, otherwise `false`.
 pub fn storage_write(key: &[u8], value: &[u8]) -> bool {
-    match unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).storage_write(
+    unwrap(try_storage_write(key, value))
+}
+/// Same as `storage_write`, but returns an error instead of panicking.
+pub fn try_storage_write(key: &[u8], value: &[u8]) -> Result<bool, EnvError> {
+    bool_return_code(unsafe {
+        try_with_interface(|b| {
+            b.storage_write(
                 key.len() as _,
                 key.as_ptr() as _,
                 value.len() as _,
                 value.as_ptr() as _,
                 EVICTED_REGISTER,
             )
-        })
-    } {
-        0 => false,
-        1 => true,
-        _ => panic!(RETURN_CODE_ERR),
-    }
+        })?
+    })
 }
 /// Reads the value stored under the given key.
 pub fn storage_read(key: &[u8]) -> Option<Vec<u8>> {
+    unwrap(try_storage_read(key))
+}
+/// Same as `storage_read`, but returns an error instead of panicking.
+pub fn try_storage_read(key: &[u8]) -> Result<Option<Vec<u8>>, EnvError> {
     match unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).storage_read(
-                key.len() as _,
-                key.as_ptr() as _,
-                ATOMIC_OP_REGISTER,
-            )
-        })
+        try_with_interface(|b| {
+            b.storage_read(key.len() as _, key.as_ptr() as _, ATOMIC_OP_REGISTER)
+        })?
     } {
-        0 => None,
-        1 => Some(read_register(ATOMIC_OP_REGISTER).expect(REGISTER_EXPECTED_ERR)),
-        _ => panic!(RETURN_CODE_ERR),
+        0 => Ok(None),
+        1 => Ok(Some(expect_register(ATOMIC_OP_REGISTER)?)),
+        code => Err(EnvError::InvalidReturnCode(code)),
     }
 }
 /// Removes the value stored under the given key.
 /// If key-value existed returns `true`, otherwise `false`.
 pub fn storage_remove(key: &[u8]) -> bool {
-    match unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow().as_ref().expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR).storage_remove(
-                key.len() as _,
-                key.as_ptr() as _,
-                EVICTED_REGISTER,
-            )
-        })
-    } {
-        0 => false,
-        1 => true,
-        _ => panic!(RETURN_CODE_ERR),
-    }
+    unwrap(try_storage_remove(key))
+}
+/// Same as `storage_remove`, but returns an error instead of panicking.
+pub fn try_storage_remove(key: &[u8]) -> Result<bool, EnvError> {
+    bool_return_code(unsafe {
+        try_with_interface(|b| {
+            b.storage_remove(key.len() as _, key.as_ptr() as _, EVICTED_REGISTER)
+        })?
+    })
 }
 /// Reads the most recent value that was evicted with `storage_write` or `storage_remove` command.
 pub fn storage_get_evicted() -> Option<Vec<u8>> {
-    read_register(EVICTED_REGISTER)
+    unwrap(try_storage_get_evicted())
+}
+/// Same as `storage_get_evicted`, but returns an error instead of panicking.
+pub fn try_storage_get_evicted() -> Result<Option<Vec<u8>>, EnvError> {
+    try_read_register(EVICTED_REGISTER)
 }
 /// Checks if there is a key-value in the storage.
 pub fn storage_has_key(key: &[u8]) -> bool {
-    match unsafe {
-        BLOCKCHAIN_INTERFACE.with(|b| {
-            b.borrow()
-                .as_ref()
-                .expect(BLOCKCHAIN_INTERFACE_NOT_SET_ERR)
-                .storage_has_key(key.len() as _, key.as_ptr() as _)
-        })
-    } {
-        0 => false,
-        1 => true,
-        _ => panic!(RETURN_CODE_ERR),
-    }
+    unwrap(try_storage_has_key(key))
+}
+/// Same as `storage_has_key`, but returns an error instead of panicking.
+pub fn try_storage_has_key(key: &[u8]) -> Result<bool, EnvError> {
+    bool_return_code(unsafe {
+        try_with_interface(|b| b.storage_has_key(key.len() as _, key.as_ptr() as _))?
+    })
 }
 
 // ############################################
@@ -766,7 +1361,12 @@ pub fn state_write<T: borsh::BorshSerialize>(state: &T) {
 
 /// Returns `true` if the contract state exists and `false` otherwise.
 pub fn state_exists() -> bool {
-    storage_has_key(STATE_KEY)
+    unwrap(try_state_exists())
+}
+
+/// Same as `state_exists`, but returns an error instead of panicking.
+pub fn try_state_exists() -> Result<bool, EnvError> {
+    try_storage_has_key(STATE_KEY)
 }
 
 // ##################
@@ -806,6 +1406,7 @@ pub fn is_valid_account_id(account_id: &[u8]) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{MockedBlockchain, VMContextBuilder};
 
     #[test]
     fn test_is_valid_account_id_strings() {
@@ -890,4 +1491,193 @@ mod tests {
         assert!(!is_valid_account_id(&[0, 1, 2]));
         assert!(is_valid_account_id(b"near"));
     }
+
+    fn blockchain(block_index: BlockHeight) -> Box<dyn BlockchainInterface> {
+        let context = VMContextBuilder::new().block_index(block_index).build();
+        Box::new(MockedBlockchain::new(context, Default::default(), vec![]))
+    }
+
+    #[test]
+    fn test_with_blockchain_per_thread() {
+        let threads: Vec<_> = (0..4)
+            .map(|index| {
+                std::thread::spawn(move || {
+                    with_blockchain(blockchain(index), || {
+                        storage_write(b"thread", &index.to_le_bytes());
+                        std::thread::yield_now();
+                        assert_eq!(block_index(), index);
+                        storage_read(b"thread")
+                    })
+                })
+            })
+            .collect();
+        for (index, thread) in threads.into_iter().enumerate() {
+            assert_eq!(thread.join().unwrap(), Some((index as u64).to_le_bytes().to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_with_blockchain_restores_previous() {
+        let previous = take_blockchain_interface();
+        set_blockchain_interface(blockchain(1));
+        let result = std::panic::catch_unwind(|| {
+            with_blockchain(blockchain(2), || {
+                assert_eq!(block_index(), 2);
+                panic!("aborted")
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(block_index(), 1);
+
+        // The interface can be replaced while one of its methods runs.
+        let outer = with_interface(|b| {
+            set_blockchain_interface(blockchain(3));
+            assert_eq!(block_index(), 3);
+            unsafe { b.block_index() }
+        });
+        assert_eq!(outer, 1);
+        assert_eq!(block_index(), 3);
+
+        take_blockchain_interface();
+        if let Some(previous) = previous {
+            set_blockchain_interface(previous);
+        }
+    }
+
+    #[test]
+    fn test_context_cache() {
+        crate::testing_env!(VMContextBuilder::new().block_index(1).build());
+        let handle = crate::test_utils::record_trace();
+        let host_calls =
+            |name: &str| handle.trace().calls.iter().filter(|call| call.name == name).count();
+        assert_eq!(current_account_id(), current_account_id());
+        assert_eq!(block_index(), 1);
+        assert_eq!(block_index(), 1);
+        assert_eq!((host_calls("current_account_id"), host_calls("block_index")), (1, 1));
+        reset_context_cache();
+        assert_eq!(block_index(), 1);
+        assert_eq!(host_calls("block_index"), 2);
+
+        // Replacing the blockchain interface resets the cache.
+        crate::testing_env!(VMContextBuilder::new().block_index(2).build());
+        assert_eq!(block_index(), 2);
+        with_blockchain(blockchain(3), || assert_eq!(block_index(), 3));
+        assert_eq!(block_index(), 2);
+    }
+
+    #[test]
+    fn test_try_errors() {
+        let not_set = std::thread::spawn(|| (try_block_index(), try_storage_read(b"key")));
+        assert_eq!(
+            not_set.join().unwrap(),
+            (Err(EnvError::InterfaceNotSet), Err(EnvError::InterfaceNotSet))
+        );
+
+        // The host is trusted to return valid account IDs, but they are checked anyway.
+        let context = VMContextBuilder::new()
+            .predecessor_account_id(AccountId::new_unchecked("Alice".into()))
+            .build();
+        let promise_results = vec![PromiseResult::NotReady, PromiseResult::Failed];
+        let blockchain = MockedBlockchain::new(context, Default::default(), promise_results);
+        with_blockchain(Box::new(blockchain), || {
+            assert_eq!(
+                try_predecessor_account_id(),
+                Err(EnvError::InvalidAccountId(b"Alice".to_vec()))
+            );
+            assert_eq!(try_promise_result(0), Err(EnvError::PromiseResultNotReady(0)));
+            assert_eq!(try_promise_result(1), Err(EnvError::PromiseFailed(1)));
+            assert_eq!(try_storage_write(b"key", b"value"), Ok(false));
+            assert_eq!(try_storage_read(b"key"), Ok(Some(b"value".to_vec())));
+        });
+    }
+
+    #[cfg(feature = "ripemd160")]
+    #[test]
+    fn test_ripemd160() {
+        crate::testing_env!(VMContextBuilder::new().build());
+        assert_eq!(
+            ripemd160(b"abc"),
+            b"\x8e\xb2\x08\xf7\xe0\x5d\x98\x7a\x9b\x04\x4a\x8e\x98\xc6\xb0\x87\xf1\x5a\x0b\xfc"
+        );
+    }
+
+    #[cfg(feature = "ecrecover")]
+    #[test]
+    fn test_ecrecover() {
+        crate::testing_env!(VMContextBuilder::new().build());
+        assert_eq!(try_ecrecover(&[1; 32], &[0; 64], 0, false), Ok(None));
+        assert_eq!(read_register(ATOMIC_OP_REGISTER), None);
+        let result = std::panic::catch_unwind(|| ecrecover(&[1; 32], &[1; 64], 4, false));
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "ed25519-verify")]
+    #[test]
+    fn test_ed25519_verify() {
+        crate::testing_env!(VMContextBuilder::new().build());
+        // Test 1 of RFC 8032.
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(
+            &bs58::decode("FVen3X669xLzsi6N2V91DoiyzHzg1uAgqiT8jZ9nS96Z").into_vec().unwrap(),
+        );
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&bs58::decode("5awYiUvGiDFA33EJjj4TXJG44a5afJc8QjWRpGgQiu6b23jCr7yndW2fmp9ujwqJVe32J456wV3VF78Asb1obnTc").into_vec().unwrap());
+        assert!(ed25519_verify(&signature, b"", &public_key));
+        assert!(!ed25519_verify(&signature, b"message", &public_key));
+    }
+
+    #[cfg(feature = "alt-bn128")]
+    #[test]
+    fn test_alt_bn128_groth16() {
+        use crate::test_utils::crypto::alt_bn128::g2_generator_mul;
+
+        crate::testing_env!(VMContextBuilder::new().build());
+        let scalar = |value: u64| {
+            let mut scalar = [0u8; 32];
+            scalar[..8].copy_from_slice(&value.to_le_bytes());
+            scalar
+        };
+        let mut one = G1Point::default();
+        one.x[0] = 1;
+        one.y[0] = 2;
+        let g1 = |value: u64| alt_bn128_g1_multiexp(&[(one, scalar(value))]);
+        let g2 = |value: u64| {
+            let bytes = g2_generator_mul(value);
+            let mut point = G2Point::default();
+            for (i, c) in point.x.iter_mut().chain(point.y.iter_mut()).enumerate() {
+                c.copy_from_slice(&bytes[32 * i..32 * i + 32]);
+            }
+            point
+        };
+
+        // The verifying key of a circuit with one public input, given by the discrete logarithms
+        // alpha = 5, beta = 7, gamma = 11, delta = 13 and the input commitments 17 and 19.
+        let (alpha, beta, gamma, delta) = (g1(5), g2(7), g2(11), g2(13));
+        let ic = [g1(17), g1(19)];
+        let verify = |a: G1Point, b: G2Point, c: G1Point, input: u64| {
+            let l = alt_bn128_g1_multiexp(&[(ic[0], scalar(1)), (ic[1], scalar(input))]);
+            let minus_a = alt_bn128_g1_sum(&[(true, a)]);
+            alt_bn128_pairing_check(&[(minus_a, b), (alpha, beta), (l, gamma), (c, delta)])
+        };
+        // a b = alpha beta + (17 + 19 input) gamma + c delta for a = 2, b = 2521, c = 1 and the
+        // input 23.
+        assert!(verify(g1(2), g2(2521), g1(1), 23));
+        assert!(!verify(g1(2), g2(2521), g1(1), 24));
+        assert!(!verify(g1(2), g2(2521), g1(2), 23));
+
+        assert_eq!(alt_bn128_g1_sum(&[(false, g1(3)), (true, g1(3))]), G1Point::default());
+        let mut invalid = g1(1);
+        invalid.y[0] ^= 1;
+        let result = std::panic::catch_unwind(|| alt_bn128_g1_sum(&[(false, invalid)]));
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "alt-bn128")]
+    #[test]
+    fn test_g1_point_from_short_register() {
+        crate::testing_env!(VMContextBuilder::new().build());
+        // The hash is shorter than a point.
+        with_interface(|b| unsafe { b.sha256(3, b"abc".as_ptr() as _, ATOMIC_OP_REGISTER) });
+        assert_eq!(g1_point_from_register(), Err(EnvError::InvalidRegisterLength(32)));
+    }
 }
@@ -0,0 +1,75 @@
+//! The list of the host functions of `BlockchainInterface` for generating their declarations and
+//! the interfaces that delegate to the host or to another interface.
+
+/// Calls the macro `$m` with the signatures of all the host functions of `BlockchainInterface`,
+/// without the receiver, as `fn name(arg: u64, ...) -> u64;` items. The host functions that are
+/// gated behind a cargo feature carry their `#[cfg]` attribute, which the macro has to apply to
+/// the items it generates for them.
+macro_rules! for_each_host_function {
+    ($m:ident) => {
+        $m! {
+            fn read_register(register_id: u64, ptr: u64);
+            fn register_len(register_id: u64) -> u64;
+            fn current_account_id(register_id: u64);
+            fn signer_account_id(register_id: u64);
+            fn signer_account_pk(register_id: u64);
+            fn predecessor_account_id(register_id: u64);
+            fn input(register_id: u64);
+            fn block_index() -> u64;
+            fn block_timestamp() -> u64;
+            fn epoch_height() -> u64;
+            fn storage_usage() -> u64;
+            fn account_balance(balance_ptr: u64);
+            fn account_locked_balance(balance_ptr: u64);
+            fn attached_deposit(balance_ptr: u64);
+            fn prepaid_gas() -> u64;
+            fn used_gas() -> u64;
+            fn random_seed(register_id: u64);
+            fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
+            fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
+            fn keccak512(value_len: u64, value_ptr: u64, register_id: u64);
+            #[cfg(feature = "ripemd160")]
+            fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64);
+            #[cfg(feature = "ecrecover")]
+            fn ecrecover(hash_len: u64, hash_ptr: u64, sig_len: u64, sig_ptr: u64, v: u64, malleability_flag: u64, register_id: u64) -> u64;
+            #[cfg(feature = "ed25519-verify")]
+            fn ed25519_verify(signature_len: u64, signature_ptr: u64, message_len: u64, message_ptr: u64, public_key_len: u64, public_key_ptr: u64) -> u64;
+            #[cfg(feature = "alt-bn128")]
+            fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+            #[cfg(feature = "alt-bn128")]
+            fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
+            #[cfg(feature = "alt-bn128")]
+            fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+            fn value_return(value_len: u64, value_ptr: u64);
+            fn panic();
+            fn panic_utf8(len: u64, ptr: u64);
+            fn log_utf8(len: u64, ptr: u64);
+            fn log_utf16(len: u64, ptr: u64);
+            fn promise_create(account_id_len: u64, account_id_ptr: u64, method_name_len: u64, method_name_ptr: u64, arguments_len: u64, arguments_ptr: u64, amount_ptr: u64, gas: u64) -> u64;
+            fn promise_then(promise_index: u64, account_id_len: u64, account_id_ptr: u64, method_name_len: u64, method_name_ptr: u64, arguments_len: u64, arguments_ptr: u64, amount_ptr: u64, gas: u64) -> u64;
+            fn promise_and(promise_idx_ptr: u64, promise_idx_count: u64) -> u64;
+            fn promise_batch_create(account_id_len: u64, account_id_ptr: u64) -> u64;
+            fn promise_batch_then(promise_index: u64, account_id_len: u64, account_id_ptr: u64) -> u64;
+            fn promise_batch_action_create_account(promise_index: u64);
+            fn promise_batch_action_deploy_contract(promise_index: u64, code_len: u64, code_ptr: u64);
+            fn promise_batch_action_function_call(promise_index: u64, method_name_len: u64, method_name_ptr: u64, arguments_len: u64, arguments_ptr: u64, amount_ptr: u64, gas: u64);
+            #[cfg(feature = "function-call-weight")]
+            fn promise_batch_action_function_call_weight(promise_index: u64, method_name_len: u64, method_name_ptr: u64, arguments_len: u64, arguments_ptr: u64, amount_ptr: u64, gas: u64, gas_weight: u64);
+            fn promise_batch_action_transfer(promise_index: u64, amount_ptr: u64);
+            fn promise_batch_action_stake(promise_index: u64, amount_ptr: u64, public_key_len: u64, public_key_ptr: u64);
+            fn promise_batch_action_add_key_with_full_access(promise_index: u64, public_key_len: u64, public_key_ptr: u64, nonce: u64);
+            fn promise_batch_action_add_key_with_function_call(promise_index: u64, public_key_len: u64, public_key_ptr: u64, nonce: u64, allowance_ptr: u64, receiver_id_len: u64, receiver_id_ptr: u64, method_names_len: u64, method_names_ptr: u64);
+            fn promise_batch_action_delete_key(promise_index: u64, public_key_len: u64, public_key_ptr: u64);
+            fn promise_batch_action_delete_account(promise_index: u64, beneficiary_id_len: u64, beneficiary_id_ptr: u64);
+            fn promise_results_count() -> u64;
+            fn promise_result(result_idx: u64, register_id: u64) -> u64;
+            fn promise_return(promise_id: u64);
+            fn storage_write(key_len: u64, key_ptr: u64, value_len: u64, value_ptr: u64, register_id: u64) -> u64;
+            fn storage_read(key_len: u64, key_ptr: u64, register_id: u64) -> u64;
+            fn storage_remove(key_len: u64, key_ptr: u64, register_id: u64) -> u64;
+            fn storage_has_key(key_len: u64, key_ptr: u64) -> u64;
+            fn validator_stake(account_id_len: u64, account_id_ptr: u64, stake_ptr: u64);
+            fn validator_total_stake(stake_ptr: u64);
+        }
+    };
+}
@@ -1,2 +1,14 @@
+#[macro_use]
+mod host_functions;
+
 pub mod blockchain_interface;
 pub mod env;
+mod context_cache;
+
+#[cfg(target_arch = "wasm32")]
+mod near_blockchain;
+#[cfg(target_arch = "wasm32")]
+pub use near_blockchain::NearBlockchain;
+
+#[cfg(target_arch = "wasm32")]
+pub mod sys;
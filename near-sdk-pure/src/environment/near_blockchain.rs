@@ -0,0 +1,23 @@
+use crate::environment::blockchain_interface::BlockchainInterface;
+use crate::environment::sys;
+
+/// Implementation of the blockchain interface that contracts actually use during the execution
+/// of the contract. It has no state, so `env` calls the host functions through it without any
+/// allocation or dynamic dispatch.
+pub struct NearBlockchain;
+
+macro_rules! impl_near_blockchain {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        impl BlockchainInterface for NearBlockchain {
+            $(
+                $(#[$meta])*
+                #[inline(always)]
+                unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    sys::$name($($arg),*)
+                }
+            )*
+        }
+    };
+}
+
+for_each_host_function!(impl_near_blockchain);
@@ -0,0 +1,11 @@
+//! The host functions imported by the contracts compiled to `wasm32`.
+
+macro_rules! declare_host_functions {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        extern "C" {
+            $($(#[$meta])* pub fn $name($($arg: $ty),*) $(-> $ret)?;)*
+        }
+    };
+}
+
+for_each_host_function!(declare_host_functions);
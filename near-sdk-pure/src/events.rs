@@ -0,0 +1,49 @@
+//! Structured events in the NEP-297 format, which the indexers read from the logs of the form
+//! `EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[...]}`.
+use alloc::format;
+use alloc::string::String;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::env;
+
+/// Prefix of the logs that carry events as JSON.
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
+/// Event of a standard, usually implemented through `#[near_event]` on an enum whose variants are
+/// the events and whose fields are their data.
+pub trait NearEvent: Serialize + DeserializeOwned {
+    const STANDARD: &'static str;
+    const VERSION: &'static str;
+
+    /// The event as the JSON of its log, without the prefix.
+    fn to_event_json(&self) -> String {
+        let log = EventLog { standard: Self::STANDARD, version: Self::VERSION, event: self };
+        serde_json::to_string(&log).expect("Failed to serialize the event using JSON.")
+    }
+
+    /// Logs the event with the `EVENT_JSON:` prefix.
+    fn emit(&self) {
+        env::log(format!("{}{}", EVENT_JSON_PREFIX, self.to_event_json()).as_bytes())
+    }
+
+    /// Parses the event from the JSON of its log. Returns `None` if the JSON is an event of
+    /// another standard or version, or does not match the type.
+    fn from_event_json(value: &serde_json::Value) -> Option<Self> {
+        let mut object = value.as_object()?.clone();
+        if object.remove("standard")? != Self::STANDARD
+            || object.remove("version")? != Self::VERSION
+        {
+            return None;
+        }
+        serde_json::from_value(serde_json::Value::Object(object)).ok()
+    }
+}
+
+#[derive(Serialize)]
+struct EventLog<'a, T> {
+    standard: &'a str,
+    version: &'a str,
+    #[serde(flatten)]
+    event: &'a T,
+}
@@ -3,7 +3,6 @@ use serde::Serialize;
 use core::convert::{TryFrom, TryInto};
 use alloc::string::{String, ToString};
 
-use crate::env::is_valid_account_id;
 use crate::AccountId;
 
 /// Helper class to validate account ID during serialization and deserializiation
@@ -12,12 +11,6 @@ use crate::AccountId;
 )]
 pub struct ValidAccountId(AccountId);
 
-impl ValidAccountId {
-    fn is_valid(&self) -> bool {
-        is_valid_account_id(&self.0.as_bytes())
-    }
-}
-
 impl AsRef<AccountId> for ValidAccountId {
     fn as_ref(&self) -> &AccountId {
         &self.0
@@ -47,12 +40,13 @@ impl TryFrom<String> for ValidAccountId {
     type Error = String;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let res = Self(value);
-        if res.is_valid() {
-            Ok(res)
-        } else {
-            Err("The account ID is invalid".into())
-        }
+        AccountId::try_from(value).map(Self).map_err(|_| "The account ID is invalid".into())
+    }
+}
+
+impl From<AccountId> for ValidAccountId {
+    fn from(value: AccountId) -> Self {
+        Self(value)
     }
 }
 
@@ -5,6 +5,9 @@ extern crate alloc;
 #[cfg(test)]
 extern crate quickcheck;
 
+#[cfg(not(target_arch = "wasm32"))]
+extern crate std;
+
 
 pub mod maybestd {
     pub use borsh::maybestd::*;
@@ -13,20 +16,34 @@ pub mod maybestd {
 
 
 pub use near_sdk_pure_macros::{
-    callback, callback_vec, ext_contract, init, metadata, near_bindgen, result_serializer,
-    serializer, PanicOnDefault,
+    callback, callback_vec, ext_contract, init, metadata, near_bindgen, near_event,
+    result_serializer, serializer, PanicOnDefault,
 };
 
 pub mod collections;
+#[macro_use]
 mod environment;
 pub use environment::env;
+#[cfg(target_arch = "wasm32")]
+pub use environment::{sys, NearBlockchain};
 
 mod promise;
-pub use promise::{Promise, PromiseOrValue};
+pub use promise::{Promise, PromiseAction, PromiseDescription, PromiseOrValue};
 
 mod metadata;
 pub use metadata::{Metadata, MethodMetadata};
 
+pub mod events;
+pub use events::NearEvent;
+
+pub mod storage;
+pub use storage::{StorageError, StorageGuard};
+
+pub mod state;
+pub use state::{ContractState, Migrate};
+
+pub mod upgrade;
+
 pub mod json_types;
 pub mod types;
 
@@ -40,6 +57,9 @@ pub use environment::blockchain_interface::BlockchainInterface;
 
 pub mod test_utils;
 
+#[cfg(any(feature = "bump-allocator", feature = "free-list-allocator"))]
+pub mod allocator;
+
 // Exporting common crates
 
 #[doc(hidden)]
@@ -1,10 +1,19 @@
 use borsh::BorshSchema;
-use crate::types::{AccountId, Balance, Gas, PromiseIndex, PublicKey};
+use crate::types::{AccountId, Balance, Gas, GasWeight, PromiseIndex, PublicKey};
 use core::cell::RefCell;
 use crate::maybestd::collections::HashMap;
 use crate::maybestd::io::{Error, Write};
-use alloc::{rc::Rc, vec::Vec, vec};
+use alloc::{boxed::Box, rc::Rc, vec::Vec, vec};
 
+/// Gas kept for the fees of scheduling every function call of the tree, when the unused gas is
+/// split by the SDK.
+#[cfg(not(feature = "function-call-weight"))]
+const GAS_PER_SCHEDULED_CALL: Gas = Gas::from_tgas(5);
+/// Gas kept for the rest of the current call, when the unused gas is split by the SDK.
+#[cfg(not(feature = "function-call-weight"))]
+const GAS_FOR_REST_OF_CALL: Gas = Gas::from_tgas(5);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PromiseAction {
     CreateAccount,
     DeployContract {
@@ -16,6 +25,15 @@ pub enum PromiseAction {
         amount: Balance,
         gas: Gas,
     },
+    /// A function call that also gets a share of the unused gas, see
+    /// `Promise::function_call_weight`.
+    FunctionCallWeight {
+        method_name: Vec<u8>,
+        arguments: Vec<u8>,
+        amount: Balance,
+        gas: Gas,
+        weight: GasWeight,
+    },
     Transfer {
         amount: Balance,
     },
@@ -57,6 +75,28 @@ impl PromiseAction {
                     *gas,
                 )
             }
+            #[cfg(feature = "function-call-weight")]
+            FunctionCallWeight { method_name, arguments, amount, gas, weight } => {
+                crate::env::promise_batch_action_function_call_weight(
+                    promise_index,
+                    method_name,
+                    arguments,
+                    *amount,
+                    *gas,
+                    *weight,
+                )
+            }
+            // The share of the unused gas is added to `gas` by `Promise` before it is scheduled.
+            #[cfg(not(feature = "function-call-weight"))]
+            FunctionCallWeight { method_name, arguments, amount, gas, .. } => {
+                crate::env::promise_batch_action_function_call(
+                    promise_index,
+                    method_name,
+                    arguments,
+                    *amount,
+                    *gas,
+                )
+            }
             Transfer { amount } => {
                 crate::env::promise_batch_action_transfer(promise_index, *amount)
             }
@@ -90,6 +130,42 @@ impl PromiseAction {
     }
 }
 
+/// A read-only view of the tree of a `Promise`: the receivers with their actions, the `then`
+/// chains and the `and` joins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PromiseDescription {
+    /// A batch of actions executed on `receiver_id` after the promise `after`, if any, completes.
+    Single {
+        receiver_id: AccountId,
+        actions: Vec<PromiseAction>,
+        after: Option<Box<PromiseDescription>>,
+    },
+    /// A promise that completes when all of the given promises complete.
+    Joint(Vec<PromiseDescription>),
+}
+
+impl PromiseDescription {
+    /// Describes a batch of actions on the given account, for building the expected trees in
+    /// tests.
+    pub fn new(receiver_id: AccountId, actions: Vec<PromiseAction>) -> Self {
+        PromiseDescription::Single { receiver_id, actions, after: None }
+    }
+
+    /// Same as `Promise::and`.
+    pub fn and(self, other: PromiseDescription) -> Self {
+        PromiseDescription::Joint(vec![self, other])
+    }
+
+    /// Same as `Promise::then`.
+    pub fn then(self, mut other: PromiseDescription) -> Self {
+        match &mut other {
+            PromiseDescription::Single { after, .. } => *after = Some(Box::new(self)),
+            PromiseDescription::Joint(_) => panic!("Cannot callback joint promise."),
+        }
+        other
+    }
+}
+
 pub struct PromiseSingle {
     pub account_id: AccountId,
     pub actions: RefCell<Vec<PromiseAction>>,
@@ -209,6 +285,31 @@ impl Promise {
         self.add_action(PromiseAction::FunctionCall { method_name, arguments, amount, gas })
     }
 
+    /// A function call that gets `gas` and, on top of it, a share of the gas that the current call
+    /// leaves unused, so the callee does not have to be given a guessed amount. The unused gas is
+    /// split between the weighted calls proportionally to their `weight`.
+    ///
+    /// With the `function-call-weight` feature the runtime splits the gas left at the end of the
+    /// call. Otherwise the SDK splits `env::remaining_gas()` when the promise is scheduled, among
+    /// the weighted calls of its tree, keeping some gas for scheduling the calls and for the rest
+    /// of the current call. The promises scheduled after it then get only their static gas.
+    pub fn function_call_weight(
+        self,
+        method_name: Vec<u8>,
+        arguments: Vec<u8>,
+        amount: Balance,
+        gas: Gas,
+        weight: GasWeight,
+    ) -> Self {
+        self.add_action(PromiseAction::FunctionCallWeight {
+            method_name,
+            arguments,
+            amount,
+            gas,
+            weight,
+        })
+    }
+
     /// Transfer tokens to the account that this promise acts on.
     pub fn transfer(self, amount: Balance) -> Self {
         self.add_action(PromiseAction::Transfer { amount })
@@ -273,6 +374,21 @@ impl Promise {
         other
     }
 
+    /// Returns the tree of receivers and actions that this promise schedules when it is dropped,
+    /// without scheduling anything.
+    pub fn describe(&self) -> PromiseDescription {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => PromiseDescription::Single {
+                receiver_id: x.account_id.clone(),
+                actions: x.actions.borrow().clone(),
+                after: x.after.borrow().as_ref().map(|after| Box::new(after.describe())),
+            },
+            PromiseSubtype::Joint(x) => {
+                PromiseDescription::Joint(vec![x.promise_a.describe(), x.promise_b.describe()])
+            }
+        }
+    }
+
     /// A specialized, relatively low-level API method. Allows to mark the given promise as the one
     /// that should be considered as a return value.
     ///
@@ -292,11 +408,11 @@ impl Promise {
     /// #[near_bindgen]
     /// impl ContractA {
     ///     pub fn a1(&self) {
-    ///        contract_b::b(&"bob_near".to_string(), 0, 1_000).as_return();
+    ///        contract_b::b(&"bob_near".parse().unwrap(), 0, 1_000.into()).as_return();
     ///     }
     ///
     ///     pub fn a2(&self) -> Promise {
-    ///        contract_b::b(&"bob_near".to_string(), 0, 1_000)
+    ///        contract_b::b(&"bob_near".parse().unwrap(), 0, 1_000.into())
     ///     }
     /// }
     /// ```
@@ -305,6 +421,81 @@ impl Promise {
         self
     }
 
+    /// Calls `f` on the actions of the promises of the tree that are not scheduled yet.
+    #[cfg(not(feature = "function-call-weight"))]
+    fn for_each_pending_action(&self, f: &mut dyn FnMut(&mut PromiseAction)) {
+        match &self.subtype {
+            PromiseSubtype::Single(x) => {
+                if x.promise_index.borrow().is_some() {
+                    return;
+                }
+                x.actions.borrow_mut().iter_mut().for_each(&mut *f);
+                if let Some(after) = x.after.borrow().as_ref() {
+                    after.for_each_pending_action(f);
+                }
+            }
+            PromiseSubtype::Joint(x) => {
+                if x.promise_index.borrow().is_some() {
+                    return;
+                }
+                x.promise_a.for_each_pending_action(f);
+                x.promise_b.for_each_pending_action(f);
+            }
+        }
+    }
+
+    /// Turns the weighted function calls of the tree into the plain ones, adding to their gas the
+    /// shares of the remaining gas that is not attached to any call of the tree.
+    #[cfg(not(feature = "function-call-weight"))]
+    fn distribute_unused_gas(&self) {
+        let mut calls = 0;
+        let mut static_gas = Gas::ZERO;
+        let mut total_weight = 0u64;
+        self.for_each_pending_action(&mut |action| match action {
+            PromiseAction::FunctionCall { gas, .. } => {
+                calls += 1;
+                static_gas = static_gas.saturating_add(*gas);
+            }
+            PromiseAction::FunctionCallWeight { gas, weight, .. } => {
+                calls += 1;
+                static_gas = static_gas.saturating_add(*gas);
+                total_weight = total_weight.saturating_add(weight.0);
+            }
+            _ => {}
+        });
+        if total_weight == 0 {
+            return;
+        }
+        let unused = crate::env::remaining_gas()
+            .saturating_sub(static_gas)
+            .saturating_sub(GAS_PER_SCHEDULED_CALL.saturating_mul(calls))
+            .saturating_sub(GAS_FOR_REST_OF_CALL);
+        let mut weight_so_far = 0u64;
+        let mut distributed = Gas::ZERO;
+        self.for_each_pending_action(&mut |action| {
+            let weight = match action {
+                PromiseAction::FunctionCallWeight { weight, .. } => weight.0,
+                _ => return,
+            };
+            // The shares are rounded down cumulatively, so they add up to exactly `unused`.
+            weight_so_far += weight;
+            let total = (unused.0 as u128 * weight_so_far as u128 / total_weight as u128) as u64;
+            let share = Gas(total) - distributed;
+            distributed = Gas(total);
+            if let PromiseAction::FunctionCallWeight {
+                method_name, arguments, amount, gas, ..
+            } = core::mem::replace(action, PromiseAction::CreateAccount)
+            {
+                *action = PromiseAction::FunctionCall {
+                    method_name,
+                    arguments,
+                    amount,
+                    gas: gas + share,
+                };
+            }
+        });
+    }
+
     fn construct_recursively(&self) -> PromiseIndex {
         let res = match &self.subtype {
             PromiseSubtype::Single(x) => x.construct_recursively(),
@@ -319,6 +510,8 @@ impl Promise {
 
 impl Drop for Promise {
     fn drop(&mut self) {
+        #[cfg(not(feature = "function-call-weight"))]
+        self.distribute_unused_gas();
         self.construct_recursively();
     }
 }
@@ -0,0 +1,196 @@
+//! The contract state, the struct of `#[near_bindgen]` stored with Borsh under the `STATE` key.
+//! With `#[near_bindgen(version = N)]` the version of the struct layout is stored alongside, and
+//! the state stored with an older version is converted into the current layout when it is read.
+use alloc::format;
+use borsh::{BorshDeserialize, BorshSerialize};
+use core::convert::TryInto;
+
+use crate::env;
+
+/// Key of the version of the state layout. The state stored without a version has version 0.
+pub const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// How the contract struct is read and written by the methods generated by `#[near_bindgen]`,
+/// which implements it for the struct: as is, or together with the version for the structs of
+/// `#[near_bindgen(version = N)]`.
+pub trait ContractState: BorshSerialize + BorshDeserialize {
+    /// Reads the state, `None` if the contract is not initialized yet.
+    fn state_read() -> Option<Self> {
+        env::state_read()
+    }
+
+    /// Writes the state.
+    fn state_write(&self) {
+        env::state_write(self)
+    }
+}
+
+/// Contract state with a versioned layout, implemented by `#[near_bindgen(version = N)]`. The
+/// states of the older versions listed as `migrate_from(StateV1 = 1, StateV2 = 2)` are converted
+/// into the current one with the `From<StateV1>` and `From<StateV2>` implementations.
+///
+/// ```
+/// # use near_sdk_pure::borsh::{self, BorshDeserialize, BorshSerialize};
+/// # use near_sdk_pure::near_bindgen;
+/// #[derive(BorshDeserialize)]
+/// pub struct CounterV1 {
+///     value: u32,
+/// }
+///
+/// #[near_bindgen(version = 2, migrate_from(CounterV1 = 1))]
+/// #[derive(Default, BorshDeserialize, BorshSerialize)]
+/// pub struct Counter {
+///     value: u64,
+///     calls: u64,
+/// }
+///
+/// impl From<CounterV1> for Counter {
+///     fn from(old: CounterV1) -> Self {
+///         Self { value: old.value.into(), calls: 0 }
+///     }
+/// }
+/// ```
+pub trait Migrate: BorshSerialize + BorshDeserialize {
+    /// The version of the current layout.
+    const VERSION: u32;
+
+    /// Converts the state stored with the older `version` into the current layout. Returns `None`
+    /// if the version is unknown.
+    fn migrate(version: u32, data: &[u8]) -> Option<Self>;
+}
+
+/// The version of the stored state layout, 0 if the state is stored without a version.
+pub fn state_version() -> u32 {
+    match env::storage_read(STATE_VERSION_KEY) {
+        Some(data) => match data.as_slice().try_into() {
+            Ok(bytes) => u32::from_le_bytes(bytes),
+            Err(_) => env::panic(b"Cannot deserialize the contract state version."),
+        },
+        None => 0,
+    }
+}
+
+/// Reads the state, converting it from the stored version into the current one.
+///
+/// # Panics
+///
+/// Panics if the stored version can not be migrated from.
+pub fn versioned_state_read<T: Migrate>() -> Option<T> {
+    let data = env::storage_read(env::STATE_KEY)?;
+    let version = state_version();
+    if version == T::VERSION {
+        return Some(T::try_from_slice(&data).expect("Cannot deserialize the contract state."));
+    }
+    match T::migrate(version, &data) {
+        Some(state) => Some(state),
+        None => env::panic(
+            format!("Cannot migrate the contract state from version {}.", version).as_bytes(),
+        ),
+    }
+}
+
+/// Writes the state together with its current version. The version is only written when the
+/// stored one differs, on the first write and after a migration, so that the other calls do not
+/// pay for the second write.
+pub fn versioned_state_write<T: Migrate>(state: &T) {
+    env::state_write(state);
+    if state_version() != T::VERSION {
+        env::storage_write(STATE_VERSION_KEY, &T::VERSION.to_le_bytes());
+    }
+}
+
+/// Converts the stored state into the current version and writes it back, which the `migrate`
+/// method generated by `#[near_bindgen(version = N)]` does. Returns the version that the state
+/// was stored with.
+///
+/// # Panics
+///
+/// Panics if the contract is not initialized or the stored version can not be migrated from.
+pub fn migrate_state<T: Migrate>() -> u32 {
+    let version = state_version();
+    let state: T = versioned_state_read()
+        .unwrap_or_else(|| env::panic(b"The contract state is not initialized."));
+    versioned_state_write(&state);
+    version
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct StateV1 {
+        value: u32,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct State {
+        value: u64,
+        calls: u64,
+    }
+
+    impl From<StateV1> for State {
+        fn from(old: StateV1) -> Self {
+            Self { value: old.value.into(), calls: 0 }
+        }
+    }
+
+    impl Migrate for State {
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, data: &[u8]) -> Option<Self> {
+            match version {
+                1 => Some(StateV1::try_from_slice(data).unwrap().into()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_versioned_state() {
+        testing_env!(VMContextBuilder::new().build());
+        assert_eq!(versioned_state_read::<State>(), None);
+
+        env::state_write(&StateV1 { value: 5 });
+        env::storage_write(STATE_VERSION_KEY, &1u32.to_le_bytes());
+        assert_eq!(versioned_state_read(), Some(State { value: 5, calls: 0 }));
+        // The state is only migrated in memory until it is written.
+        assert_eq!(state_version(), 1);
+
+        assert_eq!(migrate_state::<State>(), 1);
+        assert_eq!(state_version(), 2);
+        assert_eq!(env::state_read(), Some(State { value: 5, calls: 0 }));
+        versioned_state_write(&State { value: 6, calls: 1 });
+        assert_eq!(versioned_state_read(), Some(State { value: 6, calls: 1 }));
+    }
+
+    #[test]
+    fn test_version_written_once() {
+        testing_env!(VMContextBuilder::new().build());
+        versioned_state_write(&State { value: 1, calls: 1 });
+        assert_eq!(state_version(), 2);
+        let gas = |f: &dyn Fn()| {
+            let before = env::used_gas();
+            f();
+            env::used_gas() - before
+        };
+        // The version is read, but only the state is written again.
+        let versioned = gas(&|| versioned_state_write(&State { value: 2, calls: 2 }));
+        let state_only = gas(&|| {
+            env::state_write(&State { value: 3, calls: 3 });
+            state_version();
+        });
+        assert_eq!(versioned, state_only);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot migrate the contract state from version 0.")]
+    fn test_unknown_version() {
+        testing_env!(VMContextBuilder::new().build());
+        env::storage_remove(STATE_VERSION_KEY);
+        env::state_write(&StateV1 { value: 5 });
+        versioned_state_read::<State>();
+    }
+}
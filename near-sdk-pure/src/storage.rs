@@ -0,0 +1,221 @@
+//! Charging the callers for the storage that their calls take, from the attached deposit.
+use alloc::string::ToString;
+use core::fmt;
+
+use crate::types::{Balance, StorageUsage};
+use crate::{env, Promise};
+
+/// Error returned by `StorageGuard::try_finish`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageError {
+    /// The deposit does not cover the cost of the storage added by the call.
+    InsufficientDeposit {
+        /// The bytes added by the call.
+        bytes: StorageUsage,
+        /// The cost of the added bytes.
+        required: Balance,
+        /// The deposit that pays for the storage.
+        deposit: Balance,
+    },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::InsufficientDeposit { bytes, required, deposit } => write!(
+                f,
+                "The deposit of {} yoctoNEAR does not cover the storage of {} bytes, {} yoctoNEAR is required.",
+                deposit, bytes, required
+            ),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for StorageError {}
+
+/// Measures the storage that the call adds between the creation of the guard and `finish`, and
+/// pays for it from the deposit: the rest of the deposit is refunded to the predecessor, and the
+/// call panics if the deposit is not enough. The storage released by the call is not paid out.
+///
+/// `#[payable(storage)]` on a method of `#[near_bindgen]` wraps the whole method, including the
+/// state write, with the guard.
+///
+/// ```
+/// # use near_sdk_pure::{env, testing_env, StorageGuard};
+/// # use near_sdk_pure::test_utils::{RuntimeFeesConfig, VMContextBuilder};
+/// let byte_cost = RuntimeFeesConfig::default().storage_amount_per_byte;
+/// testing_env!(VMContextBuilder::new().attached_deposit(byte_cost * 100).build());
+/// let guard = StorageGuard::new();
+/// env::storage_write(b"key", b"value");
+/// // 40 bytes of the record overhead, the key and the value.
+/// assert_eq!(guard.finish(), byte_cost * (100 - 48));
+/// ```
+#[must_use = "the storage is only paid for when the guard is finished"]
+pub struct StorageGuard {
+    initial_usage: StorageUsage,
+    deposit: Balance,
+}
+
+impl StorageGuard {
+    /// Starts measuring the storage, with the attached deposit paying for it.
+    pub fn new() -> Self {
+        Self::with_deposit(env::attached_deposit())
+    }
+
+    /// Starts measuring the storage, with the given deposit paying for it, e.g. the part of the
+    /// attached deposit that is left after the other payments.
+    pub fn with_deposit(deposit: Balance) -> Self {
+        Self { initial_usage: env::storage_usage(), deposit }
+    }
+
+    /// The storage usage when the guard was created.
+    pub fn initial_usage(&self) -> StorageUsage {
+        self.initial_usage
+    }
+
+    /// The deposit that pays for the storage.
+    pub fn deposit(&self) -> Balance {
+        self.deposit
+    }
+
+    /// The bytes added since the guard was created, zero if the storage usage has decreased.
+    pub fn bytes_added(&self) -> StorageUsage {
+        env::storage_usage().saturating_sub(self.initial_usage)
+    }
+
+    /// The cost of the bytes added so far.
+    pub fn storage_cost(&self) -> Balance {
+        Balance::from(self.bytes_added()) * env::storage_byte_cost()
+    }
+
+    /// Pays for the added storage and transfers the rest of the deposit back to the predecessor.
+    /// Returns the refunded amount.
+    pub fn finish(self) -> Balance {
+        match self.try_finish() {
+            Ok(refund) => refund,
+            Err(err) => env::panic(err.to_string().as_bytes()),
+        }
+    }
+
+    /// Same as `finish`, but returns an error instead of panicking. Nothing is refunded on error.
+    pub fn try_finish(self) -> Result<Balance, StorageError> {
+        let bytes = self.bytes_added();
+        let required = self.storage_cost();
+        if required > self.deposit {
+            return Err(StorageError::InsufficientDeposit {
+                bytes,
+                required,
+                deposit: self.deposit,
+            });
+        }
+        let refund = self.deposit - required;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        Ok(refund)
+    }
+}
+
+impl Default for StorageGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{bob, with_mocked_blockchain, RuntimeFeesConfig, VMContextBuilder};
+    use crate::{testing_env, PromiseAction, PromiseDescription};
+    use alloc::vec;
+
+    const BYTE_COST: Balance = 10_000_000_000_000_000_000;
+
+    fn setup(deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    #[test]
+    fn test_refund() {
+        setup(BYTE_COST * 100);
+        let guard = StorageGuard::new();
+        env::storage_write(b"key", b"value");
+        assert_eq!(guard.bytes_added(), 48);
+        assert_eq!(guard.finish(), BYTE_COST * 52);
+        with_mocked_blockchain(|blockchain| {
+            assert_eq!(
+                blockchain.scheduled_promises(),
+                vec![PromiseDescription::new(
+                    bob(),
+                    vec![PromiseAction::Transfer { amount: BYTE_COST * 52 }]
+                )]
+            );
+        });
+    }
+
+    #[test]
+    fn test_exact_deposit_and_released_storage() {
+        setup(BYTE_COST * 48);
+        let guard = StorageGuard::new();
+        env::storage_write(b"key", b"value");
+        assert_eq!(guard.finish(), 0);
+        with_mocked_blockchain(|blockchain| assert!(blockchain.promises().is_empty()));
+
+        // Only the deposit is refunded when the call releases the storage.
+        let guard = StorageGuard::with_deposit(10);
+        env::storage_remove(b"key");
+        assert_eq!(guard.bytes_added(), 0);
+        assert_eq!(guard.try_finish(), Ok(10));
+    }
+
+    #[test]
+    fn test_insufficient_deposit() {
+        setup(BYTE_COST * 47);
+        let guard = StorageGuard::new();
+        env::storage_write(b"key", b"value");
+        assert_eq!(
+            guard.try_finish(),
+            Err(StorageError::InsufficientDeposit {
+                bytes: 48,
+                required: BYTE_COST * 48,
+                deposit: BYTE_COST * 47,
+            })
+        );
+        with_mocked_blockchain(|blockchain| assert!(blockchain.promises().is_empty()));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not cover the storage of 48 bytes")]
+    fn test_finish_panics() {
+        setup(0);
+        let guard = StorageGuard::new();
+        env::storage_write(b"key", b"value");
+        let _ = guard.finish();
+    }
+
+    #[test]
+    fn test_configured_byte_cost() {
+        let byte_cost = BYTE_COST * 10;
+        let context = VMContextBuilder::new()
+            .predecessor_account_id(bob())
+            .attached_deposit(byte_cost * 50)
+            .account_balance(byte_cost * 48)
+            .build();
+        let fees_config =
+            RuntimeFeesConfig { storage_amount_per_byte: byte_cost, ..Default::default() };
+        testing_env!(context, vec![], fees_config);
+        assert_eq!(env::storage_byte_cost(), byte_cost);
+        let guard = StorageGuard::new();
+        env::storage_write(b"key", b"value");
+        assert_eq!(guard.storage_cost(), byte_cost * 48);
+        assert_eq!(guard.finish(), byte_cost * 2);
+        // The balance covers the storage at the same price that the guard charges.
+        with_mocked_blockchain(|blockchain| assert_eq!(blockchain.check_storage_stake(), Ok(())));
+        env::storage_write(b"key2", b"value");
+        with_mocked_blockchain(|blockchain| assert!(blockchain.check_storage_stake().is_err()));
+    }
+}
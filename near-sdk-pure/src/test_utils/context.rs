@@ -0,0 +1,163 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::test_utils::{alice, bob, VMContext};
+use crate::types::{AccountId, Balance, BlockHeight, EpochHeight, Gas, PublicKey, StorageUsage};
+
+/// Simple context builder for the unit tests that run with `testing_env!`.
+///
+/// By default the call is made by `bob.near` to the contract deployed on `alice.near`, without
+/// deposit and with 300 Tgas attached.
+#[derive(Clone)]
+pub struct VMContextBuilder {
+    pub context: VMContext,
+}
+
+impl Default for VMContextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VMContextBuilder {
+    pub fn new() -> Self {
+        Self {
+            context: VMContext {
+                current_account_id: alice(),
+                signer_account_id: bob(),
+                signer_account_pk: vec![0, 1, 2],
+                predecessor_account_id: bob(),
+                input: vec![],
+                block_index: 0,
+                block_timestamp: 0,
+                epoch_height: 0,
+                account_balance: 10u128.pow(26),
+                account_locked_balance: 0,
+                storage_usage: 0,
+                attached_deposit: 0,
+                prepaid_gas: Gas::from_tgas(300),
+                random_seed: vec![0, 1, 2],
+                validators: Default::default(),
+            },
+        }
+    }
+
+    pub fn current_account_id(&mut self, account_id: AccountId) -> &mut Self {
+        self.context.current_account_id = account_id;
+        self
+    }
+
+    pub fn signer_account_id(&mut self, account_id: AccountId) -> &mut Self {
+        self.context.signer_account_id = account_id;
+        self
+    }
+
+    pub fn signer_account_pk(&mut self, pk: PublicKey) -> &mut Self {
+        self.context.signer_account_pk = pk;
+        self
+    }
+
+    pub fn predecessor_account_id(&mut self, account_id: AccountId) -> &mut Self {
+        self.context.predecessor_account_id = account_id;
+        self
+    }
+
+    pub fn input(&mut self, input: Vec<u8>) -> &mut Self {
+        self.context.input = input;
+        self
+    }
+
+    pub fn block_index(&mut self, block_index: BlockHeight) -> &mut Self {
+        self.context.block_index = block_index;
+        self
+    }
+
+    pub fn block_timestamp(&mut self, time: u64) -> &mut Self {
+        self.context.block_timestamp = time;
+        self
+    }
+
+    pub fn epoch_height(&mut self, epoch: EpochHeight) -> &mut Self {
+        self.context.epoch_height = epoch;
+        self
+    }
+
+    pub fn account_balance(&mut self, amount: Balance) -> &mut Self {
+        self.context.account_balance = amount;
+        self
+    }
+
+    pub fn account_locked_balance(&mut self, amount: Balance) -> &mut Self {
+        self.context.account_locked_balance = amount;
+        self
+    }
+
+    pub fn storage_usage(&mut self, usage: StorageUsage) -> &mut Self {
+        self.context.storage_usage = usage;
+        self
+    }
+
+    pub fn attached_deposit(&mut self, amount: Balance) -> &mut Self {
+        self.context.attached_deposit = amount;
+        self
+    }
+
+    pub fn prepaid_gas(&mut self, gas: Gas) -> &mut Self {
+        self.context.prepaid_gas = gas;
+        self
+    }
+
+    pub fn random_seed(&mut self, seed: Vec<u8>) -> &mut Self {
+        self.context.random_seed = seed;
+        self
+    }
+
+    pub fn validator_stake(&mut self, account_id: AccountId, stake: Balance) -> &mut Self {
+        self.context.validators.insert(account_id, stake);
+        self
+    }
+
+    pub fn build(&self) -> VMContext {
+        self.context.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::carol;
+    use crate::{env, testing_env, PromiseResult};
+
+    #[test]
+    fn test_builder() {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(carol())
+            .attached_deposit(5)
+            .block_timestamp(42)
+            .build());
+        assert_eq!(env::current_account_id(), alice());
+        assert_eq!(env::signer_account_id(), bob());
+        assert_eq!(env::predecessor_account_id(), carol());
+        assert_eq!(env::attached_deposit(), 5);
+        assert_eq!(env::block_timestamp(), 42);
+        assert_eq!(env::prepaid_gas(), Gas::from_tgas(300));
+    }
+
+    #[test]
+    fn test_storage_persists_between_calls() {
+        testing_env!(VMContextBuilder::new().build());
+        env::storage_write(b"key", b"value");
+        testing_env!(
+            VMContextBuilder::new().block_index(1).build(),
+            vec![PromiseResult::Successful(b"1".to_vec())]
+        );
+        assert_eq!(env::block_index(), 1);
+        assert_eq!(env::storage_read(b"key"), Some(b"value".to_vec()));
+        assert_eq!(env::storage_usage(), 3 + 5 + 40);
+        assert_eq!(env::promise_results_count(), 1);
+
+        // The removal of the record carried over releases its storage.
+        env::storage_remove(b"key");
+        assert_eq!(env::storage_usage(), 0);
+    }
+}
@@ -0,0 +1,656 @@
+//! Arithmetic of the alt_bn128 (BN254) curve: the group law of G1 and the pairing check.
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::{Add, Mul, Neg, Sub};
+
+use super::uint::U256;
+
+/// Modulus of the base field.
+const P: U256 = U256([
+    0x3c20_8c16_d87c_fd47,
+    0x9781_6a91_6871_ca8d,
+    0xb850_45b6_8181_585d,
+    0x3064_4e72_e131_a029,
+]);
+
+/// `-P^-1 mod 2^64`, for the Montgomery reduction.
+const P_INV: u64 = 0x87d2_0782_e486_6389;
+
+/// `2^512 mod P`, which converts the integers into the Montgomery form.
+const R2: U256 = U256([
+    0xf32c_fc5b_538a_fa89,
+    0xb5e7_1911_d445_01fb,
+    0x47ab_1eff_0a41_7ff6,
+    0x06d8_9f71_cab8_351f,
+]);
+
+/// Order of the G1 and G2 groups.
+const R: U256 = U256([
+    0x43e1_f593_f000_0001,
+    0x2833_e848_79b9_7091,
+    0xb850_45b6_8181_585d,
+    0x3064_4e72_e131_a029,
+]);
+
+/// Loop length of the ate pairing, `t - 1 = 6 u^2` for the curve parameter `u`.
+const ATE_LOOP: u128 = 0x6f4d_8248_eeb8_59fb_f83e_9682_e87c_fd46;
+
+/// Size of an item of `alt_bn128_g1_multiexp`: a G1 point and a scalar.
+pub(crate) const G1_MULTIEXP_ITEM_LEN: u64 = 96;
+/// Size of an item of `alt_bn128_g1_sum`: a sign byte and a G1 point.
+pub(crate) const G1_SUM_ITEM_LEN: u64 = 65;
+/// Size of an item of `alt_bn128_pairing_check`: a G1 point and a G2 point.
+pub(crate) const PAIRING_CHECK_ITEM_LEN: u64 = 192;
+
+/// `a b 2^-256 mod P`, the Montgomery multiplication.
+fn mont_mul(a: &U256, b: &U256) -> U256 {
+    let mut t = [0u64; 6];
+    for b_i in b.0.iter() {
+        let mut carry = 0u128;
+        for (j, a_j) in a.0.iter().enumerate() {
+            let s = t[j] as u128 + *a_j as u128 * *b_i as u128 + carry;
+            t[j] = s as u64;
+            carry = s >> 64;
+        }
+        let s = t[4] as u128 + carry;
+        t[4] = s as u64;
+        t[5] = (s >> 64) as u64;
+        // Add the multiple of P that zeroes the lowest limb and shift by one limb.
+        let m = t[0].wrapping_mul(P_INV);
+        let mut carry = (t[0] as u128 + m as u128 * P.0[0] as u128) >> 64;
+        for j in 1..4 {
+            let s = t[j] as u128 + m as u128 * P.0[j] as u128 + carry;
+            t[j - 1] = s as u64;
+            carry = s >> 64;
+        }
+        let s = t[4] as u128 + carry;
+        t[3] = s as u64;
+        t[4] = t[5] + (s >> 64) as u64;
+    }
+    let result = U256([t[0], t[1], t[2], t[3]]);
+    if t[4] != 0 || result >= P {
+        result.overflowing_sub(&P).0
+    } else {
+        result
+    }
+}
+
+trait Field:
+    Copy + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    fn zero() -> Self;
+    fn inv(self) -> Self;
+
+    fn is_zero(self) -> bool {
+        self == Self::zero()
+    }
+}
+
+/// Element of the base field in the Montgomery form `a 2^256 mod P`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fp(U256);
+
+impl Fp {
+    /// The field element of the integer, `None` if it is not less than `P`.
+    fn new(value: &U256) -> Option<Fp> {
+        if *value >= P {
+            None
+        } else {
+            Some(Fp(mont_mul(value, &R2)))
+        }
+    }
+
+    fn from_u64(value: u64) -> Fp {
+        Fp(mont_mul(&U256::from_u64(value), &R2))
+    }
+
+    fn to_u256(self) -> U256 {
+        mont_mul(&self.0, &U256::ONE)
+    }
+}
+
+impl Add for Fp {
+    type Output = Fp;
+
+    fn add(self, other: Fp) -> Fp {
+        Fp(super::uint::add_mod(&self.0, &other.0, &P))
+    }
+}
+
+impl Sub for Fp {
+    type Output = Fp;
+
+    fn sub(self, other: Fp) -> Fp {
+        Fp(super::uint::sub_mod(&self.0, &other.0, &P))
+    }
+}
+
+impl Mul for Fp {
+    type Output = Fp;
+
+    fn mul(self, other: Fp) -> Fp {
+        Fp(mont_mul(&self.0, &other.0))
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+
+    fn neg(self) -> Fp {
+        Fp::zero() - self
+    }
+}
+
+impl Field for Fp {
+    fn zero() -> Self {
+        Fp(U256::ZERO)
+    }
+
+    fn inv(self) -> Self {
+        let exponent = P.overflowing_sub(&U256::from_u64(2)).0;
+        let mut result = Fp::from_u64(1);
+        for i in (0..256).rev() {
+            result = result * result;
+            if exponent.bit(i) {
+                result = result * self;
+            }
+        }
+        result
+    }
+}
+
+/// Element `c0 + c1 i` of the quadratic extension, where `i^2 = -1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fp2 {
+    c0: Fp,
+    c1: Fp,
+}
+
+impl Fp2 {
+    fn from_fp(c0: Fp) -> Fp2 {
+        Fp2 { c0, c1: Fp::zero() }
+    }
+
+    /// `9 + i`, the non-residue that defines the twist and the degree 12 extension.
+    fn xi() -> Fp2 {
+        Fp2 { c0: Fp::from_u64(9), c1: Fp::from_u64(1) }
+    }
+}
+
+impl Add for Fp2 {
+    type Output = Fp2;
+
+    fn add(self, other: Fp2) -> Fp2 {
+        Fp2 { c0: self.c0 + other.c0, c1: self.c1 + other.c1 }
+    }
+}
+
+impl Sub for Fp2 {
+    type Output = Fp2;
+
+    fn sub(self, other: Fp2) -> Fp2 {
+        Fp2 { c0: self.c0 - other.c0, c1: self.c1 - other.c1 }
+    }
+}
+
+impl Mul for Fp2 {
+    type Output = Fp2;
+
+    fn mul(self, other: Fp2) -> Fp2 {
+        Fp2 {
+            c0: self.c0 * other.c0 - self.c1 * other.c1,
+            c1: self.c0 * other.c1 + self.c1 * other.c0,
+        }
+    }
+}
+
+impl Neg for Fp2 {
+    type Output = Fp2;
+
+    fn neg(self) -> Fp2 {
+        Fp2 { c0: -self.c0, c1: -self.c1 }
+    }
+}
+
+impl Field for Fp2 {
+    fn zero() -> Self {
+        Fp2::from_fp(Fp::zero())
+    }
+
+    fn inv(self) -> Self {
+        let norm_inv = (self.c0 * self.c0 + self.c1 * self.c1).inv();
+        Fp2 { c0: self.c0 * norm_inv, c1: -self.c1 * norm_inv }
+    }
+}
+
+/// Element `c_0 + c_1 w + ... + c_5 w^5` of the degree 12 extension, where `w^6 = 9 + i`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Fp12([Fp2; 6]);
+
+impl Fp12 {
+    fn one() -> Fp12 {
+        let mut c = [Fp2::zero(); 6];
+        c[0] = Fp2::from_fp(Fp::from_u64(1));
+        Fp12(c)
+    }
+
+    fn pow(self, exponent: &[u64]) -> Fp12 {
+        let mut result = Fp12::one();
+        for i in (0..64 * exponent.len()).rev() {
+            result = result * result;
+            if (exponent[i / 64] >> (i % 64)) & 1 == 1 {
+                result = result * self;
+            }
+        }
+        result
+    }
+}
+
+impl Mul for Fp12 {
+    type Output = Fp12;
+
+    fn mul(self, other: Fp12) -> Fp12 {
+        let mut wide = [Fp2::zero(); 11];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                wide[i + j] = wide[i + j] + *a * *b;
+            }
+        }
+        let xi = Fp2::xi();
+        let mut result = [Fp2::zero(); 6];
+        for (k, c) in result.iter_mut().enumerate() {
+            *c = if k < 5 { wide[k] + wide[k + 6] * xi } else { wide[k] };
+        }
+        Fp12(result)
+    }
+}
+
+/// Affine point of a curve `y^2 = x^3 + b`, `None` for the point at infinity.
+type Point<F> = Option<(F, F)>;
+
+/// Slope of the line through the points, or of the tangent if they are equal.
+fn slope<F: Field>((x1, y1): (F, F), (x2, y2): (F, F)) -> F {
+    if x1 == x2 {
+        let x1_squared = x1 * x1;
+        (x1_squared + x1_squared + x1_squared) * (y1 + y1).inv()
+    } else {
+        (y2 - y1) * (x2 - x1).inv()
+    }
+}
+
+/// Third point of the curve on the line through the points with the given slope, negated.
+fn chord<F: Field>((x1, y1): (F, F), (x2, _): (F, F), slope: F) -> (F, F) {
+    let x3 = slope * slope - x1 - x2;
+    (x3, slope * (x1 - x3) - y1)
+}
+
+fn add<F: Field>(p: Point<F>, q: Point<F>) -> Point<F> {
+    match (p, q) {
+        (None, q) => q,
+        (p, None) => p,
+        (Some(p), Some(q)) if p.0 == q.0 && (p.1 + q.1).is_zero() => None,
+        (Some(p), Some(q)) => Some(chord(p, q, slope(p, q))),
+    }
+}
+
+fn mul<F: Field>(p: Point<F>, scalar: &U256) -> Point<F> {
+    let mut result = None;
+    for i in (0..256).rev() {
+        result = add(result, result);
+        if scalar.bit(i) {
+            result = add(result, p);
+        }
+    }
+    result
+}
+
+fn is_on_curve<F: Field>(p: Point<F>, b: F) -> bool {
+    match p {
+        None => true,
+        Some((x, y)) => y * y == x * x * x + b,
+    }
+}
+
+/// Value of the Miller function of the ate pairing at `p` in G1 for `q` on the twist. The twist
+/// point `(x, y)` maps to `(x w^2, y w^3)` on the curve over the degree 12 extension, so the line
+/// through `T` with the slope `l w` evaluated at `p` is `y_p - l x_p w + (l x_T - y_T) w^3`. The
+/// vertical lines are omitted, as the final exponentiation maps them to one.
+fn miller_loop(p: (Fp, Fp), q: (Fp2, Fp2)) -> Fp12 {
+    let line = |t: (Fp2, Fp2), slope: Fp2| {
+        let mut c = [Fp2::zero(); 6];
+        c[0] = Fp2::from_fp(p.1);
+        c[1] = -(slope * Fp2::from_fp(p.0));
+        c[3] = slope * t.0 - t.1;
+        Fp12(c)
+    };
+    let mut t = q;
+    let mut f = Fp12::one();
+    let bits = 128 - ATE_LOOP.leading_zeros();
+    for i in (0..bits - 1).rev() {
+        let tangent = slope(t, t);
+        f = f * f * line(t, tangent);
+        t = chord(t, t, tangent);
+        if (ATE_LOOP >> i) & 1 == 1 {
+            let secant = slope(t, q);
+            f = f * line(t, secant);
+            t = chord(t, q, secant);
+        }
+    }
+    f
+}
+
+/// `(P^12 - 1) / R` as little-endian limbs, the exponent of the final exponentiation.
+fn final_exponent() -> Vec<u64> {
+    let mut power = vec![1u64];
+    for _ in 0..12 {
+        let mut product = vec![0u64; power.len() + 4];
+        for (i, a) in power.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, b) in P.0.iter().enumerate() {
+                let t = *a as u128 * *b as u128 + product[i + j] as u128 + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+        power = product;
+    }
+    // P^12 is odd, so subtracting one does not borrow.
+    power[0] -= 1;
+    let mut quotient = vec![0u64; power.len()];
+    let mut remainder = U256::ZERO;
+    for i in (0..64 * power.len()).rev() {
+        // R < 2^255, so doubling the remainder does not overflow.
+        remainder = remainder.overflowing_add(&remainder).0;
+        remainder.0[0] |= (power[i / 64] >> (i % 64)) & 1;
+        if remainder >= R {
+            remainder = remainder.overflowing_sub(&R).0;
+            quotient[i / 64] |= 1 << (i % 64);
+        }
+    }
+    debug_assert!(remainder.is_zero());
+    quotient
+}
+
+fn read_fp(bytes: &[u8]) -> Option<Fp> {
+    let mut value = [0u8; 32];
+    value.copy_from_slice(bytes);
+    Fp::new(&U256::from_le_bytes(&value))
+}
+
+/// Reads the G1 point from its 64 bytes `x || y`.
+fn read_g1(bytes: &[u8]) -> Option<Point<Fp>> {
+    let (x, y) = (read_fp(&bytes[..32])?, read_fp(&bytes[32..64])?);
+    let p = if x.is_zero() && y.is_zero() { None } else { Some((x, y)) };
+    if is_on_curve(p, Fp::from_u64(3)) {
+        Some(p)
+    } else {
+        None
+    }
+}
+
+/// Reads the G2 point from its 128 bytes `x.c0 || x.c1 || y.c0 || y.c1`, checking that it is on
+/// the twist `y^2 = x^3 + 3 / (9 + i)` and in the subgroup of order `R`.
+fn read_g2(bytes: &[u8]) -> Option<Point<Fp2>> {
+    let x = Fp2 { c0: read_fp(&bytes[..32])?, c1: read_fp(&bytes[32..64])? };
+    let y = Fp2 { c0: read_fp(&bytes[64..96])?, c1: read_fp(&bytes[96..128])? };
+    let p = if x.is_zero() && y.is_zero() { None } else { Some((x, y)) };
+    let b = Fp2::from_fp(Fp::from_u64(3)) * Fp2::xi().inv();
+    if is_on_curve(p, b) && mul(p, &R).is_none() {
+        Some(p)
+    } else {
+        None
+    }
+}
+
+fn write_g1(p: Point<Fp>) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = p {
+        bytes[..32].copy_from_slice(&x.to_u256().to_le_bytes());
+        bytes[32..].copy_from_slice(&y.to_u256().to_le_bytes());
+    }
+    bytes
+}
+
+/// Sum of the G1 points multiplied by the scalars, given as the items of `G1_MULTIEXP_ITEM_LEN`
+/// bytes. `None` if a point or a scalar is not valid.
+pub(crate) fn g1_multiexp(value: &[u8]) -> Option<[u8; 64]> {
+    let mut sum = None;
+    for item in value.chunks(G1_MULTIEXP_ITEM_LEN as usize) {
+        let p = read_g1(&item[..64])?;
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&item[64..]);
+        let scalar = U256::from_le_bytes(&scalar);
+        if scalar >= R {
+            return None;
+        }
+        sum = add(sum, mul(p, &scalar));
+    }
+    Some(write_g1(sum))
+}
+
+/// Sum of the G1 points, given as the items of `G1_SUM_ITEM_LEN` bytes, where the points with the
+/// sign byte 1 are negated. `None` if a point or a sign is not valid.
+pub(crate) fn g1_sum(value: &[u8]) -> Option<[u8; 64]> {
+    let mut sum = None;
+    for item in value.chunks(G1_SUM_ITEM_LEN as usize) {
+        let p = read_g1(&item[1..])?;
+        sum = match item[0] {
+            0 => add(sum, p),
+            1 => add(sum, p.map(|(x, y)| (x, -y))),
+            _ => return None,
+        };
+    }
+    Some(write_g1(sum))
+}
+
+/// Whether the product of the pairings of the G1 and G2 points, given as the items of
+/// `PAIRING_CHECK_ITEM_LEN` bytes, is one. `None` if a point is not valid.
+pub(crate) fn pairing_check(value: &[u8]) -> Option<bool> {
+    let mut f = Fp12::one();
+    for item in value.chunks(PAIRING_CHECK_ITEM_LEN as usize) {
+        let (p, q) = (read_g1(&item[..64])?, read_g2(&item[64..])?);
+        if let (Some(p), Some(q)) = (p, q) {
+            f = f * miller_loop(p, q);
+        }
+    }
+    Some(f.pow(&final_exponent()) == Fp12::one())
+}
+
+/// Generator of G2 multiplied by the scalar, as the 128 bytes that `pairing_check` reads.
+#[cfg(test)]
+pub(crate) fn g2_generator_mul(scalar: u64) -> [u8; 128] {
+    let fp = |limbs: [u64; 4]| Fp::new(&U256(limbs)).unwrap();
+    let generator = Some((
+        Fp2 {
+            c0: fp([
+                0x46de_bd5c_d992_f6ed,
+                0x6743_22d4_f75e_dadd,
+                0x426a_0066_5e5c_4479,
+                0x1800_deef_121f_1e76,
+            ]),
+            c1: fp([
+                0x97e4_85b7_aef3_12c2,
+                0xf1aa_4933_35a9_e712,
+                0x7260_bfb7_31fb_5d25,
+                0x198e_9393_920d_483a,
+            ]),
+        },
+        Fp2 {
+            c0: fp([
+                0x4ce6_cc01_66fa_7daa,
+                0xe3d1_e769_0c43_d37b,
+                0x4aab_7180_8dcb_408f,
+                0x12c8_5ea5_db8c_6deb,
+            ]),
+            c1: fp([
+                0x55ac_dadc_d122_975b,
+                0xbc4b_3133_70b3_8ef3,
+                0xec9e_99ad_690c_3395,
+                0x0906_89d0_585f_f075,
+            ]),
+        },
+    ));
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = mul(generator, &U256::from_u64(scalar)) {
+        for (i, c) in [x.c0, x.c1, y.c0, y.c1].iter().enumerate() {
+            bytes[32 * i..32 * i + 32].copy_from_slice(&c.to_u256().to_le_bytes());
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Generator of G1 multiplied by the scalar.
+    fn g1(scalar: u64) -> [u8; 64] {
+        write_g1(mul(Some((Fp::from_u64(1), Fp::from_u64(2))), &U256::from_u64(scalar)))
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(P_INV.wrapping_mul(P.0[0]), u64::MAX);
+        let r = super::super::uint::reduce_wide(&[0, 0, 0, 0, 1, 0, 0, 0], &P);
+        assert_eq!(super::super::uint::mul_mod(&r, &r, &P), R2);
+        let a = Fp::from_u64(12345);
+        assert_eq!(a.to_u256(), U256::from_u64(12345));
+        assert_eq!((a * a.inv()).to_u256(), U256::ONE);
+        let b = Fp2 { c0: a, c1: Fp::from_u64(7) };
+        assert_eq!(b * b.inv(), Fp2::from_fp(Fp::from_u64(1)));
+    }
+
+    #[test]
+    fn test_groups() {
+        // The generators are valid and have the order R.
+        assert!(read_g2(&g2_generator_mul(1)).unwrap().is_some());
+        assert_eq!(g2_generator_mul(0), [0u8; 128]);
+        let generator = [g1(1).to_vec(), [0u8; 32].to_vec()].concat();
+        let mut order = generator.clone();
+        order[64..].copy_from_slice(&R.to_le_bytes());
+        assert_eq!(g1_multiexp(&order), None);
+        let mut minus_one = generator;
+        minus_one[64..].copy_from_slice(&R.overflowing_sub(&U256::ONE).0.to_le_bytes());
+        assert_eq!(g1_sum(&[&[1u8][..], &g1(1)].concat()), g1_multiexp(&minus_one));
+
+        // 2 G + 3 (5 G) - 4 G = 13 G.
+        let mut items = Vec::new();
+        for (point, scalar) in [(g1(1), 2u64), (g1(5), 3)] {
+            items.extend_from_slice(&point);
+            items.extend_from_slice(&U256::from_u64(scalar).to_le_bytes());
+        }
+        let multiexp = g1_multiexp(&items).unwrap();
+        assert_eq!(multiexp, g1(17));
+        let sum = g1_sum(&[&[0u8][..], &multiexp, &[1], &g1(4)].concat());
+        assert_eq!(sum, Some(g1(13)));
+        assert_eq!(g1_sum(&[&[0u8][..], &g1(4), &[1], &g1(4)].concat()), Some([0; 64]));
+
+        // The points that are not on the curve and the invalid signs are rejected.
+        let mut invalid = g1(1);
+        invalid[32] ^= 1;
+        assert_eq!(g1_sum(&[&[0u8][..], &invalid].concat()), None);
+        assert_eq!(g1_sum(&[&[2u8][..], &g1(1)].concat()), None);
+        let mut invalid = g2_generator_mul(1);
+        invalid[64] ^= 1;
+        assert_eq!(read_g2(&invalid), None);
+    }
+
+    #[test]
+    fn test_pairing_check() {
+        // e(6 P, 5 Q) e(-10 P, 3 Q) = e(P, Q)^0.
+        let input = [
+            &g1(6)[..],
+            &g2_generator_mul(5),
+            &g1_sum(&[&[1u8][..], &g1(10)].concat()).unwrap(),
+            &g2_generator_mul(3),
+        ]
+        .concat();
+        assert_eq!(pairing_check(&input), Some(true));
+        // The pairing is not degenerate.
+        assert_eq!(pairing_check(&[&g1(1)[..], &g2_generator_mul(1)].concat()), Some(false));
+        // The pairs with the point at infinity are ignored.
+        assert_eq!(pairing_check(&[&[0u8; 64][..], &g2_generator_mul(1)].concat()), Some(true));
+        assert_eq!(pairing_check(&[]), Some(true));
+    }
+
+    /// Reads the 32-byte big-endian words of the Ethereum precompiles as the little-endian ones
+    /// of the host functions.
+    fn eth_words(hex: &str) -> Vec<[u8; 32]> {
+        let hex: alloc::string::String = hex.split_whitespace().collect();
+        (0..hex.len() / 64)
+            .map(|i| {
+                let mut word = [0u8; 32];
+                for (j, byte) in word.iter_mut().rev().enumerate() {
+                    let k = 64 * i + 2 * j;
+                    *byte = u8::from_str_radix(&hex[k..k + 2], 16).unwrap();
+                }
+                word
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_eip196_vectors() {
+        // The `chfast1` cases of `bn256Add` and `bn256ScalarMul` of go-ethereum.
+        let input = eth_words(
+            "18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9
+             063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266
+             07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed
+             06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+        );
+        let expected = eth_words(
+            "2243525c5efd4b9c3d3c45ac0ca3fe4dd85e830a4ce6b65fa1eeaee202839703
+             301d1d33be6da8e509df21cc35964723180eed7532537db9ae5e7d48f195c915",
+        );
+        let sum = [&[0u8][..], &input[0], &input[1], &[0], &input[2], &input[3]].concat();
+        assert_eq!(g1_sum(&sum).unwrap().to_vec(), expected.concat());
+
+        let input = eth_words(
+            "2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7
+             21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204
+             00000000000000000000000000000000000000000000000011138ce750fa15c2",
+        );
+        let expected = eth_words(
+            "070a8d6a982153cae4be29d434e8faef8a47b274a053f5a4ee2a6c9c13c31e5c
+             031b8ce914eba3a9ffb989f9cdd5b0f01943074bf4f0f315690ec3cec6981afc",
+        );
+        assert_eq!(g1_multiexp(&input.concat()).unwrap().to_vec(), expected.concat());
+
+        // 2 G, the point of the `bn256Add` case that doubles the generator.
+        let expected = eth_words(
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3
+             15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+        );
+        assert_eq!(g1(2).to_vec(), expected.concat());
+    }
+
+    #[test]
+    fn test_eip197_vectors() {
+        // The `jeff1` case of `bn256Pairing` of go-ethereum. The precompile takes the G2
+        // coordinates as `c1 || c0`, the host function as `c0 || c1`.
+        let words = eth_words(
+            "1c76476f4def4bb94541d57ebba1193381ffa7aa76ada664dd31c16024c43f59
+             3034dd2920f673e204fee2811c678745fc819b55d3e9d294e45c9b03a76aef41
+             209dd15ebff5d46c4bd888e51a93cf99a7329636c63514396b4a452003a35bf7
+             04bf11ca01483bfa8b34b43561848d28905960114c8ac04049af4b6315a41678
+             2bb8324af6cfc93537a2ad1a445cfd0ca2a71acd7ac41fadbf933c2a51be344d
+             120a2a4cf30c1bf9845f20c6fe39e07ea2cce61f0c9bb048165fe5e4de877550
+             111e129f1cf1097710d41c4ac70fcdfa5ba2023c6ff1cbeac322de49d1b6df7c
+             2032c61a830e3c17286de9462bf242fca2883585b93870a73853face6a6bf411
+             198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2
+             1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed
+             090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b
+             12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        );
+        let items: Vec<Vec<u8>> =
+            words.chunks(6).map(|w| [w[0], w[1], w[3], w[2], w[5], w[4]].concat()).collect();
+        // The last G2 point is the generator.
+        assert_eq!(items[1][64..].to_vec(), g2_generator_mul(1).to_vec());
+        assert_eq!(pairing_check(&items.concat()), Some(true));
+        assert_eq!(pairing_check(&items[0]), Some(false));
+        // The G2 point with its coordinates in the order of the precompile is not on the twist.
+        let swapped: Vec<u8> = words[..6].concat();
+        assert_eq!(pairing_check(&swapped), None);
+    }
+}
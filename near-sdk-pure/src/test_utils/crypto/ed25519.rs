@@ -0,0 +1,213 @@
+//! Verification of the ed25519 signatures.
+use sha2::{Digest, Sha512};
+
+use super::uint::{reduce_wide, Field, U256};
+
+/// `2^255 - 19`.
+const P: U256 = U256([
+    0xffff_ffff_ffff_ffed,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x7fff_ffff_ffff_ffff,
+]);
+
+/// Order of the base point, `2^252 + 27742317777372353535851937790883648493`.
+const L: U256 = U256([
+    0x5812_631a_5cf5_d3ed,
+    0x14de_f9de_a2f7_9cd6,
+    0x0000_0000_0000_0000,
+    0x1000_0000_0000_0000,
+]);
+
+/// Point of the twisted Edwards curve `-x^2 + y^2 = 1 + d x^2 y^2` in the extended coordinates
+/// `(X : Y : Z : T)`, where `x = X / Z`, `y = Y / Z` and `x y = T / Z`.
+#[derive(Clone, Copy)]
+struct Point {
+    x: U256,
+    y: U256,
+    z: U256,
+    t: U256,
+}
+
+struct Curve {
+    field: Field,
+    d: U256,
+    /// `2 d`.
+    d2: U256,
+    /// Square root of `-1`.
+    sqrt_m1: U256,
+    base: Point,
+}
+
+impl Curve {
+    fn new() -> Self {
+        let field = Field::new(P);
+        // d = -121665 / 121666.
+        let d = field.neg(&field.mul(&U256::from_u64(121665), &field.inv(&U256::from_u64(121666))));
+        let d2 = field.add(&d, &d);
+        // sqrt(-1) = 2^((p - 1) / 4).
+        let exponent = U256([
+            0xffff_ffff_ffff_fffb,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0x1fff_ffff_ffff_ffff,
+        ]);
+        let sqrt_m1 = field.pow(&U256::from_u64(2), &exponent);
+        let mut curve = Self { field, d, d2, sqrt_m1, base: Self::identity() };
+        // The base point has y = 4 / 5 and a positive x.
+        let y = curve.field.mul(&U256::from_u64(4), &curve.field.inv(&U256::from_u64(5)));
+        curve.base = curve.decompress(&y.to_le_bytes()).unwrap();
+        curve
+    }
+
+    fn identity() -> Point {
+        Point { x: U256::ZERO, y: U256::ONE, z: U256::ONE, t: U256::ZERO }
+    }
+
+    fn add(&self, p: &Point, q: &Point) -> Point {
+        let f = &self.field;
+        let a = f.mul(&f.sub(&p.y, &p.x), &f.sub(&q.y, &q.x));
+        let b = f.mul(&f.add(&p.y, &p.x), &f.add(&q.y, &q.x));
+        let c = f.mul(&f.mul(&p.t, &self.d2), &q.t);
+        let d = f.mul(&f.add(&p.z, &p.z), &q.z);
+        let e = f.sub(&b, &a);
+        let ff = f.sub(&d, &c);
+        let g = f.add(&d, &c);
+        let h = f.add(&b, &a);
+        Point { x: f.mul(&e, &ff), y: f.mul(&g, &h), z: f.mul(&ff, &g), t: f.mul(&e, &h) }
+    }
+
+    fn neg(&self, p: &Point) -> Point {
+        Point { x: self.field.neg(&p.x), y: p.y, z: p.z, t: self.field.neg(&p.t) }
+    }
+
+    fn mul(&self, p: &Point, scalar: &U256) -> Point {
+        let mut result = Self::identity();
+        for i in (0..256).rev() {
+            result = self.add(&result, &result);
+            if scalar.bit(i) {
+                result = self.add(&result, p);
+            }
+        }
+        result
+    }
+
+    fn compress(&self, p: &Point) -> [u8; 32] {
+        let f = &self.field;
+        let z_inv = f.inv(&p.z);
+        let x = f.mul(&p.x, &z_inv);
+        let y = f.mul(&p.y, &z_inv);
+        let mut bytes = y.to_le_bytes();
+        bytes[31] |= (x.0[0] as u8 & 1) << 7;
+        bytes
+    }
+
+    fn decompress(&self, bytes: &[u8; 32]) -> Option<Point> {
+        let f = &self.field;
+        let sign = bytes[31] >> 7 == 1;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        let y = f.reduce(&U256::from_le_bytes(&y_bytes));
+        // x^2 = u / v, where u = y^2 - 1 and v = d y^2 + 1.
+        let y2 = f.square(&y);
+        let u = f.sub(&y2, &U256::ONE);
+        let v = f.add(&f.mul(&self.d, &y2), &U256::ONE);
+        // x = u v^3 (u v^7)^((p - 5) / 8).
+        let v3 = f.mul(&f.square(&v), &v);
+        let v7 = f.mul(&f.square(&v3), &v);
+        let exponent = U256([
+            0xffff_ffff_ffff_fffd,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0x0fff_ffff_ffff_ffff,
+        ]);
+        let mut x = f.mul(&f.mul(&u, &v3), &f.pow(&f.mul(&u, &v7), &exponent));
+        let vx2 = f.mul(&v, &f.square(&x));
+        if vx2 == f.neg(&u) {
+            x = f.mul(&x, &self.sqrt_m1);
+        } else if vx2 != u {
+            return None;
+        }
+        if (x.0[0] & 1 == 1) != sign {
+            x = f.neg(&x);
+        }
+        Some(Point { x, y, z: U256::ONE, t: f.mul(&x, &y) })
+    }
+}
+
+/// Verifies the ed25519 signature `R || S` of the message, checking that `[S]B = R + [k]A`.
+pub(crate) fn ed25519_verify(signature: &[u8; 64], message: &[u8], public_key: &[u8; 32]) -> bool {
+    let curve = Curve::new();
+    let mut s_bytes = [0u8; 32];
+    s_bytes.copy_from_slice(&signature[32..]);
+    let s = U256::from_le_bytes(&s_bytes);
+    if s >= L {
+        return false;
+    }
+    let a = match curve.decompress(public_key) {
+        Some(a) => a,
+        None => return false,
+    };
+    let k = hash_to_scalar(&[&signature[..32], public_key, message]);
+    let r = curve.add(&curve.mul(&curve.base, &s), &curve.mul(&curve.neg(&a), &k));
+    curve.compress(&r)[..] == signature[..32]
+}
+
+/// SHA-512 of the concatenated parts as a scalar modulo `L`.
+fn hash_to_scalar(parts: &[&[u8]]) -> U256 {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    let hash = hasher.finalize();
+    let mut wide = [0u64; 8];
+    for (i, limb) in wide.iter_mut().enumerate() {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[8 * i..8 * i + 8]);
+        *limb = u64::from_le_bytes(bytes);
+    }
+    reduce_wide(&wide, &L)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex<const N: usize>(s: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        // Test 1 of RFC 8032: the public key is derived from the secret key.
+        let secret_key: [u8; 32] =
+            hex("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60");
+        let public_key: [u8; 32] =
+            hex("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a");
+        let curve = Curve::new();
+        let mut scalar = [0u8; 32];
+        scalar.copy_from_slice(&Sha512::digest(&secret_key)[..32]);
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+        let derived = curve.mul(&curve.base, &U256::from_le_bytes(&scalar));
+        assert_eq!(curve.compress(&derived), public_key);
+
+        let signature: [u8; 64] = hex("e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b");
+        assert!(ed25519_verify(&signature, b"", &public_key));
+        assert!(!ed25519_verify(&signature, b"x", &public_key));
+
+        // Test 2 of RFC 8032.
+        let public_key: [u8; 32] =
+            hex("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c");
+        let signature: [u8; 64] = hex("92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00");
+        assert!(ed25519_verify(&signature, &[0x72], &public_key));
+        let mut tampered = signature;
+        tampered[40] ^= 1;
+        assert!(!ed25519_verify(&tampered, &[0x72], &public_key));
+    }
+}
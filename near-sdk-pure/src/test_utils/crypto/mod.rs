@@ -0,0 +1,146 @@
+//! Pure-Rust implementations of the cryptographic host functions that the `MockedBlockchain`
+//! provides behind the cargo features of the same names.
+
+#[cfg(feature = "ripemd160")]
+mod ripemd160;
+#[cfg(feature = "ripemd160")]
+pub(crate) use self::ripemd160::ripemd160;
+
+#[cfg(any(feature = "ecrecover", feature = "ed25519-verify", feature = "alt-bn128"))]
+mod uint;
+
+#[cfg(feature = "ecrecover")]
+mod secp256k1;
+#[cfg(feature = "ecrecover")]
+pub(crate) use self::secp256k1::ecrecover;
+
+#[cfg(feature = "ed25519-verify")]
+mod ed25519;
+#[cfg(feature = "ed25519-verify")]
+pub(crate) use self::ed25519::ed25519_verify;
+
+#[cfg(feature = "alt-bn128")]
+pub(crate) mod alt_bn128;
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "ripemd160")]
+    #[test]
+    fn test_ripemd160() {
+        use super::ripemd160;
+        use alloc::vec;
+
+        let hex = |hash: [u8; 20]| {
+            hash.iter().map(|b| std::format!("{:02x}", b)).collect::<std::string::String>()
+        };
+        assert_eq!(hex(ripemd160(b"")), "9c1185a5c5e9fc54612808977ee8f548b2258d31");
+        assert_eq!(hex(ripemd160(b"abc")), "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc");
+        assert_eq!(
+            hex(ripemd160(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "12a053384a9c0c88e405a06c27dcf49ada62eb2b"
+        );
+        assert_eq!(
+            hex(ripemd160(&vec![b'a'; 1_000_000])),
+            "52783243c1697bdbe16d37f97f68f08325dc1528"
+        );
+    }
+
+    #[cfg(feature = "ecrecover")]
+    #[test]
+    fn test_ecrecover() {
+        use super::secp256k1::{sign, Curve, N};
+        use super::uint::U256;
+
+        // 2G, the public key of the secret key 2.
+        let mut expected = [0u8; 64];
+        expected[..32].copy_from_slice(
+            &U256([0xabac09b95c709ee5, 0x5c778e4b8cef3ca7, 0x3045406e95c07cd8, 0xc6047f9441ed7d6d])
+                .to_be_bytes(),
+        );
+        expected[32..].copy_from_slice(
+            &U256([0x236431a950cfe52a, 0xf7f632653266d0e1, 0xa3c58419466ceaee, 0x1ae168fea63dc339])
+                .to_be_bytes(),
+        );
+        let curve = Curve::new();
+        let (x, y) = curve.to_affine(&curve.mul(&Curve::generator(), &U256::from_u64(2))).unwrap();
+        assert_eq!([x.to_be_bytes(), y.to_be_bytes()].concat(), expected.to_vec());
+
+        let hash = [7u8; 32];
+        for nonce in 1..8 {
+            let (signature, v) =
+                sign(&hash, &U256::from_u64(2), &U256::from_u64(nonce * 1_000_003));
+            assert_eq!(super::ecrecover(&hash, &signature, v, false), Some(expected));
+            // The other parity recovers a different key.
+            assert_ne!(super::ecrecover(&hash, &signature, v ^ 1, false), Some(expected));
+
+            // The same signature with s replaced by N - s is valid for the other parity, unless
+            // the malleable signatures are rejected.
+            let mut s_bytes = [0u8; 32];
+            s_bytes.copy_from_slice(&signature[32..]);
+            let s = U256::from_be_bytes(&s_bytes);
+            let mut flipped = signature;
+            flipped[32..].copy_from_slice(&N.overflowing_sub(&s).0.to_be_bytes());
+            assert_eq!(super::ecrecover(&hash, &flipped, v ^ 1, false), Some(expected));
+            let (low, high) = if s < N.overflowing_sub(&s).0 {
+                ((signature, v), (flipped, v ^ 1))
+            } else {
+                ((flipped, v ^ 1), (signature, v))
+            };
+            assert_eq!(super::ecrecover(&hash, &low.0, low.1, true), Some(expected));
+            assert_eq!(super::ecrecover(&hash, &high.0, high.1, true), None);
+        }
+        assert_eq!(super::ecrecover(&hash, &[0u8; 64], 0, false), None);
+    }
+
+    #[cfg(feature = "ecrecover")]
+    #[test]
+    fn test_ecrecover_ethereum_vectors() {
+        use super::secp256k1::N;
+        use super::uint::U256;
+        use sha3::{Digest, Keccak256};
+
+        fn hex<const L: usize>(s: &str) -> [u8; L] {
+            let mut bytes = [0u8; L];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+            }
+            bytes
+        }
+        // The Ethereum address is the last 20 bytes of the keccak256 of the public key.
+        let address = |public_key: [u8; 64]| {
+            let mut address = [0u8; 20];
+            address.copy_from_slice(&Keccak256::digest(&public_key)[12..]);
+            address
+        };
+
+        // The transaction of the EIP-155 example, signed with the secret key 0x4646...46. The
+        // signing data is the RLP of the transaction with the chain id 1, and `v = 37` is the
+        // recovery id 0.
+        let signing_data: [u8; 45] = hex("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080");
+        let hash: [u8; 32] =
+            hex("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53");
+        assert_eq!(Keccak256::digest(&signing_data)[..], hash[..]);
+        let signature: [u8; 64] = hex("28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa63627667cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+        let expected: [u8; 20] = hex("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f");
+        let public_key = super::ecrecover(&hash, &signature, 0, true).unwrap();
+        assert_eq!(address(public_key), expected);
+        assert_ne!(super::ecrecover(&hash, &signature, 1, false).map(address), Some(expected));
+
+        // The same signature with the upper `s = N - s` and the other recovery id is valid, unless
+        // the malleable signatures are rejected, as Ethereum does since Homestead.
+        let mut s_bytes = [0u8; 32];
+        s_bytes.copy_from_slice(&signature[32..]);
+        let s = U256::from_be_bytes(&s_bytes);
+        let mut high_s = signature;
+        high_s[32..].copy_from_slice(&N.overflowing_sub(&s).0.to_be_bytes());
+        assert_eq!(super::ecrecover(&hash, &high_s, 1, false), Some(public_key));
+        assert_eq!(super::ecrecover(&hash, &high_s, 1, true), None);
+
+        // The test of the ecrecover precompile, with `v = 28`, i.e. the recovery id 1.
+        let hash: [u8; 32] =
+            hex("456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3");
+        let signature: [u8; 64] = hex("9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac80388256084f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada");
+        let expected: [u8; 20] = hex("7156526fbd7a3c72969b54f64e42c10fbb768c8a");
+        assert_eq!(super::ecrecover(&hash, &signature, 1, true).map(address), Some(expected));
+    }
+}
@@ -0,0 +1,226 @@
+//! Recovery of the secp256k1 public keys from the ECDSA signatures.
+use super::uint::{inv_mod, mul_mod, sub_mod, Field, U256};
+
+/// `2^256 - 2^32 - 977`.
+const P: U256 = U256([
+    0xffff_fffe_ffff_fc2f,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+]);
+
+/// Order of the generator.
+pub(crate) const N: U256 = U256([
+    0xbfd2_5e8c_d036_4141,
+    0xbaae_dce6_af48_a03b,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+]);
+
+/// `N / 2`, the largest `s` allowed when the malleable signatures are rejected.
+const HALF_N: U256 = U256([
+    0xdfe9_2f46_681b_20a0,
+    0x5d57_6e73_57a4_501d,
+    0xffff_ffff_ffff_ffff,
+    0x7fff_ffff_ffff_ffff,
+]);
+
+const GX: U256 = U256([
+    0x59f2_815b_16f8_1798,
+    0x029b_fcdb_2dce_28d9,
+    0x55a0_6295_ce87_0b07,
+    0x79be_667e_f9dc_bbac,
+]);
+
+const GY: U256 = U256([
+    0x9c47_d08f_fb10_d4b8,
+    0xfd17_b448_a685_5419,
+    0x5da4_fbfc_0e11_08a8,
+    0x483a_da77_26a3_c465,
+]);
+
+/// Point of the curve `y^2 = x^3 + 7` in the Jacobian coordinates `(X : Y : Z)`, where
+/// `x = X / Z^2` and `y = Y / Z^3`. The point at infinity has `Z = 0`.
+#[derive(Clone, Copy)]
+pub(crate) struct Point {
+    x: U256,
+    y: U256,
+    z: U256,
+}
+
+pub(crate) struct Curve {
+    field: Field,
+}
+
+impl Curve {
+    pub fn new() -> Self {
+        Self { field: Field::new(P) }
+    }
+
+    pub fn generator() -> Point {
+        Point { x: GX, y: GY, z: U256::ONE }
+    }
+
+    fn infinity() -> Point {
+        Point { x: U256::ZERO, y: U256::ONE, z: U256::ZERO }
+    }
+
+    fn double(&self, p: &Point) -> Point {
+        let f = &self.field;
+        if p.z.is_zero() || p.y.is_zero() {
+            return Self::infinity();
+        }
+        let a = f.square(&p.x);
+        let b = f.square(&p.y);
+        let c = f.square(&b);
+        let xb = f.square(&f.add(&p.x, &b));
+        let d = f.sub(&f.sub(&xb, &a), &c);
+        let d = f.add(&d, &d);
+        let e = f.add(&f.add(&a, &a), &a);
+        let x = f.sub(&f.square(&e), &f.add(&d, &d));
+        let c8 = f.mul(&c, &U256::from_u64(8));
+        let y = f.sub(&f.mul(&e, &f.sub(&d, &x)), &c8);
+        let yz = f.mul(&p.y, &p.z);
+        Point { x, y, z: f.add(&yz, &yz) }
+    }
+
+    fn add(&self, p: &Point, q: &Point) -> Point {
+        let f = &self.field;
+        if p.z.is_zero() {
+            return *q;
+        }
+        if q.z.is_zero() {
+            return *p;
+        }
+        let z1z1 = f.square(&p.z);
+        let z2z2 = f.square(&q.z);
+        let u1 = f.mul(&p.x, &z2z2);
+        let u2 = f.mul(&q.x, &z1z1);
+        let s1 = f.mul(&f.mul(&p.y, &q.z), &z2z2);
+        let s2 = f.mul(&f.mul(&q.y, &p.z), &z1z1);
+        if u1 == u2 {
+            return if s1 == s2 { self.double(p) } else { Self::infinity() };
+        }
+        let h = f.sub(&u2, &u1);
+        let i = f.square(&f.add(&h, &h));
+        let j = f.mul(&h, &i);
+        let r = f.sub(&s2, &s1);
+        let r = f.add(&r, &r);
+        let v = f.mul(&u1, &i);
+        let x = f.sub(&f.sub(&f.square(&r), &j), &f.add(&v, &v));
+        let s1j = f.mul(&s1, &j);
+        let y = f.sub(&f.mul(&r, &f.sub(&v, &x)), &f.add(&s1j, &s1j));
+        let zz = f.sub(&f.sub(&f.square(&f.add(&p.z, &q.z)), &z1z1), &z2z2);
+        Point { x, y, z: f.mul(&zz, &h) }
+    }
+
+    pub fn mul(&self, p: &Point, scalar: &U256) -> Point {
+        let mut result = Self::infinity();
+        for i in (0..256).rev() {
+            result = self.double(&result);
+            if scalar.bit(i) {
+                result = self.add(&result, p);
+            }
+        }
+        result
+    }
+
+    /// Affine coordinates of the point, `None` for the point at infinity.
+    pub fn to_affine(&self, p: &Point) -> Option<(U256, U256)> {
+        let f = &self.field;
+        if p.z.is_zero() {
+            return None;
+        }
+        let z_inv = f.inv(&p.z);
+        let z_inv2 = f.square(&z_inv);
+        Some((f.mul(&p.x, &z_inv2), f.mul(&f.mul(&p.y, &z_inv2), &z_inv)))
+    }
+
+    /// The point with the given `x` and the parity of `y`.
+    fn lift_x(&self, x: &U256, odd: bool) -> Option<Point> {
+        let f = &self.field;
+        let y2 = f.add(&f.mul(&f.square(x), x), &U256::from_u64(7));
+        // p = 3 (mod 4), so the square root is y2^((p + 1) / 4).
+        let exponent = U256([
+            0xffff_ffff_bfff_ff0c,
+            0xffff_ffff_ffff_ffff,
+            0xffff_ffff_ffff_ffff,
+            0x3fff_ffff_ffff_ffff,
+        ]);
+        let mut y = f.pow(&y2, &exponent);
+        if f.square(&y) != y2 {
+            return None;
+        }
+        if (y.0[0] & 1 == 1) != odd {
+            y = f.neg(&y);
+        }
+        Some(Point { x: *x, y, z: U256::ONE })
+    }
+}
+
+/// Recovers the public key that signed the 32-byte hash with the signature `r || s` and the
+/// recovery id `v`, as the 64-byte concatenation of its coordinates. With `reject_malleable`, the
+/// signatures with `s > N / 2` are not valid.
+pub(crate) fn ecrecover(
+    hash: &[u8; 32],
+    signature: &[u8; 64],
+    v: u8,
+    reject_malleable: bool,
+) -> Option<[u8; 64]> {
+    let mut r_bytes = [0u8; 32];
+    r_bytes.copy_from_slice(&signature[..32]);
+    let mut s_bytes = [0u8; 32];
+    s_bytes.copy_from_slice(&signature[32..]);
+    let r = U256::from_be_bytes(&r_bytes);
+    let s = U256::from_be_bytes(&s_bytes);
+    if v > 3 || r.is_zero() || s.is_zero() || r >= N || s >= N {
+        return None;
+    }
+    if reject_malleable && s > HALF_N {
+        return None;
+    }
+    let curve = Curve::new();
+    // The x coordinate of the nonce point is r or r + N.
+    let x = if v & 2 == 2 {
+        let (x, overflow) = r.overflowing_add(&N);
+        if overflow || x >= P {
+            return None;
+        }
+        x
+    } else {
+        r
+    };
+    let nonce_point = curve.lift_x(&x, v & 1 == 1)?;
+    // Q = r^-1 (s R - e G).
+    let e = U256::from_be_bytes(hash);
+    let e = if e >= N { e.overflowing_sub(&N).0 } else { e };
+    let r_inv = inv_mod(&r, &N);
+    let u1 = mul_mod(&sub_mod(&U256::ZERO, &e, &N), &r_inv, &N);
+    let u2 = mul_mod(&s, &r_inv, &N);
+    let q = curve.add(&curve.mul(&Curve::generator(), &u1), &curve.mul(&nonce_point, &u2));
+    let (qx, qy) = curve.to_affine(&q)?;
+    let mut public_key = [0u8; 64];
+    public_key[..32].copy_from_slice(&qx.to_be_bytes());
+    public_key[32..].copy_from_slice(&qy.to_be_bytes());
+    Some(public_key)
+}
+
+/// Signs the hash with the secret key and the nonce, returning the signature and the recovery id.
+#[cfg(test)]
+pub(crate) fn sign(hash: &[u8; 32], secret_key: &U256, nonce: &U256) -> ([u8; 64], u8) {
+    let curve = Curve::new();
+    let (x, y) = curve.to_affine(&curve.mul(&Curve::generator(), nonce)).unwrap();
+    let r = if x >= N { x.overflowing_sub(&N).0 } else { x };
+    let e = U256::from_be_bytes(hash);
+    let e = if e >= N { e.overflowing_sub(&N).0 } else { e };
+    let s = mul_mod(
+        &inv_mod(nonce, &N),
+        &super::uint::add_mod(&e, &mul_mod(&r, secret_key, &N), &N),
+        &N,
+    );
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&r.to_be_bytes());
+    signature[32..].copy_from_slice(&s.to_be_bytes());
+    let v = (y.0[0] & 1) as u8 | if x >= N { 2 } else { 0 };
+    (signature, v)
+}
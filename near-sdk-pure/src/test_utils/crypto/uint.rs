@@ -0,0 +1,248 @@
+//! 256-bit unsigned integers and the modular arithmetic of the elliptic curves. Nothing here is
+//! constant time, it is only meant for checking signatures in tests.
+// Each curve is behind its own feature and uses only a part of the arithmetic.
+#![allow(dead_code)]
+use core::cmp::Ordering;
+
+/// 256-bit unsigned integer as little-endian 64-bit limbs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct U256(pub [u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+
+    pub fn from_u64(value: u64) -> Self {
+        U256([value, 0, 0, 0])
+    }
+
+    pub fn from_be_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let mut chunk = [0u8; 8];
+            chunk.copy_from_slice(&bytes[24 - 8 * i..32 - 8 * i]);
+            *limb = u64::from_be_bytes(chunk);
+        }
+        U256(limbs)
+    }
+
+    pub fn to_be_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        for (i, limb) in self.0.iter().enumerate() {
+            bytes[24 - 8 * i..32 - 8 * i].copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_le_bytes(bytes: &[u8; 32]) -> Self {
+        let mut reversed = *bytes;
+        reversed.reverse();
+        Self::from_be_bytes(&reversed)
+    }
+
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = self.to_be_bytes();
+        bytes.reverse();
+        bytes
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+
+    pub fn bit(&self, i: usize) -> bool {
+        (self.0[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    pub fn overflowing_add(&self, other: &U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut carry = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (sum, c1) = self.0[i].overflowing_add(other.0[i]);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+        }
+        (U256(result), carry)
+    }
+
+    pub fn overflowing_sub(&self, other: &U256) -> (U256, bool) {
+        let mut result = [0u64; 4];
+        let mut borrow = false;
+        for (i, limb) in result.iter_mut().enumerate() {
+            let (diff, b1) = self.0[i].overflowing_sub(other.0[i]);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = b1 || b2;
+        }
+        (U256(result), borrow)
+    }
+
+    /// Full 512-bit product as little-endian limbs.
+    pub fn widening_mul(&self, other: &U256) -> [u64; 8] {
+        let mut result = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let t = self.0[i] as u128 * other.0[j] as u128 + result[i + j] as u128 + carry;
+                result[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            result[i + 4] = carry as u64;
+        }
+        result
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Adds two residues modulo `m`.
+pub(crate) fn add_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    let (sum, carry) = a.overflowing_add(b);
+    if carry || sum >= *m {
+        sum.overflowing_sub(m).0
+    } else {
+        sum
+    }
+}
+
+/// Subtracts two residues modulo `m`.
+pub(crate) fn sub_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    let (diff, borrow) = a.overflowing_sub(b);
+    if borrow {
+        diff.overflowing_add(m).0
+    } else {
+        diff
+    }
+}
+
+/// Reduces the 512-bit little-endian number modulo `m` bit by bit.
+pub(crate) fn reduce_wide(value: &[u64; 8], m: &U256) -> U256 {
+    let mut result = U256::ZERO;
+    for i in (0..512).rev() {
+        // result = 2 * result + bit, where 2 * result may overflow 256 bits.
+        let (doubled, carry) = result.overflowing_add(&result);
+        result = if carry || doubled >= *m { doubled.overflowing_sub(m).0 } else { doubled };
+        if (value[i / 64] >> (i % 64)) & 1 == 1 {
+            result = add_mod(&result, &U256::ONE, m);
+        }
+    }
+    result
+}
+
+/// Multiplies two residues modulo an arbitrary `m`.
+pub(crate) fn mul_mod(a: &U256, b: &U256, m: &U256) -> U256 {
+    reduce_wide(&a.widening_mul(b), m)
+}
+
+/// Inverts the residue modulo the prime `m`.
+pub(crate) fn inv_mod(a: &U256, m: &U256) -> U256 {
+    let exponent = m.overflowing_sub(&U256::from_u64(2)).0;
+    let mut result = U256::ONE;
+    for i in (0..256).rev() {
+        result = mul_mod(&result, &result, m);
+        if exponent.bit(i) {
+            result = mul_mod(&result, a, m);
+        }
+    }
+    result
+}
+
+/// Prime field whose modulus `p` is close to `2^256`, so that `2^256 mod p` fits into 64 bits and
+/// the products are reduced by folding their upper half.
+pub(crate) struct Field {
+    pub p: U256,
+    /// `2^256 mod p`.
+    c: u64,
+}
+
+impl Field {
+    pub fn new(p: U256) -> Self {
+        let mut c = U256::ZERO.overflowing_sub(&p).0;
+        while c >= p {
+            c = c.overflowing_sub(&p).0;
+        }
+        assert!(c.0[1..].iter().all(|limb| *limb == 0), "The modulus is too small.");
+        Self { p, c: c.0[0] }
+    }
+
+    pub fn add(&self, a: &U256, b: &U256) -> U256 {
+        add_mod(a, b, &self.p)
+    }
+
+    pub fn sub(&self, a: &U256, b: &U256) -> U256 {
+        sub_mod(a, b, &self.p)
+    }
+
+    pub fn neg(&self, a: &U256) -> U256 {
+        sub_mod(&U256::ZERO, a, &self.p)
+    }
+
+    pub fn mul(&self, a: &U256, b: &U256) -> U256 {
+        let wide = a.widening_mul(b);
+        // lo + hi * 2^256 = lo + hi * c (mod p), which fits into 5 limbs.
+        let mut folded = [0u64; 5];
+        let mut carry = 0u128;
+        for i in 0..4 {
+            let t = wide[i] as u128 + wide[i + 4] as u128 * self.c as u128 + carry;
+            folded[i] = t as u64;
+            carry = t >> 64;
+        }
+        folded[4] = carry as u64;
+        // Fold the fifth limb the same way.
+        let (mut result, mut overflow) = U256([folded[0], folded[1], folded[2], folded[3]])
+            .overflowing_add(&U256(mul_u64(folded[4], self.c)));
+        while overflow {
+            let (sum, carry) = result.overflowing_add(&U256::from_u64(self.c));
+            result = sum;
+            overflow = carry;
+        }
+        while result >= self.p {
+            result = result.overflowing_sub(&self.p).0;
+        }
+        result
+    }
+
+    pub fn square(&self, a: &U256) -> U256 {
+        self.mul(a, a)
+    }
+
+    pub fn pow(&self, a: &U256, exponent: &U256) -> U256 {
+        let mut result = U256::ONE;
+        for i in (0..256).rev() {
+            result = self.square(&result);
+            if exponent.bit(i) {
+                result = self.mul(&result, a);
+            }
+        }
+        result
+    }
+
+    pub fn inv(&self, a: &U256) -> U256 {
+        self.pow(a, &self.p.overflowing_sub(&U256::from_u64(2)).0)
+    }
+
+    /// Reduces the number that may be not less than `p`.
+    pub fn reduce(&self, a: &U256) -> U256 {
+        let mut result = *a;
+        while result >= self.p {
+            result = result.overflowing_sub(&self.p).0;
+        }
+        result
+    }
+}
+
+fn mul_u64(a: u64, b: u64) -> [u64; 4] {
+    let t = a as u128 * b as u128;
+    [t as u64, (t >> 64) as u64, 0, 0]
+}
@@ -0,0 +1,190 @@
+//! A blockchain interface that aborts the contract in the middle of a call, for testing that the
+//! contract state stays consistent when an execution terminates unexpectedly.
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use crate::env;
+use crate::environment::blockchain_interface::BlockchainInterface;
+use crate::test_utils::MockedBlockchain;
+use crate::types::StorageUsage;
+
+/// Prefix of the panic message of an injected fault.
+pub const INJECTED_FAULT: &str = "Injected fault";
+
+/// When the injected fault happens relative to the host call it is injected at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The host call panics without being executed.
+    Before,
+    /// The host call is executed and the contract is aborted right after it returns.
+    After,
+}
+
+/// Storage of the `MockedBlockchain` and its storage usage.
+type StorageSnapshot = (BTreeMap<Vec<u8>, Vec<u8>>, StorageUsage);
+
+/// Wrapper of another blockchain interface that panics on the Nth host call.
+///
+/// The storage of the wrapped `MockedBlockchain` is snapshotted when the wrapper is created. When
+/// the call is aborted, either by the injected fault or by the contract panicking through the
+/// host, the storage is rolled back to the snapshot, as the runtime discards the state changes of
+/// the failed execution. The rollback can be disabled to observe the partial writes instead.
+pub struct FaultInjector {
+    inner: Box<dyn BlockchainInterface>,
+    calls: Cell<u64>,
+    fault: Option<(u64, Fault)>,
+    rollback_on_abort: bool,
+    snapshot: Option<StorageSnapshot>,
+}
+
+impl FaultInjector {
+    /// Wraps the interface without injecting any fault.
+    pub fn new(inner: Box<dyn BlockchainInterface>) -> Self {
+        let snapshot = inner
+            .as_mocked_blockchain()
+            .map(|blockchain| (blockchain.storage(), blockchain.context().storage_usage));
+        Self { inner, calls: Cell::new(0), fault: None, rollback_on_abort: true, snapshot }
+    }
+
+    /// Injects the fault at the host call with the given number, starting from 1.
+    pub fn fail_at(mut self, call: u64, fault: Fault) -> Self {
+        self.fault = Some((call, fault));
+        self
+    }
+
+    /// Whether the storage is rolled back when the call is aborted. Enabled by default.
+    pub fn rollback_on_abort(mut self, rollback: bool) -> Self {
+        self.rollback_on_abort = rollback;
+        self
+    }
+
+    /// The number of host calls made so far.
+    pub fn calls(&self) -> u64 {
+        self.calls.get()
+    }
+
+    /// Restores the storage of the wrapped `MockedBlockchain` to the snapshot.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the wrapped interface is not a `MockedBlockchain`.
+    pub fn rollback(&self) {
+        let (storage, storage_usage) =
+            self.snapshot.clone().expect("Rollback requires a MockedBlockchain.");
+        self.inner.as_mocked_blockchain().unwrap().restore_storage(storage, storage_usage);
+    }
+
+    fn abort(&self) -> ! {
+        if self.rollback_on_abort {
+            self.rollback();
+        }
+        panic!("{} at host call {}", INJECTED_FAULT, self.calls.get())
+    }
+
+    fn before_call(&self, name: &str) {
+        self.calls.set(self.calls.get() + 1);
+        if self.fault == Some((self.calls.get(), Fault::Before)) {
+            self.abort();
+        }
+        // The contract aborts itself.
+        if (name == "panic" || name == "panic_utf8") && self.rollback_on_abort {
+            self.rollback();
+        }
+    }
+
+    fn after_call(&self) {
+        if self.fault == Some((self.calls.get(), Fault::After)) {
+            self.abort();
+        }
+    }
+}
+
+macro_rules! impl_fault_injector {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        impl BlockchainInterface for FaultInjector {
+            $(
+                $(#[$meta])*
+                unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    self.before_call(stringify!($name));
+                    let result = self.inner.$name($($arg),*);
+                    self.after_call();
+                    result
+                }
+            )*
+
+            fn as_mocked_blockchain(&self) -> Option<&MockedBlockchain> {
+                self.inner.as_mocked_blockchain()
+            }
+        }
+    };
+}
+
+for_each_host_function!(impl_fault_injector);
+
+/// Wraps the current blockchain interface into a `FaultInjector` that injects the fault at the
+/// host call with the given number.
+///
+/// # Panics
+///
+/// Panics if the blockchain interface is not set.
+pub fn inject_fault(call: u64, fault: Fault) {
+    let inner = env::take_blockchain_interface().expect("Blockchain interface is not set.");
+    env::set_blockchain_interface(Box::new(FaultInjector::new(inner).fail_at(call, fault)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::Vector;
+    use crate::test_utils::{with_mocked_blockchain, VMContextBuilder};
+    use crate::testing_env;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::string::String;
+
+    fn aborted<F: FnOnce()>(f: F) -> String {
+        let payload = catch_unwind(AssertUnwindSafe(f)).expect_err("The call was not aborted");
+        payload.downcast_ref::<String>().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_fault_before_and_after() {
+        testing_env!(VMContextBuilder::new().build());
+        env::storage_write(b"fault_a", b"1");
+
+        inject_fault(2, Fault::Before);
+        let message = aborted(|| {
+            env::storage_write(b"fault_b", b"1");
+            env::storage_write(b"fault_c", b"1");
+        });
+        assert_eq!(message, "Injected fault at host call 2");
+        assert!(env::storage_has_key(b"fault_a"));
+        assert!(!env::storage_has_key(b"fault_b"));
+
+        testing_env!(VMContextBuilder::new().build());
+        let inner = env::take_blockchain_interface().unwrap();
+        env::set_blockchain_interface(Box::new(
+            FaultInjector::new(inner).fail_at(1, Fault::After).rollback_on_abort(false),
+        ));
+        aborted(|| {
+            env::storage_write(b"fault_b", b"1");
+        });
+        assert!(env::storage_has_key(b"fault_b"));
+    }
+
+    #[test]
+    fn test_contract_panic_rolls_back() {
+        testing_env!(VMContextBuilder::new().build());
+        let before = with_mocked_blockchain(|blockchain| blockchain.storage());
+        inject_fault(u64::MAX, Fault::Before);
+        let message = aborted(|| {
+            let mut vector = Vector::new(b"v".to_vec());
+            vector.push(&1u8);
+            env::panic(b"abort");
+        });
+        assert_eq!(message, "Smart contract panicked: abort");
+        assert_eq!(with_mocked_blockchain(|blockchain| blockchain.storage()), before);
+        assert_eq!(env::storage_usage(), 0);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::types::{Balance, Gas, StorageUsage};
+
+/// Fees charged by the `MockedBlockchain` for the host functions and the actions of the created
+/// promises, and the parameters of the storage usage accounting.
+///
+/// The default values follow the fees of the runtime, so `env::used_gas()` gives an estimate of
+/// the gas that the method would burn on chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuntimeFeesConfig {
+    /// Base cost of calling any host function.
+    pub base: Gas,
+
+    /// Costs of reading the guest memory.
+    pub read_memory_base: Gas,
+    pub read_memory_byte: Gas,
+    /// Costs of writing to the guest memory.
+    pub write_memory_base: Gas,
+    pub write_memory_byte: Gas,
+    /// Costs of reading a register into the guest memory.
+    pub read_register_base: Gas,
+    pub read_register_byte: Gas,
+    /// Costs of writing data into a register.
+    pub write_register_base: Gas,
+    pub write_register_byte: Gas,
+
+    /// Costs of decoding the strings passed to the log and panic functions.
+    pub utf8_decoding_base: Gas,
+    pub utf8_decoding_byte: Gas,
+    pub utf16_decoding_base: Gas,
+    pub utf16_decoding_byte: Gas,
+
+    /// Costs of hashing.
+    pub sha256_base: Gas,
+    pub sha256_byte: Gas,
+    pub keccak256_base: Gas,
+    pub keccak256_byte: Gas,
+    pub keccak512_base: Gas,
+    pub keccak512_byte: Gas,
+    /// Cost of RIPEMD-160 per 64-byte block of the padded message.
+    pub ripemd160_base: Gas,
+    pub ripemd160_block: Gas,
+
+    /// Costs of the signature checks.
+    pub ecrecover_base: Gas,
+    pub ed25519_verify_base: Gas,
+    pub ed25519_verify_byte: Gas,
+
+    /// Costs of the alt_bn128 operations per item of the input.
+    pub alt_bn128_g1_multiexp_base: Gas,
+    pub alt_bn128_g1_multiexp_element: Gas,
+    pub alt_bn128_g1_sum_base: Gas,
+    pub alt_bn128_g1_sum_element: Gas,
+    pub alt_bn128_pairing_check_base: Gas,
+    pub alt_bn128_pairing_check_element: Gas,
+
+    /// Costs of logging.
+    pub log_base: Gas,
+    pub log_byte: Gas,
+
+    /// Costs of the storage functions.
+    pub storage_write_base: Gas,
+    pub storage_write_key_byte: Gas,
+    pub storage_write_value_byte: Gas,
+    pub storage_write_evicted_byte: Gas,
+    pub storage_read_base: Gas,
+    pub storage_read_key_byte: Gas,
+    pub storage_read_value_byte: Gas,
+    pub storage_remove_base: Gas,
+    pub storage_remove_key_byte: Gas,
+    pub storage_remove_ret_value_byte: Gas,
+    pub storage_has_key_base: Gas,
+    pub storage_has_key_byte: Gas,
+
+    /// Costs of the promise functions.
+    pub promise_and_base: Gas,
+    pub promise_and_per_promise: Gas,
+    pub promise_return: Gas,
+
+    /// Costs of the validator functions.
+    pub validator_stake_base: Gas,
+    pub validator_total_stake_base: Gas,
+
+    /// Cost of creating a receipt for a promise.
+    pub action_receipt_creation: Gas,
+    /// Cost of any action other than a function call and a contract deployment.
+    pub action_base: Gas,
+    /// Costs of a function call action, per byte of the method name and the arguments.
+    pub action_function_call_base: Gas,
+    pub action_function_call_byte: Gas,
+    /// Costs of a contract deployment action, per byte of the code.
+    pub action_deploy_contract_base: Gas,
+    pub action_deploy_contract_byte: Gas,
+
+    /// Number of bytes of the storage usage that every storage record takes in addition to its
+    /// key and value.
+    pub storage_num_extra_bytes_record: StorageUsage,
+    /// Balance that the account has to hold for every byte of its storage usage.
+    pub storage_amount_per_byte: Balance,
+}
+
+impl Default for RuntimeFeesConfig {
+    fn default() -> Self {
+        Self {
+            base: Gas(264_768_111),
+            read_memory_base: Gas(2_609_863_200),
+            read_memory_byte: Gas(3_801_333),
+            write_memory_base: Gas(2_803_794_861),
+            write_memory_byte: Gas(2_723_772),
+            read_register_base: Gas(2_517_165_186),
+            read_register_byte: Gas(98_562),
+            write_register_base: Gas(2_865_522_486),
+            write_register_byte: Gas(3_801_564),
+            utf8_decoding_base: Gas(3_111_779_061),
+            utf8_decoding_byte: Gas(291_580_479),
+            utf16_decoding_base: Gas(3_543_313_050),
+            utf16_decoding_byte: Gas(163_577_493),
+            sha256_base: Gas(4_540_970_250),
+            sha256_byte: Gas(24_117_351),
+            keccak256_base: Gas(5_879_491_275),
+            keccak256_byte: Gas(21_471_105),
+            keccak512_base: Gas(5_811_388_236),
+            keccak512_byte: Gas(36_649_701),
+            ripemd160_base: Gas(853_675_086),
+            ripemd160_block: Gas(680_107_584),
+            ecrecover_base: Gas(278_821_988_457),
+            ed25519_verify_base: Gas(210_000_000_000),
+            ed25519_verify_byte: Gas(9_000_000),
+            alt_bn128_g1_multiexp_base: Gas(713_000_000_000),
+            alt_bn128_g1_multiexp_element: Gas(320_000_000_000),
+            alt_bn128_g1_sum_base: Gas(3_000_000_000),
+            alt_bn128_g1_sum_element: Gas(5_000_000_000),
+            alt_bn128_pairing_check_base: Gas(9_686_000_000_000),
+            alt_bn128_pairing_check_element: Gas(5_102_000_000_000),
+            log_base: Gas(3_543_313_050),
+            log_byte: Gas(13_198_791),
+            storage_write_base: Gas(64_196_736_000),
+            storage_write_key_byte: Gas(70_482_867),
+            storage_write_value_byte: Gas(31_018_539),
+            storage_write_evicted_byte: Gas(32_117_307),
+            storage_read_base: Gas(56_356_845_750),
+            storage_read_key_byte: Gas(30_952_533),
+            storage_read_value_byte: Gas(5_611_005),
+            storage_remove_base: Gas(53_473_030_500),
+            storage_remove_key_byte: Gas(38_220_384),
+            storage_remove_ret_value_byte: Gas(11_531_556),
+            storage_has_key_base: Gas(54_039_896_625),
+            storage_has_key_byte: Gas(30_790_845),
+            promise_and_base: Gas(1_465_013_400),
+            promise_and_per_promise: Gas(5_452_176),
+            promise_return: Gas(560_152_386),
+            validator_stake_base: Gas(911_834_726_400),
+            validator_total_stake_base: Gas(911_834_726_400),
+            action_receipt_creation: Gas(216_119_000_000),
+            action_base: Gas(200_000_000_000),
+            action_function_call_base: Gas(4_639_723_000_000),
+            action_function_call_byte: Gas(4_471_868),
+            action_deploy_contract_base: Gas(369_531_500_000),
+            action_deploy_contract_byte: Gas(13_625_998),
+            storage_num_extra_bytes_record: 40,
+            storage_amount_per_byte: 10u128.pow(19),
+        }
+    }
+}
+
+impl RuntimeFeesConfig {
+    /// A config where nothing costs gas. The storage usage and the storage staking are still
+    /// accounted for.
+    pub fn free() -> Self {
+        Self {
+            base: Gas::ZERO,
+            read_memory_base: Gas::ZERO,
+            read_memory_byte: Gas::ZERO,
+            write_memory_base: Gas::ZERO,
+            write_memory_byte: Gas::ZERO,
+            read_register_base: Gas::ZERO,
+            read_register_byte: Gas::ZERO,
+            write_register_base: Gas::ZERO,
+            write_register_byte: Gas::ZERO,
+            utf8_decoding_base: Gas::ZERO,
+            utf8_decoding_byte: Gas::ZERO,
+            utf16_decoding_base: Gas::ZERO,
+            utf16_decoding_byte: Gas::ZERO,
+            sha256_base: Gas::ZERO,
+            sha256_byte: Gas::ZERO,
+            keccak256_base: Gas::ZERO,
+            keccak256_byte: Gas::ZERO,
+            keccak512_base: Gas::ZERO,
+            keccak512_byte: Gas::ZERO,
+            ripemd160_base: Gas::ZERO,
+            ripemd160_block: Gas::ZERO,
+            ecrecover_base: Gas::ZERO,
+            ed25519_verify_base: Gas::ZERO,
+            ed25519_verify_byte: Gas::ZERO,
+            alt_bn128_g1_multiexp_base: Gas::ZERO,
+            alt_bn128_g1_multiexp_element: Gas::ZERO,
+            alt_bn128_g1_sum_base: Gas::ZERO,
+            alt_bn128_g1_sum_element: Gas::ZERO,
+            alt_bn128_pairing_check_base: Gas::ZERO,
+            alt_bn128_pairing_check_element: Gas::ZERO,
+            log_base: Gas::ZERO,
+            log_byte: Gas::ZERO,
+            storage_write_base: Gas::ZERO,
+            storage_write_key_byte: Gas::ZERO,
+            storage_write_value_byte: Gas::ZERO,
+            storage_write_evicted_byte: Gas::ZERO,
+            storage_read_base: Gas::ZERO,
+            storage_read_key_byte: Gas::ZERO,
+            storage_read_value_byte: Gas::ZERO,
+            storage_remove_base: Gas::ZERO,
+            storage_remove_key_byte: Gas::ZERO,
+            storage_remove_ret_value_byte: Gas::ZERO,
+            storage_has_key_base: Gas::ZERO,
+            storage_has_key_byte: Gas::ZERO,
+            promise_and_base: Gas::ZERO,
+            promise_and_per_promise: Gas::ZERO,
+            promise_return: Gas::ZERO,
+            validator_stake_base: Gas::ZERO,
+            validator_total_stake_base: Gas::ZERO,
+            action_receipt_creation: Gas::ZERO,
+            action_base: Gas::ZERO,
+            action_function_call_base: Gas::ZERO,
+            action_function_call_byte: Gas::ZERO,
+            action_deploy_contract_base: Gas::ZERO,
+            action_deploy_contract_byte: Gas::ZERO,
+            ..Self::default()
+        }
+    }
+}
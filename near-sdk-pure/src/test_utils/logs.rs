@@ -0,0 +1,100 @@
+//! Helpers for checking the logs emitted by a call in the `MockedBlockchain`.
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::events::NearEvent;
+pub use crate::events::EVENT_JSON_PREFIX;
+use crate::test_utils::with_mocked_blockchain;
+
+/// The messages logged during the current call, decoded from either UTF-8 or UTF-16.
+pub fn get_logs() -> Vec<String> {
+    with_mocked_blockchain(|blockchain| blockchain.logs())
+}
+
+/// The events logged during the current call with the `EVENT_JSON:` prefix.
+///
+/// # Panics
+///
+/// Panics if the JSON of an event cannot be parsed.
+pub fn get_events() -> Vec<serde_json::Value> {
+    get_logs()
+        .iter()
+        .filter_map(|log| log.strip_prefix(EVENT_JSON_PREFIX))
+        .map(|event| {
+            serde_json::from_str(event.trim_start())
+                .unwrap_or_else(|err| panic!("Cannot parse event {}: {}", event, err))
+        })
+        .collect()
+}
+
+/// The events of the type `T` logged during the current call, parsed back from their JSON. The
+/// events of the other standards and versions are skipped.
+///
+/// # Panics
+///
+/// Panics if the JSON of an event cannot be parsed.
+pub fn get_near_events<T: NearEvent>() -> Vec<T> {
+    get_events().iter().filter_map(T::from_event_json).collect()
+}
+
+/// Asserts that the event was logged during the current call with the `EVENT_JSON:` prefix.
+///
+/// ```
+/// # use near_sdk_pure::{env, testing_env};
+/// # use near_sdk_pure::test_utils::{assert_event_emitted, VMContextBuilder};
+/// # use serde_json::json;
+/// testing_env!(VMContextBuilder::new().build());
+/// env::log(br#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint"}"#);
+/// assert_event_emitted(json!({"standard": "nep171", "version": "1.0.0", "event": "nft_mint"}));
+/// ```
+#[track_caller]
+pub fn assert_event_emitted(expected: serde_json::Value) {
+    let events = get_events();
+    if !events.contains(&expected) {
+        panic!("Event {} was not emitted, emitted events: {:?}", expected, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::{env, testing_env};
+    use alloc::string::ToString;
+    use alloc::vec;
+    use serde_json::json;
+
+    #[test]
+    fn test_get_logs() {
+        testing_env!(VMContextBuilder::new().build());
+        env::log("hello".as_bytes());
+        let message: Vec<u16> = "héllo 🌍".encode_utf16().collect();
+        env::log_utf16(&message);
+        crate::log!("x = {}", 1);
+        assert_eq!(
+            get_logs(),
+            vec!["hello".to_string(), "héllo 🌍".to_string(), "x = 1".to_string()]
+        );
+
+        testing_env!(VMContextBuilder::new().build());
+        assert!(get_logs().is_empty());
+    }
+
+    #[test]
+    fn test_events() {
+        testing_env!(VMContextBuilder::new().build());
+        env::log(b"EVENT_JSON:{\"event\":\"a\",\"data\":[1]}");
+        env::log(b"not an event");
+        env::log(b"EVENT_JSON: {\"event\":\"b\"}");
+        assert_eq!(get_events(), vec![json!({"event": "a", "data": [1]}), json!({"event": "b"})]);
+        assert_event_emitted(json!({"event": "b"}));
+    }
+
+    #[test]
+    #[should_panic(expected = "Event {\"event\":\"c\"} was not emitted")]
+    fn test_event_not_emitted() {
+        testing_env!(VMContextBuilder::new().build());
+        env::log(b"EVENT_JSON:{\"event\":\"a\"}");
+        assert_event_emitted(json!({"event": "c"}));
+    }
+}
@@ -0,0 +1,1483 @@
+//! An in-memory implementation of `BlockchainInterface` that lets the contract code, `env::*` and
+//! the persistent collections run natively, e.g. in unit tests.
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::{format, vec, vec::Vec};
+use core::cell::RefCell;
+use core::convert::TryFrom;
+use core::mem::size_of;
+
+use sha2::Digest;
+
+use crate::environment::blockchain_interface::BlockchainInterface;
+use crate::promise::{PromiseAction, PromiseDescription};
+#[cfg(any(
+    feature = "ripemd160",
+    feature = "ecrecover",
+    feature = "ed25519-verify",
+    feature = "alt-bn128"
+))]
+use crate::test_utils::crypto;
+use crate::test_utils::{alice, bob, RuntimeFeesConfig};
+#[cfg(feature = "function-call-weight")]
+use crate::types::GasWeight;
+use crate::types::{
+    AccountId, Balance, BlockHeight, EpochHeight, Gas, PromiseIndex, PromiseResult, PublicKey,
+    ReturnData, StorageUsage,
+};
+
+const ERR_INVALID_REGISTER: &str = "Invalid register id";
+const ERR_INVALID_PROMISE_INDEX: &str = "Invalid promise index";
+const ERR_INVALID_PROMISE_RESULT_INDEX: &str = "Invalid promise result index";
+const ERR_JOINT_PROMISE_ACTION: &str = "Cannot append action to a joint promise";
+pub(crate) const ERR_RETURN_JOINT_PROMISE: &str = "Returning joint promise is currently prohibited";
+const ERR_INVALID_UTF8: &str = "Invalid UTF-8 string";
+const ERR_INVALID_ACCOUNT_ID: &str = "Invalid account ID";
+const ERR_INVALID_UTF16: &str = "Invalid UTF-16 string";
+const ERR_GAS_EXCEEDED: &str = "Exceeded the prepaid gas.";
+const ERR_BALANCE_EXCEEDED: &str = "Exceeded the account balance.";
+#[cfg(any(feature = "ecrecover", feature = "ed25519-verify"))]
+const ERR_SIGNATURE_LEN: &str = "Invalid signature length";
+#[cfg(feature = "ecrecover")]
+const ERR_ECRECOVER_HASH_LEN: &str = "Invalid hash length";
+#[cfg(feature = "ecrecover")]
+const ERR_ECRECOVER_V: &str = "Invalid recovery id, only 0 through 3 are valid";
+#[cfg(feature = "ecrecover")]
+const ERR_ECRECOVER_MALLEABILITY_FLAG: &str = "Invalid malleability flag, only 0 and 1 are valid";
+#[cfg(feature = "ed25519-verify")]
+const ERR_PUBLIC_KEY_LEN: &str = "Invalid public key length";
+#[cfg(feature = "alt-bn128")]
+const ERR_ALT_BN128_INPUT_LEN: &str = "Invalid alt_bn128 input length";
+#[cfg(feature = "alt-bn128")]
+const ERR_ALT_BN128_INPUT: &str = "Invalid alt_bn128 point, scalar or sign";
+
+/// Context of the contract call that the `MockedBlockchain` exposes through the context and the
+/// economics API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VMContext {
+    /// The account id of the current contract that we are executing.
+    pub current_account_id: AccountId,
+    /// The account id of that signed the original transaction that led to this execution.
+    pub signer_account_id: AccountId,
+    /// The public key that was used to sign the original transaction that led to this execution.
+    pub signer_account_pk: PublicKey,
+    /// The id of the account that was the previous contract in the chain of cross-contract calls.
+    pub predecessor_account_id: AccountId,
+    /// The input to the contract call.
+    pub input: Vec<u8>,
+    /// The current block height.
+    pub block_index: BlockHeight,
+    /// The current block timestamp (number of non-leap-nanoseconds since January 1, 1970 0:00:00 UTC).
+    pub block_timestamp: u64,
+    /// The current epoch height.
+    pub epoch_height: EpochHeight,
+    /// The balance attached to the given account. Excludes the `attached_deposit` that was
+    /// attached to the transaction.
+    pub account_balance: Balance,
+    /// The balance of locked tokens on the given account.
+    pub account_locked_balance: Balance,
+    /// The account's storage usage before the contract execution.
+    pub storage_usage: StorageUsage,
+    /// The balance that was attached to the call that will be immediately deposited before the
+    /// contract execution starts.
+    pub attached_deposit: Balance,
+    /// The gas attached to the call that can be used to pay for the gas fees.
+    pub prepaid_gas: Gas,
+    /// Initial seed for randomness.
+    pub random_seed: Vec<u8>,
+    /// Stakes of the current validators, returned by the validator API.
+    pub validators: BTreeMap<AccountId, Balance>,
+}
+
+/// The call is made by `bob.near` to `alice.near`, as account IDs cannot be empty, and the other
+/// fields are zero or empty.
+impl Default for VMContext {
+    fn default() -> Self {
+        Self {
+            current_account_id: alice(),
+            signer_account_id: bob(),
+            signer_account_pk: vec![],
+            predecessor_account_id: bob(),
+            input: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            epoch_height: 0,
+            account_balance: 0,
+            account_locked_balance: 0,
+            storage_usage: 0,
+            attached_deposit: 0,
+            prepaid_gas: Gas::ZERO,
+            random_seed: vec![],
+            validators: BTreeMap::new(),
+        }
+    }
+}
+
+/// A promise created by the contract through the promise API of the `MockedBlockchain`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MockedPromise {
+    /// A batch of actions executed on `receiver_id` after the promise `after`, if any, completes.
+    Receipt { receiver_id: AccountId, actions: Vec<PromiseAction>, after: Option<PromiseIndex> },
+    /// A promise that completes when all of the given promises complete.
+    Joint(Vec<PromiseIndex>),
+}
+
+/// Implementation of the blockchain interface that keeps registers, storage, logs and created
+/// promises in memory. Pointers passed to the host functions are addresses in the native memory.
+///
+/// Host functions charge gas according to the `RuntimeFeesConfig` and the call panics with
+/// "Exceeded the prepaid gas." once the used gas goes over `prepaid_gas` of the context. The
+/// storage usage of the context is updated on every storage write and removal.
+///
+/// With `with_balance_tracking` the tokens attached to the created promises are also taken from
+/// the account balance of the context, and the storage stake is checked at the end of the call.
+pub struct MockedBlockchain {
+    context: RefCell<VMContext>,
+    fees: RuntimeFeesConfig,
+    track_balances: bool,
+    used_gas: RefCell<Gas>,
+    registers: RefCell<BTreeMap<u64, Vec<u8>>>,
+    storage: RefCell<BTreeMap<Vec<u8>, Vec<u8>>>,
+    logs: RefCell<Vec<String>>,
+    promises: RefCell<Vec<MockedPromise>>,
+    promise_results: Vec<PromiseResult>,
+    return_data: RefCell<ReturnData>,
+}
+
+impl MockedBlockchain {
+    /// Create a blockchain that executes a call with the given `context` on top of `storage`.
+    /// `promise_results` are the results available to the call if it is a callback.
+    pub fn new(
+        context: VMContext,
+        storage: BTreeMap<Vec<u8>, Vec<u8>>,
+        promise_results: Vec<PromiseResult>,
+    ) -> Self {
+        Self {
+            context: RefCell::new(context),
+            fees: RuntimeFeesConfig::default(),
+            track_balances: false,
+            used_gas: RefCell::new(Gas::ZERO),
+            registers: RefCell::new(BTreeMap::new()),
+            storage: RefCell::new(storage),
+            logs: RefCell::new(vec![]),
+            promises: RefCell::new(vec![]),
+            promise_results,
+            return_data: RefCell::new(ReturnData::None),
+        }
+    }
+
+    /// Use the given fees instead of the default ones.
+    pub fn with_fees_config(mut self, fees: RuntimeFeesConfig) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Take the tokens transferred and attached to the function calls by the created promises from
+    /// the account balance, panicking with "Exceeded the account balance." if it is not enough.
+    /// The methods generated by `#[near_bindgen]` then also fail if the balance does not cover the
+    /// storage usage at the end of the call, see `finish_call`.
+    pub fn with_balance_tracking(mut self) -> Self {
+        self.track_balances = true;
+        self
+    }
+
+    /// Checks that the account balance covers the storage usage, as the runtime does at the end of
+    /// each call. Returns the error that the runtime would fail the call with otherwise.
+    pub fn check_storage_stake(&self) -> Result<(), String> {
+        let context = self.context.borrow();
+        let required = Balance::from(context.storage_usage) * self.fees.storage_amount_per_byte;
+        let available = context.account_balance + context.account_locked_balance;
+        if available < required {
+            return Err(format!(
+                "The account {} wouldn't have enough balance to cover storage, required to have {} yoctoNEAR more",
+                context.current_account_id,
+                required - available
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks what the runtime checks at the end of the call: with `with_balance_tracking`, that
+    /// the account balance covers the storage usage.
+    pub fn finish_call(&self) -> Result<(), String> {
+        if self.track_balances {
+            self.check_storage_stake()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The context of the current call. Its `storage_usage` reflects the storage changes made so
+    /// far.
+    pub fn context(&self) -> VMContext {
+        self.context.borrow().clone()
+    }
+
+    /// The fees charged by the host functions.
+    pub fn fees_config(&self) -> &RuntimeFeesConfig {
+        &self.fees
+    }
+
+    /// The gas used by the call so far, including the gas attached to the created promises.
+    pub fn gas_used(&self) -> Gas {
+        *self.used_gas.borrow()
+    }
+
+    /// A copy of the current content of the storage.
+    pub fn storage(&self) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.storage.borrow().clone()
+    }
+
+    /// Replaces the storage and its usage, e.g. to roll back the changes of an aborted call.
+    pub fn restore_storage(
+        &self,
+        storage: BTreeMap<Vec<u8>, Vec<u8>>,
+        storage_usage: StorageUsage,
+    ) {
+        *self.storage.borrow_mut() = storage;
+        self.context.borrow_mut().storage_usage = storage_usage;
+    }
+
+    /// Takes the content of the storage out, leaving the storage empty.
+    pub fn take_storage(&self) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        self.storage.replace(BTreeMap::new())
+    }
+
+    /// The messages logged so far.
+    pub fn logs(&self) -> Vec<String> {
+        self.logs.borrow().clone()
+    }
+
+    /// The contents of the register, if it was written.
+    pub fn register(&self, register_id: u64) -> Option<Vec<u8>> {
+        self.registers.borrow().get(&register_id).cloned()
+    }
+
+    /// The promises created so far, indexed by their `PromiseIndex`.
+    pub fn promises(&self) -> Vec<MockedPromise> {
+        self.promises.borrow().clone()
+    }
+
+    /// Rebuilds the tree of the promise with the given index from the raw promise calls, so it
+    /// can be compared with `Promise::describe`.
+    pub fn describe_promise(&self, promise_index: PromiseIndex) -> PromiseDescription {
+        match self.promises.borrow()[promise_index as usize].clone() {
+            MockedPromise::Receipt { receiver_id, actions, after } => PromiseDescription::Single {
+                receiver_id,
+                actions,
+                after: after.map(|after| Box::new(self.describe_promise(after))),
+            },
+            MockedPromise::Joint(promise_indices) => PromiseDescription::Joint(
+                promise_indices.into_iter().map(|index| self.describe_promise(index)).collect(),
+            ),
+        }
+    }
+
+    /// The trees of the promises scheduled so far, i.e. of the promises that no other promise
+    /// depends on, in the order of creation.
+    pub fn scheduled_promises(&self) -> Vec<PromiseDescription> {
+        let promises = self.promises.borrow();
+        let mut dependencies: BTreeSet<PromiseIndex> = BTreeSet::new();
+        for promise in promises.iter() {
+            match promise {
+                MockedPromise::Receipt { after, .. } => dependencies.extend(after),
+                MockedPromise::Joint(promise_indices) => dependencies.extend(promise_indices),
+            }
+        }
+        (0..promises.len() as PromiseIndex)
+            .filter(|index| !dependencies.contains(index))
+            .map(|index| self.describe_promise(index))
+            .collect()
+    }
+
+    /// The value returned by the contract through `value_return` or `promise_return`.
+    pub fn return_data(&self) -> ReturnData {
+        self.return_data.borrow().clone()
+    }
+
+    /// Splits the gas that is left unused between the weighted function calls proportionally to
+    /// their weights, turning them into the plain function calls, as the runtime does when the
+    /// call finishes. All of the prepaid gas is used afterwards if there are any weighted calls.
+    #[cfg(feature = "function-call-weight")]
+    pub fn distribute_unused_gas(&self) {
+        let mut promises = self.promises.borrow_mut();
+        let mut actions: Vec<&mut PromiseAction> = promises
+            .iter_mut()
+            .flat_map(|promise| match promise {
+                MockedPromise::Receipt { actions, .. } => actions.iter_mut(),
+                MockedPromise::Joint(_) => [].iter_mut(),
+            })
+            .collect();
+        let total_weight: u64 = actions
+            .iter()
+            .filter_map(|action| match action {
+                PromiseAction::FunctionCallWeight { weight, .. } => Some(weight.0),
+                _ => None,
+            })
+            .sum();
+        if total_weight == 0 {
+            return;
+        }
+        let prepaid_gas = self.context.borrow().prepaid_gas;
+        let unused = prepaid_gas - *self.used_gas.borrow();
+        let mut weight_so_far = 0u64;
+        let mut distributed = Gas::ZERO;
+        for action in actions.iter_mut() {
+            let weight = match action {
+                PromiseAction::FunctionCallWeight { weight, .. } => weight.0,
+                _ => continue,
+            };
+            weight_so_far += weight;
+            let total = (unused.0 as u128 * weight_so_far as u128 / total_weight as u128) as u64;
+            let share = Gas(total) - distributed;
+            distributed = Gas(total);
+            if let PromiseAction::FunctionCallWeight {
+                method_name, arguments, amount, gas, ..
+            } = core::mem::replace(*action, PromiseAction::CreateAccount)
+            {
+                **action = PromiseAction::FunctionCall {
+                    method_name,
+                    arguments,
+                    amount,
+                    gas: gas + share,
+                };
+            }
+        }
+        *self.used_gas.borrow_mut() = prepaid_gas;
+    }
+
+    /// Adds `gas` to the used gas, panicking if it exceeds the prepaid gas. As in the runtime, the
+    /// call that runs out of gas uses all of the prepaid gas.
+    fn charge(&self, gas: Gas) {
+        let used_gas = self.used_gas.borrow().saturating_add(gas);
+        let prepaid_gas = self.context.borrow().prepaid_gas;
+        *self.used_gas.borrow_mut() = core::cmp::min(used_gas, prepaid_gas);
+        if used_gas > prepaid_gas {
+            panic!("{}", ERR_GAS_EXCEEDED);
+        }
+    }
+
+    fn charge_bytes(&self, base: Gas, per_byte: Gas, len: u64) {
+        self.charge(base.saturating_add(per_byte.saturating_mul(len)));
+    }
+
+    /// Number of the items of the alt_bn128 input, which has to consist of the whole items.
+    #[cfg(feature = "alt-bn128")]
+    fn alt_bn128_items(&self, value_len: u64, item_len: u64) -> u64 {
+        if !value_len.is_multiple_of(item_len) {
+            panic!("{}", ERR_ALT_BN128_INPUT_LEN);
+        }
+        value_len / item_len
+    }
+
+    unsafe fn read_memory(&self, len: u64, ptr: u64) -> Vec<u8> {
+        self.charge_bytes(self.fees.read_memory_base, self.fees.read_memory_byte, len);
+        core::slice::from_raw_parts(ptr as *const u8, len as usize).to_vec()
+    }
+
+    unsafe fn write_memory(&self, ptr: u64, data: &[u8]) {
+        let fees = &self.fees;
+        self.charge_bytes(fees.write_memory_base, fees.write_memory_byte, data.len() as u64);
+        core::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+    }
+
+    unsafe fn read_string(&self, len: u64, ptr: u64) -> String {
+        let data = self.read_memory(len, ptr);
+        self.charge_bytes(self.fees.utf8_decoding_base, self.fees.utf8_decoding_byte, len);
+        String::from_utf8(data).unwrap_or_else(|_| panic!("{}", ERR_INVALID_UTF8))
+    }
+
+    unsafe fn read_account_id(&self, len: u64, ptr: u64) -> AccountId {
+        AccountId::try_from(self.read_string(len, ptr))
+            .unwrap_or_else(|_| panic!("{}", ERR_INVALID_ACCOUNT_ID))
+    }
+
+    unsafe fn read_balance(&self, ptr: u64) -> Balance {
+        let mut data = [0u8; size_of::<Balance>()];
+        data.copy_from_slice(&self.read_memory(size_of::<Balance>() as _, ptr));
+        Balance::from_le_bytes(data)
+    }
+
+    unsafe fn write_balance(&self, ptr: u64, balance: Balance) {
+        self.write_memory(ptr, &balance.to_le_bytes());
+    }
+
+    fn set_register(&self, register_id: u64, data: Vec<u8>) {
+        let fees = &self.fees;
+        self.charge_bytes(fees.write_register_base, fees.write_register_byte, data.len() as u64);
+        self.registers.borrow_mut().insert(register_id, data);
+    }
+
+    /// Changes the storage usage by the size of the record with the given key and value.
+    fn update_storage_usage(&self, key: &[u8], old_value: Option<&[u8]>, new_value: Option<&[u8]>) {
+        let record_size = |value: &[u8]| {
+            (key.len() + value.len()) as StorageUsage + self.fees.storage_num_extra_bytes_record
+        };
+        let mut context = self.context.borrow_mut();
+        if let Some(old_value) = old_value {
+            context.storage_usage = context.storage_usage.saturating_sub(record_size(old_value));
+        }
+        if let Some(new_value) = new_value {
+            context.storage_usage += record_size(new_value);
+        }
+    }
+
+    fn push_promise(&self, promise: MockedPromise) -> PromiseIndex {
+        let mut promises = self.promises.borrow_mut();
+        promises.push(promise);
+        (promises.len() - 1) as PromiseIndex
+    }
+
+    fn spend_balance(&self, amount: Balance) {
+        let mut context = self.context.borrow_mut();
+        match context.account_balance.checked_sub(amount) {
+            Some(balance) => context.account_balance = balance,
+            None => {
+                drop(context);
+                panic!("{}", ERR_BALANCE_EXCEEDED)
+            }
+        }
+    }
+
+    fn push_action(&self, promise_index: PromiseIndex, action: PromiseAction) {
+        if self.track_balances {
+            match &action {
+                PromiseAction::FunctionCall { amount, .. }
+                | PromiseAction::FunctionCallWeight { amount, .. }
+                | PromiseAction::Transfer { amount } => self.spend_balance(*amount),
+                _ => {}
+            }
+        }
+        let fees = &self.fees;
+        match &action {
+            PromiseAction::FunctionCall { method_name, arguments, gas, .. }
+            | PromiseAction::FunctionCallWeight { method_name, arguments, gas, .. } => {
+                let len = (method_name.len() + arguments.len()) as u64;
+                self.charge_bytes(
+                    fees.action_function_call_base,
+                    fees.action_function_call_byte,
+                    len,
+                );
+                // The gas attached to the call is used by the current call.
+                self.charge(*gas);
+            }
+            PromiseAction::DeployContract { code } => self.charge_bytes(
+                fees.action_deploy_contract_base,
+                fees.action_deploy_contract_byte,
+                code.len() as u64,
+            ),
+            _ => self.charge(fees.action_base),
+        }
+        let mut promises = self.promises.borrow_mut();
+        match promises.get_mut(promise_index as usize) {
+            Some(MockedPromise::Receipt { actions, .. }) => actions.push(action),
+            Some(MockedPromise::Joint(_)) => panic!("{}", ERR_JOINT_PROMISE_ACTION),
+            None => panic!("{}", ERR_INVALID_PROMISE_INDEX),
+        }
+    }
+
+    fn check_promise_index(&self, promise_index: PromiseIndex) {
+        if promise_index as usize >= self.promises.borrow().len() {
+            panic!("{}", ERR_INVALID_PROMISE_INDEX);
+        }
+    }
+
+    fn create_receipt(&self, account_id: AccountId, after: Option<PromiseIndex>) -> PromiseIndex {
+        if let Some(after) = after {
+            self.check_promise_index(after);
+        }
+        self.charge(self.fees.action_receipt_creation);
+        self.push_promise(MockedPromise::Receipt {
+            receiver_id: account_id,
+            actions: vec![],
+            after,
+        })
+    }
+}
+
+impl BlockchainInterface for MockedBlockchain {
+    unsafe fn read_register(&self, register_id: u64, ptr: u64) {
+        self.charge(self.fees.base);
+        let data = self.registers.borrow().get(&register_id).cloned();
+        match data {
+            Some(data) => {
+                let fees = &self.fees;
+                let len = data.len() as u64;
+                self.charge_bytes(fees.read_register_base, fees.read_register_byte, len);
+                self.write_memory(ptr, &data)
+            }
+            None => panic!("{}", ERR_INVALID_REGISTER),
+        }
+    }
+
+    unsafe fn register_len(&self, register_id: u64) -> u64 {
+        self.charge(self.fees.base);
+        self.registers.borrow().get(&register_id).map_or(u64::MAX, |data| data.len() as u64)
+    }
+
+    unsafe fn current_account_id(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().current_account_id.as_bytes().to_vec();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn signer_account_id(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().signer_account_id.as_bytes().to_vec();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn signer_account_pk(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().signer_account_pk.clone();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn predecessor_account_id(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().predecessor_account_id.as_bytes().to_vec();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn input(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().input.clone();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn block_index(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.context.borrow().block_index
+    }
+
+    unsafe fn block_timestamp(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.context.borrow().block_timestamp
+    }
+
+    unsafe fn epoch_height(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.context.borrow().epoch_height
+    }
+
+    unsafe fn storage_usage(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.context.borrow().storage_usage
+    }
+
+    unsafe fn account_balance(&self, balance_ptr: u64) {
+        self.charge(self.fees.base);
+        let balance = self.context.borrow().account_balance;
+        self.write_balance(balance_ptr, balance)
+    }
+
+    unsafe fn account_locked_balance(&self, balance_ptr: u64) {
+        self.charge(self.fees.base);
+        let balance = self.context.borrow().account_locked_balance;
+        self.write_balance(balance_ptr, balance)
+    }
+
+    unsafe fn attached_deposit(&self, balance_ptr: u64) {
+        self.charge(self.fees.base);
+        let balance = self.context.borrow().attached_deposit;
+        self.write_balance(balance_ptr, balance)
+    }
+
+    unsafe fn prepaid_gas(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.context.borrow().prepaid_gas.0
+    }
+
+    unsafe fn used_gas(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.gas_used().0
+    }
+
+    fn storage_byte_cost(&self) -> Balance {
+        self.fees.storage_amount_per_byte
+    }
+
+    unsafe fn random_seed(&self, register_id: u64) {
+        self.charge(self.fees.base);
+        let data = self.context.borrow().random_seed.clone();
+        self.set_register(register_id, data)
+    }
+
+    unsafe fn sha256(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        self.charge_bytes(self.fees.sha256_base, self.fees.sha256_byte, value_len);
+        self.set_register(register_id, sha2::Sha256::digest(&value).to_vec())
+    }
+
+    unsafe fn keccak256(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        self.charge_bytes(self.fees.keccak256_base, self.fees.keccak256_byte, value_len);
+        self.set_register(register_id, sha3::Keccak256::digest(&value).to_vec())
+    }
+
+    unsafe fn keccak512(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        self.charge_bytes(self.fees.keccak512_base, self.fees.keccak512_byte, value_len);
+        self.set_register(register_id, sha3::Keccak512::digest(&value).to_vec())
+    }
+
+    #[cfg(feature = "ripemd160")]
+    unsafe fn ripemd160(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        // The message is padded with at least 9 bytes to a multiple of 64 bytes.
+        let blocks = (value_len + 9).div_ceil(64);
+        self.charge_bytes(self.fees.ripemd160_base, self.fees.ripemd160_block, blocks);
+        self.set_register(register_id, crypto::ripemd160(&value).to_vec())
+    }
+
+    #[cfg(feature = "ecrecover")]
+    unsafe fn ecrecover(
+        &self,
+        hash_len: u64,
+        hash_ptr: u64,
+        sig_len: u64,
+        sig_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.charge(self.fees.base);
+        self.charge(self.fees.ecrecover_base);
+        let mut hash = [0u8; 32];
+        if hash_len != hash.len() as u64 {
+            panic!("{}", ERR_ECRECOVER_HASH_LEN);
+        }
+        hash.copy_from_slice(&self.read_memory(hash_len, hash_ptr));
+        let mut signature = [0u8; 64];
+        if sig_len != signature.len() as u64 {
+            panic!("{}", ERR_SIGNATURE_LEN);
+        }
+        signature.copy_from_slice(&self.read_memory(sig_len, sig_ptr));
+        if v > 3 {
+            panic!("{}", ERR_ECRECOVER_V);
+        }
+        let reject_malleable = match malleability_flag {
+            0 => false,
+            1 => true,
+            _ => panic!("{}", ERR_ECRECOVER_MALLEABILITY_FLAG),
+        };
+        match crypto::ecrecover(&hash, &signature, v as u8, reject_malleable) {
+            Some(public_key) => {
+                self.set_register(register_id, public_key.to_vec());
+                1
+            }
+            None => 0,
+        }
+    }
+
+    #[cfg(feature = "ed25519-verify")]
+    unsafe fn ed25519_verify(
+        &self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64 {
+        self.charge(self.fees.base);
+        let mut signature = [0u8; 64];
+        if signature_len != signature.len() as u64 {
+            panic!("{}", ERR_SIGNATURE_LEN);
+        }
+        signature.copy_from_slice(&self.read_memory(signature_len, signature_ptr));
+        let message = self.read_memory(message_len, message_ptr);
+        let mut public_key = [0u8; 32];
+        if public_key_len != public_key.len() as u64 {
+            panic!("{}", ERR_PUBLIC_KEY_LEN);
+        }
+        public_key.copy_from_slice(&self.read_memory(public_key_len, public_key_ptr));
+        let fees = &self.fees;
+        self.charge_bytes(fees.ed25519_verify_base, fees.ed25519_verify_byte, message_len);
+        crypto::ed25519_verify(&signature, &message, &public_key) as u64
+    }
+
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_g1_multiexp(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        let items = self.alt_bn128_items(value_len, crypto::alt_bn128::G1_MULTIEXP_ITEM_LEN);
+        let fees = &self.fees;
+        self.charge_bytes(
+            fees.alt_bn128_g1_multiexp_base,
+            fees.alt_bn128_g1_multiexp_element,
+            items,
+        );
+        let result = crypto::alt_bn128::g1_multiexp(&value)
+            .unwrap_or_else(|| panic!("{}", ERR_ALT_BN128_INPUT));
+        self.set_register(register_id, result.to_vec())
+    }
+
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_g1_sum(&self, value_len: u64, value_ptr: u64, register_id: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        let items = self.alt_bn128_items(value_len, crypto::alt_bn128::G1_SUM_ITEM_LEN);
+        let fees = &self.fees;
+        self.charge_bytes(fees.alt_bn128_g1_sum_base, fees.alt_bn128_g1_sum_element, items);
+        let result =
+            crypto::alt_bn128::g1_sum(&value).unwrap_or_else(|| panic!("{}", ERR_ALT_BN128_INPUT));
+        self.set_register(register_id, result.to_vec())
+    }
+
+    #[cfg(feature = "alt-bn128")]
+    unsafe fn alt_bn128_pairing_check(&self, value_len: u64, value_ptr: u64) -> u64 {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        let items = self.alt_bn128_items(value_len, crypto::alt_bn128::PAIRING_CHECK_ITEM_LEN);
+        let fees = &self.fees;
+        self.charge_bytes(
+            fees.alt_bn128_pairing_check_base,
+            fees.alt_bn128_pairing_check_element,
+            items,
+        );
+        crypto::alt_bn128::pairing_check(&value)
+            .unwrap_or_else(|| panic!("{}", ERR_ALT_BN128_INPUT)) as u64
+    }
+
+    unsafe fn value_return(&self, value_len: u64, value_ptr: u64) {
+        self.charge(self.fees.base);
+        let value = self.read_memory(value_len, value_ptr);
+        *self.return_data.borrow_mut() = ReturnData::Value(value);
+    }
+
+    unsafe fn panic(&self) {
+        self.charge(self.fees.base);
+        panic!("Smart contract panicked: explicit guest panic")
+    }
+
+    unsafe fn panic_utf8(&self, len: u64, ptr: u64) {
+        self.charge(self.fees.base);
+        let message = self.read_string(len, ptr);
+        panic!("Smart contract panicked: {}", message)
+    }
+
+    unsafe fn log_utf8(&self, len: u64, ptr: u64) {
+        self.charge(self.fees.base);
+        let message = self.read_string(len, ptr);
+        self.charge_bytes(self.fees.log_base, self.fees.log_byte, message.len() as u64);
+        self.logs.borrow_mut().push(message);
+    }
+
+    unsafe fn log_utf16(&self, len: u64, ptr: u64) {
+        self.charge(self.fees.base);
+        let data = self.read_memory(len, ptr);
+        self.charge_bytes(self.fees.utf16_decoding_base, self.fees.utf16_decoding_byte, len);
+        let chunks = data.chunks_exact(2);
+        if !chunks.remainder().is_empty() {
+            panic!("{}", ERR_INVALID_UTF16);
+        }
+        let units: Vec<u16> = chunks.map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+        let message =
+            String::from_utf16(&units).unwrap_or_else(|_| panic!("{}", ERR_INVALID_UTF16));
+        self.charge_bytes(self.fees.log_base, self.fees.log_byte, message.len() as u64);
+        self.logs.borrow_mut().push(message);
+    }
+
+    unsafe fn promise_create(
+        &self,
+        account_id_len: u64,
+        account_id_ptr: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) -> u64 {
+        let promise_index = self.promise_batch_create(account_id_len, account_id_ptr);
+        self.promise_batch_action_function_call(
+            promise_index,
+            method_name_len,
+            method_name_ptr,
+            arguments_len,
+            arguments_ptr,
+            amount_ptr,
+            gas,
+        );
+        promise_index
+    }
+
+    unsafe fn promise_then(
+        &self,
+        promise_index: u64,
+        account_id_len: u64,
+        account_id_ptr: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) -> u64 {
+        let promise_index = self.promise_batch_then(promise_index, account_id_len, account_id_ptr);
+        self.promise_batch_action_function_call(
+            promise_index,
+            method_name_len,
+            method_name_ptr,
+            arguments_len,
+            arguments_ptr,
+            amount_ptr,
+            gas,
+        );
+        promise_index
+    }
+
+    unsafe fn promise_and(&self, promise_idx_ptr: u64, promise_idx_count: u64) -> u64 {
+        self.charge(self.fees.base);
+        let fees = &self.fees;
+        self.charge_bytes(fees.promise_and_base, fees.promise_and_per_promise, promise_idx_count);
+        let data =
+            self.read_memory(promise_idx_count * size_of::<PromiseIndex>() as u64, promise_idx_ptr);
+        let promise_indices: Vec<PromiseIndex> = data
+            .chunks_exact(size_of::<PromiseIndex>())
+            .map(|chunk| {
+                let mut index = [0u8; size_of::<PromiseIndex>()];
+                index.copy_from_slice(chunk);
+                PromiseIndex::from_le_bytes(index)
+            })
+            .collect();
+        for promise_index in &promise_indices {
+            self.check_promise_index(*promise_index);
+        }
+        self.push_promise(MockedPromise::Joint(promise_indices))
+    }
+
+    unsafe fn promise_batch_create(&self, account_id_len: u64, account_id_ptr: u64) -> u64 {
+        self.charge(self.fees.base);
+        let account_id = self.read_account_id(account_id_len, account_id_ptr);
+        self.create_receipt(account_id, None)
+    }
+
+    unsafe fn promise_batch_then(
+        &self,
+        promise_index: u64,
+        account_id_len: u64,
+        account_id_ptr: u64,
+    ) -> u64 {
+        self.charge(self.fees.base);
+        let account_id = self.read_account_id(account_id_len, account_id_ptr);
+        self.create_receipt(account_id, Some(promise_index))
+    }
+
+    unsafe fn promise_batch_action_create_account(&self, promise_index: u64) {
+        self.charge(self.fees.base);
+        self.push_action(promise_index, PromiseAction::CreateAccount)
+    }
+
+    unsafe fn promise_batch_action_deploy_contract(
+        &self,
+        promise_index: u64,
+        code_len: u64,
+        code_ptr: u64,
+    ) {
+        self.charge(self.fees.base);
+        let code = self.read_memory(code_len, code_ptr);
+        self.push_action(promise_index, PromiseAction::DeployContract { code })
+    }
+
+    unsafe fn promise_batch_action_function_call(
+        &self,
+        promise_index: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+    ) {
+        self.charge(self.fees.base);
+        let action = PromiseAction::FunctionCall {
+            method_name: self.read_memory(method_name_len, method_name_ptr),
+            arguments: self.read_memory(arguments_len, arguments_ptr),
+            amount: self.read_balance(amount_ptr),
+            gas: Gas(gas),
+        };
+        self.push_action(promise_index, action)
+    }
+
+    #[cfg(feature = "function-call-weight")]
+    unsafe fn promise_batch_action_function_call_weight(
+        &self,
+        promise_index: u64,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        amount_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+    ) {
+        self.charge(self.fees.base);
+        let action = PromiseAction::FunctionCallWeight {
+            method_name: self.read_memory(method_name_len, method_name_ptr),
+            arguments: self.read_memory(arguments_len, arguments_ptr),
+            amount: self.read_balance(amount_ptr),
+            gas: Gas(gas),
+            weight: GasWeight(gas_weight),
+        };
+        self.push_action(promise_index, action)
+    }
+
+    unsafe fn promise_batch_action_transfer(&self, promise_index: u64, amount_ptr: u64) {
+        self.charge(self.fees.base);
+        let amount = self.read_balance(amount_ptr);
+        self.push_action(promise_index, PromiseAction::Transfer { amount })
+    }
+
+    unsafe fn promise_batch_action_stake(
+        &self,
+        promise_index: u64,
+        amount_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) {
+        self.charge(self.fees.base);
+        let action = PromiseAction::Stake {
+            amount: self.read_balance(amount_ptr),
+            public_key: self.read_memory(public_key_len, public_key_ptr),
+        };
+        self.push_action(promise_index, action)
+    }
+
+    unsafe fn promise_batch_action_add_key_with_full_access(
+        &self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        _nonce: u64,
+    ) {
+        self.charge(self.fees.base);
+        let public_key = self.read_memory(public_key_len, public_key_ptr);
+        self.push_action(promise_index, PromiseAction::AddFullAccessKey { public_key })
+    }
+
+    unsafe fn promise_batch_action_add_key_with_function_call(
+        &self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        _nonce: u64,
+        allowance_ptr: u64,
+        receiver_id_len: u64,
+        receiver_id_ptr: u64,
+        method_names_len: u64,
+        method_names_ptr: u64,
+    ) {
+        self.charge(self.fees.base);
+        let action = PromiseAction::AddAccessKey {
+            public_key: self.read_memory(public_key_len, public_key_ptr),
+            allowance: self.read_balance(allowance_ptr),
+            receiver_id: self.read_account_id(receiver_id_len, receiver_id_ptr),
+            method_names: self.read_memory(method_names_len, method_names_ptr),
+        };
+        self.push_action(promise_index, action)
+    }
+
+    unsafe fn promise_batch_action_delete_key(
+        &self,
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) {
+        self.charge(self.fees.base);
+        let public_key = self.read_memory(public_key_len, public_key_ptr);
+        self.push_action(promise_index, PromiseAction::DeleteKey { public_key })
+    }
+
+    unsafe fn promise_batch_action_delete_account(
+        &self,
+        promise_index: u64,
+        beneficiary_id_len: u64,
+        beneficiary_id_ptr: u64,
+    ) {
+        self.charge(self.fees.base);
+        let beneficiary_id = self.read_account_id(beneficiary_id_len, beneficiary_id_ptr);
+        self.push_action(promise_index, PromiseAction::DeleteAccount { beneficiary_id })
+    }
+
+    unsafe fn promise_results_count(&self) -> u64 {
+        self.charge(self.fees.base);
+        self.promise_results.len() as u64
+    }
+
+    unsafe fn promise_result(&self, result_idx: u64, register_id: u64) -> u64 {
+        self.charge(self.fees.base);
+        match self.promise_results.get(result_idx as usize) {
+            Some(PromiseResult::NotReady) => 0,
+            Some(PromiseResult::Successful(data)) => {
+                self.set_register(register_id, data.clone());
+                1
+            }
+            Some(PromiseResult::Failed) => 2,
+            None => panic!("{}", ERR_INVALID_PROMISE_RESULT_INDEX),
+        }
+    }
+
+    unsafe fn promise_return(&self, promise_id: u64) {
+        self.charge(self.fees.base);
+        self.charge(self.fees.promise_return);
+        match self.promises.borrow().get(promise_id as usize) {
+            Some(MockedPromise::Receipt { .. }) => {}
+            Some(MockedPromise::Joint(_)) => panic!("{}", ERR_RETURN_JOINT_PROMISE),
+            None => panic!("{}", ERR_INVALID_PROMISE_INDEX),
+        }
+        *self.return_data.borrow_mut() = ReturnData::ReceiptIndex(promise_id);
+    }
+
+    unsafe fn storage_write(
+        &self,
+        key_len: u64,
+        key_ptr: u64,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> u64 {
+        self.charge(self.fees.base);
+        self.charge(self.fees.storage_write_base);
+        let key = self.read_memory(key_len, key_ptr);
+        let value = self.read_memory(value_len, value_ptr);
+        self.charge(self.fees.storage_write_key_byte.saturating_mul(key_len));
+        self.charge(self.fees.storage_write_value_byte.saturating_mul(value_len));
+        let evicted = self.storage.borrow_mut().insert(key.clone(), value.clone());
+        self.update_storage_usage(&key, evicted.as_deref(), Some(&value));
+        match evicted {
+            Some(evicted) => {
+                self.charge(
+                    self.fees.storage_write_evicted_byte.saturating_mul(evicted.len() as u64),
+                );
+                self.set_register(register_id, evicted);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn storage_read(&self, key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+        self.charge(self.fees.base);
+        self.charge(self.fees.storage_read_base);
+        let key = self.read_memory(key_len, key_ptr);
+        self.charge(self.fees.storage_read_key_byte.saturating_mul(key_len));
+        let value = self.storage.borrow().get(&key).cloned();
+        match value {
+            Some(value) => {
+                self.charge(self.fees.storage_read_value_byte.saturating_mul(value.len() as u64));
+                self.set_register(register_id, value);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn storage_remove(&self, key_len: u64, key_ptr: u64, register_id: u64) -> u64 {
+        self.charge(self.fees.base);
+        self.charge(self.fees.storage_remove_base);
+        let key = self.read_memory(key_len, key_ptr);
+        self.charge(self.fees.storage_remove_key_byte.saturating_mul(key_len));
+        let evicted = self.storage.borrow_mut().remove(&key);
+        match evicted {
+            Some(evicted) => {
+                self.update_storage_usage(&key, Some(&evicted), None);
+                self.charge(
+                    self.fees.storage_remove_ret_value_byte.saturating_mul(evicted.len() as u64),
+                );
+                self.set_register(register_id, evicted);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn storage_has_key(&self, key_len: u64, key_ptr: u64) -> u64 {
+        self.charge(self.fees.base);
+        let key = self.read_memory(key_len, key_ptr);
+        let fees = &self.fees;
+        self.charge_bytes(fees.storage_has_key_base, fees.storage_has_key_byte, key_len);
+        self.storage.borrow().contains_key(&key) as u64
+    }
+
+    unsafe fn validator_stake(&self, account_id_len: u64, account_id_ptr: u64, stake_ptr: u64) {
+        self.charge(self.fees.base);
+        let account_id = self.read_string(account_id_len, account_id_ptr);
+        self.charge(self.fees.validator_stake_base);
+        let stake =
+            self.context.borrow().validators.get(account_id.as_str()).cloned().unwrap_or_default();
+        self.write_balance(stake_ptr, stake)
+    }
+
+    unsafe fn validator_total_stake(&self, stake_ptr: u64) {
+        self.charge(self.fees.base);
+        self.charge(self.fees.validator_total_stake_base);
+        let total_stake = self.context.borrow().validators.values().sum();
+        self.write_balance(stake_ptr, total_stake)
+    }
+
+    fn as_mocked_blockchain(&self) -> Option<&MockedBlockchain> {
+        Some(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collections::{UnorderedMap, Vector};
+    use crate::test_utils::{alice, bob, carol, with_mocked_blockchain};
+    use crate::types::GasWeight;
+    use crate::{env, Promise};
+    use alloc::string::ToString;
+
+    fn setup() {
+        let context = VMContext {
+            current_account_id: alice(),
+            signer_account_id: bob(),
+            predecessor_account_id: bob(),
+            input: b"{\"a\":1}".to_vec(),
+            block_index: 10,
+            attached_deposit: 7,
+            prepaid_gas: Gas::from_tgas(300),
+            validators: vec![(carol(), 100)].into_iter().collect(),
+            ..Default::default()
+        };
+        env::set_blockchain_interface(Box::new(MockedBlockchain::new(
+            context,
+            BTreeMap::new(),
+            vec![PromiseResult::Successful(b"1".to_vec()), PromiseResult::Failed],
+        )));
+    }
+
+    #[test]
+    fn test_context() {
+        setup();
+        assert_eq!(env::current_account_id(), alice());
+        assert_eq!(env::predecessor_account_id(), bob());
+        assert_eq!(env::input(), Some(b"{\"a\":1}".to_vec()));
+        assert_eq!(env::block_index(), 10);
+        assert_eq!(env::attached_deposit(), 7);
+        assert_eq!(env::prepaid_gas(), Gas::from_tgas(300));
+        assert_eq!(env::validator_stake(&carol()), 100);
+        assert_eq!(env::validator_stake(&bob()), 0);
+        assert_eq!(env::validator_total_stake(), 100);
+    }
+
+    #[test]
+    fn test_storage() {
+        setup();
+        assert!(!env::storage_write(b"key", b"value"));
+        assert!(env::storage_write(b"key", b"value2"));
+        assert_eq!(env::storage_get_evicted(), Some(b"value".to_vec()));
+        assert_eq!(env::storage_read(b"key"), Some(b"value2".to_vec()));
+        assert!(env::storage_has_key(b"key"));
+        assert!(env::storage_remove(b"key"));
+        assert!(!env::storage_has_key(b"key"));
+        assert_eq!(env::storage_read(b"key"), None);
+    }
+
+    #[test]
+    fn test_hashes() {
+        setup();
+        assert_eq!(
+            env::sha256(b"tea"),
+            [
+                0xa9, 0xf7, 0x4d, 0x1e, 0xc3, 0x6e, 0xbd, 0xeb, 0x2d, 0xa3, 0xf6, 0xe5, 0x86, 0x80,
+                0x90, 0xcd, 0x2a, 0x2d, 0x20, 0xb3, 0xdc, 0xca, 0x7b, 0x62, 0xf6, 0x03, 0x04, 0xb1,
+                0xd3, 0xd9, 0xef, 0x42
+            ]
+        );
+        assert_eq!(env::keccak256(b"").len(), 32);
+        assert_eq!(env::keccak512(b"").len(), 64);
+    }
+
+    #[test]
+    fn test_logs_and_return() {
+        setup();
+        env::log(b"hello");
+        env::value_return(b"42");
+        with_mocked_blockchain(|blockchain| {
+            assert_eq!(blockchain.logs(), vec!["hello".to_string()]);
+            assert_eq!(blockchain.return_data(), ReturnData::Value(b"42".to_vec()));
+        });
+    }
+
+    #[test]
+    fn test_promises() {
+        setup();
+        assert_eq!(env::promise_results_count(), 2);
+        assert_eq!(env::promise_result(0), PromiseResult::Successful(b"1".to_vec()));
+        assert_eq!(env::promise_result(1), PromiseResult::Failed);
+        {
+            Promise::new(bob())
+                .transfer(10)
+                .and(Promise::new(carol()).create_account())
+                .then(Promise::new(alice()).function_call(b"cb".to_vec(), vec![], 0, Gas(5)))
+                .as_return();
+        }
+        with_mocked_blockchain(|blockchain| {
+            assert_eq!(
+                blockchain.promises(),
+                vec![
+                    MockedPromise::Receipt {
+                        receiver_id: bob(),
+                        actions: vec![PromiseAction::Transfer { amount: 10 }],
+                        after: None,
+                    },
+                    MockedPromise::Receipt {
+                        receiver_id: carol(),
+                        actions: vec![PromiseAction::CreateAccount],
+                        after: None,
+                    },
+                    MockedPromise::Joint(vec![0, 1]),
+                    MockedPromise::Receipt {
+                        receiver_id: alice(),
+                        actions: vec![PromiseAction::FunctionCall {
+                            method_name: b"cb".to_vec(),
+                            arguments: vec![],
+                            amount: 0,
+                            gas: Gas(5),
+                        }],
+                        after: Some(2),
+                    },
+                ]
+            );
+            assert_eq!(blockchain.return_data(), ReturnData::ReceiptIndex(3));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Returning joint promise is currently prohibited")]
+    fn test_return_joint_promise() {
+        setup();
+        Promise::new(bob()).transfer(10).and(Promise::new(carol()).create_account()).as_return();
+    }
+
+    #[test]
+    fn test_describe_promises() {
+        setup();
+        let promise = Promise::new(bob())
+            .transfer(10)
+            .and(Promise::new(carol()).create_account())
+            .then(Promise::new(alice()).function_call(b"on_transfer".to_vec(), vec![], 0, Gas(5)));
+        let expected = PromiseDescription::new(bob(), vec![PromiseAction::Transfer { amount: 10 }])
+            .and(PromiseDescription::new(carol(), vec![PromiseAction::CreateAccount]))
+            .then(PromiseDescription::new(
+                alice(),
+                vec![PromiseAction::FunctionCall {
+                    method_name: b"on_transfer".to_vec(),
+                    arguments: vec![],
+                    amount: 0,
+                    gas: Gas(5),
+                }],
+            ));
+        assert_eq!(promise.describe(), expected);
+        with_mocked_blockchain(|blockchain| assert!(blockchain.promises().is_empty()));
+        drop(promise);
+        Promise::new(carol()).delete_account(bob());
+        with_mocked_blockchain(|blockchain| {
+            assert_eq!(
+                blockchain.scheduled_promises(),
+                vec![
+                    expected,
+                    PromiseDescription::new(
+                        carol(),
+                        vec![PromiseAction::DeleteAccount { beneficiary_id: bob() }]
+                    )
+                ]
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Smart contract panicked: oops")]
+    fn test_panic() {
+        setup();
+        env::panic(b"oops");
+    }
+
+    #[test]
+    fn test_storage_usage() {
+        setup();
+        env::storage_write(b"key", b"value");
+        assert_eq!(env::storage_usage(), 3 + 5 + 40);
+        env::storage_write(b"key", b"v");
+        assert_eq!(env::storage_usage(), 3 + 1 + 40);
+        env::storage_write(b"key2", b"");
+        assert_eq!(env::storage_usage(), 3 + 1 + 40 + 4 + 40);
+        env::storage_remove(b"key");
+        env::storage_remove(b"key2");
+        assert_eq!(env::storage_usage(), 0);
+    }
+
+    #[test]
+    fn test_used_gas() {
+        setup();
+        let fees = RuntimeFeesConfig::default();
+        let before = env::used_gas();
+        env::storage_write(b"key", b"value");
+        let storage_write_gas = env::used_gas() - before;
+        assert!(storage_write_gas > fees.storage_write_base);
+
+        let before = env::used_gas();
+        env::storage_write(b"key", &[0u8; 1000]);
+        assert!(
+            env::used_gas() - before > storage_write_gas + fees.storage_write_value_byte * 1000
+        );
+
+        let before = env::used_gas();
+        Promise::new(bob()).function_call(b"f".to_vec(), vec![], 0, Gas::ONE_TERA);
+        assert!(env::used_gas() - before > Gas::ONE_TERA + fees.action_receipt_creation);
+    }
+
+    #[test]
+    fn test_function_call_weight() {
+        setup();
+        Promise::new(bob())
+            .function_call_weight(b"a".to_vec(), vec![], 0, Gas::ONE_TERA, GasWeight(1))
+            .then(Promise::new(alice()).function_call_weight(
+                b"b".to_vec(),
+                vec![],
+                0,
+                Gas::ONE_TERA,
+                GasWeight(2),
+            ));
+        let remaining = env::remaining_gas();
+        let attached_gas = || {
+            with_mocked_blockchain(|blockchain| {
+                blockchain
+                    .promises()
+                    .into_iter()
+                    .flat_map(|promise| match promise {
+                        MockedPromise::Receipt { actions, .. } => actions,
+                        MockedPromise::Joint(_) => vec![],
+                    })
+                    .map(|action| match action {
+                        PromiseAction::FunctionCall { gas, .. } => gas,
+                        action => panic!("Unexpected action {:?}", action),
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // The runtime splits the gas left at the end of the call.
+        #[cfg(feature = "function-call-weight")]
+        let (a, b) = {
+            with_mocked_blockchain(|blockchain| {
+                assert!(blockchain.promises().iter().all(|promise| matches!(
+                    promise,
+                    MockedPromise::Receipt { actions, .. }
+                        if matches!(actions[0], PromiseAction::FunctionCallWeight { .. })
+                )));
+                blockchain.distribute_unused_gas();
+            });
+            let gas = attached_gas();
+            assert_eq!(gas[0] + gas[1], remaining + Gas::from_tgas(2));
+            assert_eq!(
+                with_mocked_blockchain(|blockchain| blockchain.gas_used()),
+                Gas::from_tgas(300)
+            );
+            (gas[0], gas[1])
+        };
+        // The SDK splits the gas that remains when the promise is scheduled, keeping some of it.
+        #[cfg(not(feature = "function-call-weight"))]
+        let (a, b) = {
+            assert!(remaining > Gas::ZERO && remaining < Gas::from_tgas(15));
+            let gas = attached_gas();
+            (gas[0], gas[1])
+        };
+        let (a, b) = ((a - Gas::ONE_TERA).as_gas(), (b - Gas::ONE_TERA).as_gas());
+        assert!(a > Gas::from_tgas(90).as_gas());
+        assert!((2 * a).abs_diff(b) <= 2);
+    }
+
+    #[test]
+    fn test_free_fees() {
+        crate::testing_env!(
+            VMContext::default(),
+            vec![],
+            RuntimeFeesConfig { storage_num_extra_bytes_record: 0, ..RuntimeFeesConfig::free() }
+        );
+        env::storage_write(b"key", b"value");
+        env::log(b"hello");
+        assert_eq!(env::used_gas(), Gas::ZERO);
+        assert_eq!(env::storage_usage(), 8);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeded the prepaid gas.")]
+    fn test_exceeded_prepaid_gas() {
+        let context = VMContext { prepaid_gas: Gas::ONE_TERA, ..Default::default() };
+        crate::testing_env!(context);
+        let mut vec = Vector::new(b"v".to_vec());
+        for i in 0..100u64 {
+            vec.push(&i);
+        }
+    }
+
+    fn setup_balance_tracking(balance: Balance) {
+        let context = VMContext {
+            current_account_id: alice(),
+            account_balance: balance,
+            prepaid_gas: Gas::from_tgas(300),
+            ..Default::default()
+        };
+        let blockchain =
+            MockedBlockchain::new(context, BTreeMap::new(), vec![]).with_balance_tracking();
+        env::set_blockchain_interface(Box::new(blockchain));
+    }
+
+    #[test]
+    fn test_balance_tracking() {
+        setup_balance_tracking(100);
+        Promise::new(bob()).transfer(30);
+        Promise::new(bob()).function_call(b"f".to_vec(), vec![], 20, Gas::ZERO);
+        assert_eq!(env::account_balance(), 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exceeded the account balance.")]
+    fn test_balance_exceeded() {
+        setup_balance_tracking(100);
+        Promise::new(bob()).transfer(101);
+    }
+
+    #[test]
+    fn test_storage_stake() {
+        setup_balance_tracking(10u128.pow(19) * 48);
+        env::storage_write(b"key", b"value");
+        with_mocked_blockchain(|blockchain| assert_eq!(blockchain.check_storage_stake(), Ok(())));
+        env::storage_write(b"key", b"value2");
+        with_mocked_blockchain(|blockchain| {
+            assert_eq!(
+                blockchain.check_storage_stake(),
+                Err(format!(
+                    "The account {} wouldn't have enough balance to cover storage, required to have {} yoctoNEAR more",
+                    alice(),
+                    10u128.pow(19)
+                ))
+            )
+        });
+    }
+
+    #[test]
+    fn test_collections() {
+        setup();
+        let mut vec = Vector::new(b"v".to_vec());
+        vec.extend(0..10u64);
+        assert_eq!(vec.swap_remove(3), 3);
+        assert_eq!(vec.to_vec(), vec![0, 1, 2, 9, 4, 5, 6, 7, 8]);
+
+        let mut map = UnorderedMap::new(b"m".to_vec());
+        map.insert(&1u64, &"one".to_string());
+        map.insert(&2u64, &"two".to_string());
+        assert_eq!(map.remove(&1), Some("one".to_string()));
+        assert_eq!(map.to_vec(), vec![(2, "two".to_string())]);
+    }
+}
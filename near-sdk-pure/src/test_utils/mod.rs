@@ -1,9 +1,82 @@
 #[allow(dead_code)]
 pub mod test_env;
+pub use test_env::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod mocked_blockchain;
+#[cfg(not(target_arch = "wasm32"))]
+pub use mocked_blockchain::{MockedBlockchain, MockedPromise, VMContext};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod context;
+#[cfg(not(target_arch = "wasm32"))]
+pub use context::VMContextBuilder;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod fault_injection;
+#[cfg(not(target_arch = "wasm32"))]
+pub use fault_injection::{inject_fault, Fault, FaultInjector, INJECTED_FAULT};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod trace;
+#[cfg(not(target_arch = "wasm32"))]
+pub use trace::{
+    record_trace, replay_trace, HostCall, Trace, TraceHandle, TraceRecorder, TraceReplayer,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod logs;
+#[cfg(not(target_arch = "wasm32"))]
+pub use logs::{assert_event_emitted, get_events, get_logs, get_near_events, EVENT_JSON_PREFIX};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod crypto;
+#[cfg(not(target_arch = "wasm32"))]
+mod fees;
+#[cfg(not(target_arch = "wasm32"))]
+pub use fees::RuntimeFeesConfig;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod simulator;
+#[cfg(not(target_arch = "wasm32"))]
+pub use simulator::{
+    Account, ContractMethods, ExecutionOutcome, ExecutionResult, ExecutionStatus, Simulator,
+    DEFAULT_GAS,
+};
 
 use alloc::vec::Vec;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{env, PromiseResult};
+#[cfg(not(target_arch = "wasm32"))]
+use alloc::boxed::Box;
 
+/// Sets up a `MockedBlockchain` with the given `VMContext` and, optionally, the results of the
+/// promises available to a callback and the `RuntimeFeesConfig`. The storage of the previously set
+/// `MockedBlockchain` is kept, so consecutive calls act on the same contract state.
+///
+/// ```
+/// # use near_sdk_pure::testing_env;
+/// # use near_sdk_pure::test_utils::{carol, VMContextBuilder};
+/// testing_env!(VMContextBuilder::new().predecessor_account_id(carol()).attached_deposit(1).build());
+/// assert_eq!(near_sdk_pure::env::attached_deposit(), 1);
+/// ```
+#[cfg(not(target_arch = "wasm32"))]
+#[macro_export]
+macro_rules! testing_env {
+    ($context:expr, $promise_results:expr, $fees_config:expr) => {
+        $crate::test_utils::testing_env_with_fees_config($context, $promise_results, $fees_config)
+    };
+    ($context:expr, $promise_results:expr) => {
+        $crate::test_utils::testing_env_with_promise_results($context, $promise_results)
+    };
+    ($context:expr) => {
+        $crate::test_utils::testing_env_with_promise_results(
+            $context,
+            $crate::maybestd::vec::Vec::new(),
+        )
+    };
+}
 
 /// Objects stored on the trie directly should have identifiers. If identifier is not provided
 /// explicitly than `Default` trait would use this index to generate an id.
@@ -18,3 +91,60 @@ pub(crate) fn next_trie_id() -> Vec<u8> {
         id.to_le_bytes().to_vec()
     }
 }
+
+/// Calls `f` with the `MockedBlockchain` that is currently set as the blockchain interface.
+///
+/// # Panics
+///
+/// Panics if the blockchain interface is not set or is not a `MockedBlockchain`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn with_mocked_blockchain<F, R>(f: F) -> R
+where
+    F: FnOnce(&MockedBlockchain) -> R,
+{
+    env::with_interface(|b| f(b.as_mocked_blockchain().expect("Mocked blockchain is not set.")))
+}
+
+/// Fails the call with the error that the runtime would fail it with at the end, see
+/// `MockedBlockchain::finish_call`. Called by the native methods generated by `#[near_bindgen]`.
+#[doc(hidden)]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn finish_call() {
+    let result = env::with_interface(|b| b.as_mocked_blockchain().map(MockedBlockchain::finish_call));
+    if let Some(Err(message)) = result {
+        panic!("{}", message);
+    }
+}
+
+/// Function version of `testing_env!`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn testing_env_with_promise_results(context: VMContext, promise_results: Vec<PromiseResult>) {
+    testing_env_with_fees_config(context, promise_results, RuntimeFeesConfig::default())
+}
+
+/// Function version of `testing_env!` with a custom `RuntimeFeesConfig`. The storage usage of the
+/// previous `MockedBlockchain` is kept together with its storage, the `storage_usage` of the
+/// context only applies when the storage is empty.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn testing_env_with_fees_config(
+    mut context: VMContext,
+    promise_results: Vec<PromiseResult>,
+    fees_config: RuntimeFeesConfig,
+) {
+    let storage = match env::take_blockchain_interface() {
+        Some(b) => match b.as_mocked_blockchain() {
+            Some(b) => {
+                let storage = b.take_storage();
+                if !storage.is_empty() {
+                    context.storage_usage = b.context().storage_usage;
+                }
+                storage
+            }
+            None => Default::default(),
+        },
+        None => Default::default(),
+    };
+    env::set_blockchain_interface(Box::new(
+        MockedBlockchain::new(context, storage, promise_results).with_fees_config(fees_config),
+    ));
+}
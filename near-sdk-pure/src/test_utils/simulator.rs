@@ -0,0 +1,625 @@
+//! A native simulator of several contracts that execute transactions and the receipts created by
+//! their promises, in the spirit of the real runtime.
+//!
+//! Contracts are registered with the native wrappers that `#[near_bindgen]` generates for the
+//! exported methods:
+//!
+//! ```ignore
+//! let mut sim = Simulator::new();
+//! sim.deploy(alice(), &[("new", new), ("transfer", transfer), ("on_transfer", on_transfer)]);
+//! let contract = FungibleTokenContract { account_id: alice() };
+//! let res = sim.call(&bob(), contract.transfer(carol(), 10), DEFAULT_GAS, 0);
+//! assert!(res.is_ok());
+//! ```
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::env;
+use crate::promise::PromiseAction;
+use crate::test_utils::mocked_blockchain::ERR_RETURN_JOINT_PROMISE;
+use crate::test_utils::{MockedBlockchain, MockedPromise, RuntimeFeesConfig, VMContext};
+use crate::types::{AccountId, Balance, Gas, PromiseResult, PublicKey, ReturnData, StorageUsage};
+use crate::utils::PendingContractTx;
+
+/// Gas attached to a transaction when the caller does not care about it, 300 Tgas.
+pub const DEFAULT_GAS: Gas = Gas::from_tgas(300);
+
+/// Exported methods of a contract, i.e. the native wrappers generated by `#[near_bindgen]`, by
+/// method name.
+pub type ContractMethods = &'static [(&'static str, fn())];
+
+/// An account of the simulated blockchain.
+#[derive(Clone, Default)]
+pub struct Account {
+    /// The liquid balance of the account.
+    pub amount: Balance,
+    /// The balance locked for staking.
+    pub locked: Balance,
+    /// The code deployed with the `DeployContract` action.
+    pub code: Vec<u8>,
+    /// The contract storage.
+    pub storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// The storage usage of the contract storage, as accounted by the `MockedBlockchain`.
+    pub storage_usage: StorageUsage,
+    /// Public keys of the access keys added to the account.
+    pub access_keys: BTreeSet<PublicKey>,
+    methods: Option<ContractMethods>,
+}
+
+/// Status of the execution of a single receipt.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExecutionStatus {
+    /// The receipt was executed and returned the given value.
+    SuccessValue(Vec<u8>),
+    /// The receipt was executed and its result is the result of the receipt with the given id.
+    SuccessReceiptId(usize),
+    /// The receipt failed with the given message. Its state changes were reverted.
+    Failure(String),
+}
+
+/// Outcome of the execution of a single receipt.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionOutcome {
+    /// Id of the executed receipt.
+    pub receipt_id: usize,
+    /// The account that executed the receipt.
+    pub executor_id: AccountId,
+    /// The account that created the receipt.
+    pub predecessor_id: AccountId,
+    /// Messages logged during the execution.
+    pub logs: Vec<String>,
+    /// Gas used by the function calls of the receipt, including the gas attached to the promises
+    /// they created.
+    pub gas_used: Gas,
+    pub status: ExecutionStatus,
+}
+
+/// Result of a transaction together with all the receipts it spawned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionResult {
+    /// The final result of the transaction, after following the receipts it returned.
+    pub status: ExecutionStatus,
+    /// Outcomes of all executed receipts, in the order of execution.
+    pub outcomes: Vec<ExecutionOutcome>,
+}
+
+impl ExecutionResult {
+    /// Returns `true` if the transaction result is successful.
+    pub fn is_ok(&self) -> bool {
+        !matches!(self.status, ExecutionStatus::Failure(_))
+    }
+
+    /// Returns the value returned by the transaction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the transaction failed.
+    pub fn unwrap(&self) -> Vec<u8> {
+        match &self.status {
+            ExecutionStatus::SuccessValue(value) => value.clone(),
+            status => panic!("Transaction was not successful: {:?}", status),
+        }
+    }
+
+    /// Deserializes the value returned by the transaction from JSON.
+    pub fn unwrap_json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.unwrap()).expect("Cannot deserialize the result from JSON.")
+    }
+
+    /// Messages logged by all receipts of the transaction.
+    pub fn logs(&self) -> Vec<String> {
+        self.outcomes.iter().flat_map(|outcome| outcome.logs.iter().cloned()).collect()
+    }
+}
+
+/// The result a receipt resolved to, possibly by returning another receipt.
+enum ReceiptResult {
+    Value(Vec<u8>),
+    Failure(String),
+    Forward(usize),
+}
+
+struct Receipt {
+    signer_id: AccountId,
+    predecessor_id: AccountId,
+    receiver_id: AccountId,
+    actions: Vec<PromiseAction>,
+    /// Receipts whose results are passed to the function calls of this receipt.
+    dependencies: Vec<usize>,
+    result: Option<ReceiptResult>,
+}
+
+/// Result of running a single method of a contract natively.
+struct MethodOutcome {
+    storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    logs: Vec<String>,
+    promises: Vec<MockedPromise>,
+    return_data: ReturnData,
+    gas_used: Gas,
+    storage_usage: StorageUsage,
+    account_balance: Balance,
+    /// The panic message if the method panicked.
+    result: Result<(), String>,
+    /// Whether the account balance covers the storage usage at the end of the call.
+    storage_stake: Result<(), String>,
+}
+
+/// A local blockchain with several accounts and contracts.
+///
+/// Methods are executed one at a time with their own `MockedBlockchain` set as the blockchain
+/// interface of the current thread, and the interface set before the call is restored after it.
+/// Simulators in different threads do not affect each other.
+pub struct Simulator {
+    accounts: BTreeMap<AccountId, Account>,
+    codes: BTreeMap<Vec<u8>, ContractMethods>,
+    receipts: Vec<Receipt>,
+    /// Height of the current block, incremented for each executed receipt.
+    pub block_index: u64,
+    /// Timestamp of the current block in nanoseconds.
+    pub block_timestamp: u64,
+    /// Timestamp increment per block.
+    pub block_time: u64,
+    /// Fees charged by the function calls.
+    pub fees_config: RuntimeFeesConfig,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    pub fn new() -> Self {
+        Self {
+            accounts: BTreeMap::new(),
+            codes: BTreeMap::new(),
+            receipts: vec![],
+            block_index: 0,
+            block_timestamp: 0,
+            block_time: 1_000_000_000,
+            fees_config: RuntimeFeesConfig::default(),
+        }
+    }
+
+    /// Creates an account with the given balance, or updates the balance of an existing one.
+    pub fn create_account(&mut self, account_id: AccountId, amount: Balance) {
+        self.accounts.entry(account_id).or_default().amount = amount;
+    }
+
+    /// Deploys the contract with the given exported methods to the account, creating the account
+    /// if it does not exist.
+    pub fn deploy(&mut self, account_id: AccountId, methods: ContractMethods) {
+        self.accounts.entry(account_id).or_default().methods = Some(methods);
+    }
+
+    /// Associates the code that can be deployed with the `DeployContract` action with the
+    /// methods it exports.
+    pub fn register_code(&mut self, code: Vec<u8>, methods: ContractMethods) {
+        self.codes.insert(code, methods);
+    }
+
+    /// The account with the given id, if it exists.
+    pub fn account(&self, account_id: &str) -> Option<&Account> {
+        self.accounts.get(account_id)
+    }
+
+    /// Mutable access to the account with the given id, if it exists.
+    pub fn account_mut(&mut self, account_id: &str) -> Option<&mut Account> {
+        self.accounts.get_mut(account_id)
+    }
+
+    /// Reads the contract state stored by `env::state_write` on the given account.
+    pub fn state<T: borsh::BorshDeserialize>(&self, account_id: &str) -> Option<T> {
+        self.account(account_id)?
+            .storage
+            .get(env::STATE_KEY)
+            .map(|data| T::try_from_slice(data).expect("Cannot deserialize the contract state."))
+    }
+
+    /// Calls a view method. The state changes and the promises made by the method are discarded.
+    pub fn view(&self, tx: PendingContractTx) -> Result<Vec<u8>, String> {
+        let account = self
+            .accounts
+            .get(&tx.receiver_id)
+            .ok_or_else(|| format!("Account {} does not exist", tx.receiver_id))?;
+        let method = find_method(account, &tx.receiver_id, &tx.method)?;
+        let context = VMContext {
+            current_account_id: tx.receiver_id.clone(),
+            signer_account_id: tx.receiver_id.clone(),
+            predecessor_account_id: tx.receiver_id.clone(),
+            input: tx.args,
+            block_index: self.block_index,
+            block_timestamp: self.block_timestamp,
+            account_balance: account.amount,
+            account_locked_balance: account.locked,
+            storage_usage: account.storage_usage,
+            prepaid_gas: DEFAULT_GAS,
+            ..Default::default()
+        };
+        let storage = account.storage.clone();
+        let outcome = run_method(method, context, storage, vec![], &self.fees_config);
+        outcome.result?;
+        match outcome.return_data {
+            ReturnData::Value(value) => Ok(value),
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Signs and executes the transaction on behalf of `signer_id`, then executes all the
+    /// receipts created by the transaction until none remain.
+    ///
+    /// The deposit is taken from the balance of the signer, the gas is not paid for.
+    pub fn call(
+        &mut self,
+        signer_id: &AccountId,
+        tx: PendingContractTx,
+        gas: Gas,
+        deposit: Balance,
+    ) -> ExecutionResult {
+        let signer = self.accounts.get_mut(signer_id);
+        match signer {
+            Some(signer) if signer.amount >= deposit => signer.amount -= deposit,
+            _ => {
+                let message = format!(
+                    "Sender {} does not have enough balance for operation costing {}",
+                    signer_id, deposit
+                );
+                return ExecutionResult {
+                    status: ExecutionStatus::Failure(message),
+                    outcomes: vec![],
+                };
+            }
+        }
+        let receipt_id = self.receipts.len();
+        self.receipts.push(Receipt {
+            signer_id: signer_id.clone(),
+            predecessor_id: signer_id.clone(),
+            receiver_id: tx.receiver_id,
+            actions: vec![PromiseAction::FunctionCall {
+                method_name: tx.method.into_bytes(),
+                arguments: tx.args,
+                amount: deposit,
+                gas,
+            }],
+            dependencies: vec![],
+            result: None,
+        });
+        let mut pending: VecDeque<usize> = vec![receipt_id].into_iter().collect();
+        let mut outcomes = vec![];
+        while let Some(position) = pending.iter().position(|id| self.is_ready(*id)) {
+            let id = pending.remove(position).unwrap();
+            let (outcome, new_receipts) = self.execute_receipt(id);
+            outcomes.push(outcome);
+            pending.extend(new_receipts);
+        }
+        let status = match self.resolve(receipt_id) {
+            PromiseResult::Successful(value) => ExecutionStatus::SuccessValue(value),
+            _ => ExecutionStatus::Failure(self.failure_message(receipt_id)),
+        };
+        ExecutionResult { status, outcomes }
+    }
+
+    fn is_ready(&self, receipt_id: usize) -> bool {
+        self.receipts[receipt_id]
+            .dependencies
+            .iter()
+            .all(|id| self.resolve(*id) != PromiseResult::NotReady)
+    }
+
+    /// The result of the receipt, following the receipts that it returned.
+    fn resolve(&self, mut receipt_id: usize) -> PromiseResult {
+        loop {
+            match &self.receipts[receipt_id].result {
+                None => return PromiseResult::NotReady,
+                Some(ReceiptResult::Value(value)) => {
+                    return PromiseResult::Successful(value.clone())
+                }
+                Some(ReceiptResult::Failure(_)) => return PromiseResult::Failed,
+                Some(ReceiptResult::Forward(id)) => receipt_id = *id,
+            }
+        }
+    }
+
+    fn failure_message(&self, mut receipt_id: usize) -> String {
+        loop {
+            match &self.receipts[receipt_id].result {
+                Some(ReceiptResult::Forward(id)) => receipt_id = *id,
+                Some(ReceiptResult::Failure(message)) => return message.clone(),
+                _ => return "Receipt was not executed".to_string(),
+            }
+        }
+    }
+
+    /// Executes the receipt, returning its outcome and the ids of the receipts it created.
+    fn execute_receipt(&mut self, receipt_id: usize) -> (ExecutionOutcome, Vec<usize>) {
+        self.block_index += 1;
+        self.block_timestamp += self.block_time;
+        let snapshot = self.accounts.clone();
+        let promise_results: Vec<PromiseResult> =
+            self.receipts[receipt_id].dependencies.iter().map(|id| self.resolve(*id)).collect();
+        let actions = self.receipts[receipt_id].actions.clone();
+        let receiver_id = self.receipts[receipt_id].receiver_id.clone();
+
+        let mut logs = vec![];
+        let mut gas_used = Gas::ZERO;
+        let mut new_receipts = vec![];
+        let mut result = Ok(ReceiptResult::Value(vec![]));
+        for action in actions {
+            result = self
+                .apply_action(receipt_id, action, &promise_results, &mut logs, &mut gas_used)
+                .map(|(result, mut created)| {
+                    new_receipts.append(&mut created);
+                    result
+                });
+            if result.is_err() {
+                break;
+            }
+        }
+        let result = result.unwrap_or_else(|message| {
+            // Revert the state changes, drop the receipts created by the failed receipt and refund
+            // the attached tokens to the predecessor.
+            self.accounts = snapshot;
+            self.receipts.truncate(self.receipts.len() - new_receipts.len());
+            new_receipts.clear();
+            let receipt = &self.receipts[receipt_id];
+            let refund: Balance = receipt.actions.iter().map(attached_amount).sum();
+            if let Some(predecessor) = self.accounts.get_mut(&receipt.predecessor_id) {
+                predecessor.amount += refund;
+            }
+            ReceiptResult::Failure(message)
+        });
+        let status = match &result {
+            ReceiptResult::Value(value) => ExecutionStatus::SuccessValue(value.clone()),
+            ReceiptResult::Failure(message) => ExecutionStatus::Failure(message.clone()),
+            ReceiptResult::Forward(id) => ExecutionStatus::SuccessReceiptId(*id),
+        };
+        let receipt = &mut self.receipts[receipt_id];
+        receipt.result = Some(result);
+        let outcome = ExecutionOutcome {
+            receipt_id,
+            executor_id: receiver_id,
+            predecessor_id: receipt.predecessor_id.clone(),
+            logs,
+            gas_used,
+            status,
+        };
+        (outcome, new_receipts)
+    }
+
+    /// Applies a single action of the receipt. Returns the result of the action and the ids of
+    /// the receipts it created, or the error message.
+    #[allow(clippy::type_complexity)]
+    fn apply_action(
+        &mut self,
+        receipt_id: usize,
+        action: PromiseAction,
+        promise_results: &[PromiseResult],
+        logs: &mut Vec<String>,
+        gas_used: &mut Gas,
+    ) -> Result<(ReceiptResult, Vec<usize>), String> {
+        let receipt = &self.receipts[receipt_id];
+        let receiver_id = receipt.receiver_id.clone();
+        let predecessor_id = receipt.predecessor_id.clone();
+        let signer_id = receipt.signer_id.clone();
+        if let PromiseAction::CreateAccount = action {
+            if self.accounts.contains_key(&receiver_id) {
+                return Err(format!("Account {} already exists", receiver_id));
+            }
+            self.accounts.insert(receiver_id, Account::default());
+            return Ok((ReceiptResult::Value(vec![]), vec![]));
+        }
+        let account = self
+            .accounts
+            .get_mut(&receiver_id)
+            .ok_or_else(|| format!("Account {} does not exist", receiver_id))?;
+        match action {
+            PromiseAction::CreateAccount => unreachable!(),
+            PromiseAction::DeployContract { code } => {
+                account.methods = self.codes.get(&code).cloned();
+                account.code = code;
+            }
+            // The weighted calls left after `distribute_unused_gas` have zero weights.
+            PromiseAction::FunctionCall { method_name, arguments, amount, gas }
+            | PromiseAction::FunctionCallWeight { method_name, arguments, amount, gas, .. } => {
+                account.amount += amount;
+                let method_name = String::from_utf8(method_name)
+                    .map_err(|_| "Method name is not valid UTF-8".to_string())?;
+                let method = find_method(account, &receiver_id, &method_name)?;
+                let context = VMContext {
+                    current_account_id: receiver_id.clone(),
+                    signer_account_id: signer_id,
+                    predecessor_account_id: predecessor_id,
+                    input: arguments,
+                    block_index: self.block_index,
+                    block_timestamp: self.block_timestamp,
+                    account_balance: account.amount,
+                    account_locked_balance: account.locked,
+                    storage_usage: account.storage_usage,
+                    attached_deposit: amount,
+                    prepaid_gas: gas,
+                    random_seed: self.block_index.to_le_bytes().to_vec(),
+                    ..Default::default()
+                };
+                let storage = core::mem::take(&mut account.storage);
+                let promise_results = promise_results.to_vec();
+                let outcome =
+                    run_method(method, context, storage, promise_results, &self.fees_config);
+                logs.extend(outcome.logs);
+                *gas_used += outcome.gas_used;
+                outcome.result?;
+                outcome.storage_stake?;
+                account.storage = outcome.storage;
+                account.storage_usage = outcome.storage_usage;
+                account.amount = outcome.account_balance;
+                let created = self.create_receipts(receipt_id, &outcome.promises);
+                let result = match outcome.return_data {
+                    ReturnData::Value(value) => ReceiptResult::Value(value),
+                    ReturnData::ReceiptIndex(index) => match created.get(index as usize) {
+                        Some(Created::Receipt(id)) => ReceiptResult::Forward(*id),
+                        Some(Created::Joint(_)) => return Err(ERR_RETURN_JOINT_PROMISE.to_string()),
+                        None => return Err("Invalid returned promise".to_string()),
+                    },
+                    ReturnData::None => ReceiptResult::Value(vec![]),
+                };
+                let ids = created
+                    .into_iter()
+                    .filter_map(|created| match created {
+                        Created::Receipt(id) => Some(id),
+                        Created::Joint(_) => None,
+                    })
+                    .collect();
+                return Ok((result, ids));
+            }
+            PromiseAction::Transfer { amount } => account.amount += amount,
+            PromiseAction::Stake { amount, .. } => {
+                let total = account.amount + account.locked;
+                if amount > total {
+                    return Err(format!(
+                        "Account {} does not have enough balance to stake",
+                        receiver_id
+                    ));
+                }
+                account.locked = amount;
+                account.amount = total - amount;
+            }
+            PromiseAction::AddFullAccessKey { public_key }
+            | PromiseAction::AddAccessKey { public_key, .. } => {
+                account.access_keys.insert(public_key);
+            }
+            PromiseAction::DeleteKey { public_key } => {
+                if !account.access_keys.remove(&public_key) {
+                    return Err(format!("Access key does not exist on {}", receiver_id));
+                }
+            }
+            PromiseAction::DeleteAccount { beneficiary_id } => {
+                let account = self.accounts.remove(&receiver_id).unwrap();
+                if let Some(beneficiary) = self.accounts.get_mut(&beneficiary_id) {
+                    beneficiary.amount += account.amount;
+                }
+            }
+        }
+        Ok((ReceiptResult::Value(vec![]), vec![]))
+    }
+
+    /// Turns the promises created by a function call into receipts.
+    fn create_receipts(&mut self, receipt_id: usize, promises: &[MockedPromise]) -> Vec<Created> {
+        let signer_id = self.receipts[receipt_id].signer_id.clone();
+        let predecessor_id = self.receipts[receipt_id].receiver_id.clone();
+        let mut created: Vec<Created> = vec![];
+        for promise in promises {
+            let next = match promise {
+                MockedPromise::Receipt { receiver_id, actions, after } => {
+                    let dependencies = match after {
+                        Some(index) => created[*index as usize].receipt_ids(),
+                        None => vec![],
+                    };
+                    self.receipts.push(Receipt {
+                        signer_id: signer_id.clone(),
+                        predecessor_id: predecessor_id.clone(),
+                        receiver_id: receiver_id.clone(),
+                        actions: actions.clone(),
+                        dependencies,
+                        result: None,
+                    });
+                    Created::Receipt(self.receipts.len() - 1)
+                }
+                MockedPromise::Joint(indices) => Created::Joint(
+                    indices
+                        .iter()
+                        .flat_map(|index| created[*index as usize].receipt_ids())
+                        .collect(),
+                ),
+            };
+            created.push(next);
+        }
+        created
+    }
+}
+
+/// Receipts created for a promise.
+enum Created {
+    Receipt(usize),
+    Joint(Vec<usize>),
+}
+
+impl Created {
+    fn receipt_ids(&self) -> Vec<usize> {
+        match self {
+            Created::Receipt(id) => vec![*id],
+            Created::Joint(ids) => ids.clone(),
+        }
+    }
+}
+
+/// Tokens attached to the action, which are refunded if the receipt fails.
+fn attached_amount(action: &PromiseAction) -> Balance {
+    match action {
+        PromiseAction::FunctionCall { amount, .. }
+        | PromiseAction::FunctionCallWeight { amount, .. }
+        | PromiseAction::Transfer { amount } => *amount,
+        _ => 0,
+    }
+}
+
+fn find_method(account: &Account, account_id: &str, method_name: &str) -> Result<fn(), String> {
+    let methods =
+        account.methods.ok_or_else(|| format!("Contract is not deployed to {}", account_id))?;
+    methods
+        .iter()
+        .find(|(name, _)| *name == method_name)
+        .map(|(_, method)| *method)
+        .ok_or_else(|| format!("Method {} is not found on {}", method_name, account_id))
+}
+
+/// Runs the method against a fresh `MockedBlockchain`, restoring the previously set blockchain
+/// interface afterwards.
+fn run_method(
+    method: fn(),
+    context: VMContext,
+    storage: BTreeMap<Vec<u8>, Vec<u8>>,
+    promise_results: Vec<PromiseResult>,
+    fees_config: &RuntimeFeesConfig,
+) -> MethodOutcome {
+    let previous = env::take_blockchain_interface();
+    env::set_blockchain_interface(Box::new(
+        MockedBlockchain::new(context, storage, promise_results)
+            .with_fees_config(fees_config.clone())
+            .with_balance_tracking(),
+    ));
+    let panic_message = catch_unwind(AssertUnwindSafe(method)).err().map(|payload| {
+        if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else {
+            "Smart contract panicked".to_string()
+        }
+    });
+    let blockchain = env::take_blockchain_interface().expect("Blockchain interface was removed.");
+    let blockchain = blockchain.as_mocked_blockchain().expect("Blockchain interface was replaced.");
+    #[cfg(feature = "function-call-weight")]
+    if panic_message.is_none() {
+        blockchain.distribute_unused_gas();
+    }
+    let context = blockchain.context();
+    let outcome = MethodOutcome {
+        storage: blockchain.take_storage(),
+        logs: blockchain.logs(),
+        promises: blockchain.promises(),
+        return_data: blockchain.return_data(),
+        gas_used: blockchain.gas_used(),
+        storage_usage: context.storage_usage,
+        account_balance: context.account_balance,
+        result: panic_message.map_or(Ok(()), Err),
+        storage_stake: blockchain.check_storage_stake(),
+    };
+    if let Some(previous) = previous {
+        env::set_blockchain_interface(previous);
+    }
+    outcome
+}
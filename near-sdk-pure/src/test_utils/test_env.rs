@@ -1,15 +1,14 @@
 use crate::types::AccountId;
-use alloc::string::ToString;
 
 pub fn alice() -> AccountId {
-    "alice.near".to_string()
+    AccountId::new_unchecked("alice.near".into())
 }
 
 pub fn bob() -> AccountId {
-    "bob.near".to_string()
+    AccountId::new_unchecked("bob.near".into())
 }
 
 pub fn carol() -> AccountId {
-    "carol.near".to_string()
+    AccountId::new_unchecked("carol.near".into())
 }
 
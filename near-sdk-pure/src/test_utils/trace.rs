@@ -0,0 +1,477 @@
+//! Recording of the host calls made by the contract, and their replay.
+//!
+//! The trace does not depend on the addresses of the guest memory, so a trace recorded in one run
+//! can be replayed in another one, or compared with the trace of another version of the SDK.
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::mem::size_of;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::env;
+use crate::environment::blockchain_interface::BlockchainInterface;
+use crate::test_utils::MockedBlockchain;
+use crate::types::Balance;
+
+/// A host call made by the contract.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
+pub struct HostCall {
+    /// Name of the host function.
+    pub name: String,
+    /// Arguments that are not pointers to the guest memory or lengths of the memory regions.
+    pub args: Vec<u64>,
+    /// Contents of the guest memory read by the host function.
+    pub input: Vec<Vec<u8>>,
+    /// Contents of the guest memory written by the host function.
+    pub output: Option<Vec<u8>>,
+    /// Contents of the register written by the host function.
+    pub register: Option<Vec<u8>>,
+    /// The returned value, if the host function returns one.
+    pub result: Option<u64>,
+    /// The panic message, if the host function panicked.
+    pub panic: Option<String>,
+}
+
+/// Sequence of the host calls made by the contract.
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, BorshSerialize, BorshDeserialize,
+)]
+pub struct Trace {
+    pub calls: Vec<HostCall>,
+}
+
+/// Handle to the trace shared with a `TraceRecorder` or a `TraceReplayer`.
+#[derive(Clone, Default)]
+pub struct TraceHandle(Rc<RefCell<Trace>>);
+
+impl TraceHandle {
+    /// The host calls recorded so far, or the host calls that are not replayed yet.
+    pub fn trace(&self) -> Trace {
+        self.0.borrow().clone()
+    }
+}
+
+/// Guest memory written by a host function.
+enum Output {
+    /// The register is copied to the pointer.
+    Register(u64, u64),
+    /// A balance is written to the pointer.
+    Balance(u64),
+}
+
+/// Arguments of a host call split by how the host function uses them.
+#[derive(Default)]
+struct Access {
+    args: Vec<u64>,
+    /// Lengths and pointers of the memory regions that are read.
+    input: Vec<(u64, u64)>,
+    output: Option<Output>,
+    /// The written register, and whether it is only written if the call returns 1.
+    register: Option<(u64, bool)>,
+}
+
+const BALANCE_LEN: u64 = size_of::<Balance>() as u64;
+
+impl Access {
+    fn new(name: &str, args: &[u64]) -> Self {
+        let a = |index: usize| args[index];
+        let values = |indices: &[usize]| indices.iter().map(|index| args[*index]).collect();
+        match name {
+            "read_register" => Self {
+                args: values(&[0]),
+                output: Some(Output::Register(a(0), a(1))),
+                ..Self::default()
+            },
+            "current_account_id"
+            | "signer_account_id"
+            | "signer_account_pk"
+            | "predecessor_account_id"
+            | "input"
+            | "random_seed" => {
+                Self { args: values(&[0]), register: Some((a(0), false)), ..Self::default() }
+            }
+            "account_balance"
+            | "account_locked_balance"
+            | "attached_deposit"
+            | "validator_total_stake" => {
+                Self { output: Some(Output::Balance(a(0))), ..Self::default() }
+            }
+            "sha256"
+            | "keccak256"
+            | "keccak512"
+            | "ripemd160"
+            | "alt_bn128_g1_multiexp"
+            | "alt_bn128_g1_sum" => Self {
+                args: values(&[2]),
+                input: vec![(a(0), a(1))],
+                register: Some((a(2), false)),
+                ..Self::default()
+            },
+            "value_return"
+            | "panic_utf8"
+            | "log_utf8"
+            | "log_utf16"
+            | "promise_batch_create"
+            | "storage_has_key" => Self { input: vec![(a(0), a(1))], ..Self::default() },
+            "promise_create" => Self {
+                args: values(&[7]),
+                input: vec![(a(0), a(1)), (a(2), a(3)), (a(4), a(5)), (BALANCE_LEN, a(6))],
+                ..Self::default()
+            },
+            "promise_then" => Self {
+                args: values(&[0, 8]),
+                input: vec![(a(1), a(2)), (a(3), a(4)), (a(5), a(6)), (BALANCE_LEN, a(7))],
+                ..Self::default()
+            },
+            "promise_and" => {
+                Self { input: vec![(a(1) * size_of::<u64>() as u64, a(0))], ..Self::default() }
+            }
+            "promise_batch_then"
+            | "promise_batch_action_deploy_contract"
+            | "promise_batch_action_delete_key"
+            | "promise_batch_action_delete_account" => {
+                Self { args: values(&[0]), input: vec![(a(1), a(2))], ..Self::default() }
+            }
+            "promise_batch_action_function_call" => Self {
+                args: values(&[0, 6]),
+                input: vec![(a(1), a(2)), (a(3), a(4)), (BALANCE_LEN, a(5))],
+                ..Self::default()
+            },
+            "promise_batch_action_function_call_weight" => Self {
+                args: values(&[0, 6, 7]),
+                input: vec![(a(1), a(2)), (a(3), a(4)), (BALANCE_LEN, a(5))],
+                ..Self::default()
+            },
+            "promise_batch_action_transfer" => {
+                Self { args: values(&[0]), input: vec![(BALANCE_LEN, a(1))], ..Self::default() }
+            }
+            "promise_batch_action_stake" => Self {
+                args: values(&[0]),
+                input: vec![(BALANCE_LEN, a(1)), (a(2), a(3))],
+                ..Self::default()
+            },
+            "promise_batch_action_add_key_with_full_access" => {
+                Self { args: values(&[0, 3]), input: vec![(a(1), a(2))], ..Self::default() }
+            }
+            "promise_batch_action_add_key_with_function_call" => Self {
+                args: values(&[0, 3]),
+                input: vec![(a(1), a(2)), (BALANCE_LEN, a(4)), (a(5), a(6)), (a(7), a(8))],
+                ..Self::default()
+            },
+            "promise_result" => {
+                Self { args: values(&[0, 1]), register: Some((a(1), true)), ..Self::default() }
+            }
+            "storage_write" => Self {
+                args: values(&[4]),
+                input: vec![(a(0), a(1)), (a(2), a(3))],
+                register: Some((a(4), true)),
+                ..Self::default()
+            },
+            "storage_read" | "storage_remove" => Self {
+                args: values(&[2]),
+                input: vec![(a(0), a(1))],
+                register: Some((a(2), true)),
+                ..Self::default()
+            },
+            "ecrecover" => Self {
+                args: values(&[4, 5, 6]),
+                input: vec![(a(0), a(1)), (a(2), a(3))],
+                register: Some((a(6), true)),
+                ..Self::default()
+            },
+            "ed25519_verify" => {
+                Self { input: vec![(a(0), a(1)), (a(2), a(3)), (a(4), a(5))], ..Self::default() }
+            }
+            "alt_bn128_pairing_check" => Self { input: vec![(a(0), a(1))], ..Self::default() },
+            "validator_stake" => Self {
+                input: vec![(a(0), a(1))],
+                output: Some(Output::Balance(a(2))),
+                ..Self::default()
+            },
+            // The remaining host functions take no pointers.
+            _ => Self { args: args.to_vec(), ..Self::default() },
+        }
+    }
+
+    unsafe fn read_input(&self) -> Vec<Vec<u8>> {
+        self.input.iter().map(|(len, ptr)| read_memory(*len, *ptr)).collect()
+    }
+}
+
+unsafe fn read_memory(len: u64, ptr: u64) -> Vec<u8> {
+    if len == 0 {
+        return Vec::new();
+    }
+    core::slice::from_raw_parts(ptr as *const u8, len as usize).to_vec()
+}
+
+/// Conversion of the values returned by the host functions.
+trait ReturnValue: Copy {
+    fn into_result(self) -> Option<u64>;
+    fn from_result(result: Option<u64>) -> Self;
+}
+
+impl ReturnValue for () {
+    fn into_result(self) -> Option<u64> {
+        None
+    }
+
+    fn from_result(_result: Option<u64>) -> Self {}
+}
+
+impl ReturnValue for u64 {
+    fn into_result(self) -> Option<u64> {
+        Some(self)
+    }
+
+    fn from_result(result: Option<u64>) -> Self {
+        result.expect("The recorded host call has no result.")
+    }
+}
+
+fn panic_message(payload: &(dyn core::any::Any + Send)) -> String {
+    match payload.downcast_ref::<String>() {
+        Some(message) => message.clone(),
+        None => {
+            payload.downcast_ref::<&str>().map(|message| message.to_string()).unwrap_or_default()
+        }
+    }
+}
+
+/// Wrapper of a `MockedBlockchain` that records every host call to a `Trace`.
+pub struct TraceRecorder {
+    inner: Box<dyn BlockchainInterface>,
+    trace: TraceHandle,
+}
+
+impl TraceRecorder {
+    /// Wraps the interface, which should be a `MockedBlockchain` possibly wrapped into other
+    /// interfaces, since the registers are read from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interface does not give access to a `MockedBlockchain`.
+    pub fn new(inner: Box<dyn BlockchainInterface>) -> Self {
+        assert!(
+            inner.as_mocked_blockchain().is_some(),
+            "TraceRecorder requires a MockedBlockchain."
+        );
+        Self { inner, trace: TraceHandle::default() }
+    }
+
+    /// Handle to the recorded trace.
+    pub fn handle(&self) -> TraceHandle {
+        self.trace.clone()
+    }
+
+    fn register(&self, register_id: u64) -> Option<Vec<u8>> {
+        self.inner.as_mocked_blockchain().and_then(|blockchain| blockchain.register(register_id))
+    }
+
+    unsafe fn record<T: ReturnValue, F: FnOnce() -> T>(&self, name: &str, args: &[u64], f: F) -> T {
+        let access = Access::new(name, args);
+        let mut call = HostCall {
+            name: name.to_string(),
+            args: access.args.clone(),
+            input: access.read_input(),
+            ..HostCall::default()
+        };
+        if let Some(Output::Register(register_id, _)) = access.output {
+            call.output = self.register(register_id);
+        }
+        let result = match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(result) => result,
+            Err(payload) => {
+                call.panic = Some(panic_message(&*payload));
+                self.trace.0.borrow_mut().calls.push(call);
+                resume_unwind(payload)
+            }
+        };
+        call.result = result.into_result();
+        if let Some(Output::Balance(ptr)) = access.output {
+            call.output = Some(read_memory(BALANCE_LEN, ptr));
+        }
+        match access.register {
+            Some((register_id, false)) => call.register = self.register(register_id),
+            Some((register_id, true)) if call.result == Some(1) => {
+                call.register = self.register(register_id)
+            }
+            _ => {}
+        }
+        self.trace.0.borrow_mut().calls.push(call);
+        result
+    }
+}
+
+macro_rules! impl_trace_recorder {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        impl BlockchainInterface for TraceRecorder {
+            $(
+                $(#[$meta])*
+                unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    self.record(stringify!($name), &[$($arg),*], || self.inner.$name($($arg),*))
+                }
+            )*
+
+            fn as_mocked_blockchain(&self) -> Option<&MockedBlockchain> {
+                self.inner.as_mocked_blockchain()
+            }
+        }
+    };
+}
+
+for_each_host_function!(impl_trace_recorder);
+
+/// Blockchain interface that answers the host calls of the contract from a recorded `Trace`.
+///
+/// Every host call has to match the next call of the trace by the name, the arguments and the
+/// input read from the guest memory, otherwise the replayer panics.
+pub struct TraceReplayer {
+    calls: RefCell<VecDeque<HostCall>>,
+    remaining: TraceHandle,
+}
+
+impl TraceReplayer {
+    pub fn new(trace: Trace) -> Self {
+        let remaining = TraceHandle(Rc::new(RefCell::new(trace.clone())));
+        Self { calls: RefCell::new(trace.calls.into()), remaining }
+    }
+
+    /// Handle to the host calls that are not replayed yet.
+    pub fn handle(&self) -> TraceHandle {
+        self.remaining.clone()
+    }
+
+    unsafe fn replay<T: ReturnValue>(&self, name: &str, args: &[u64]) -> T {
+        let access = Access::new(name, args);
+        let call = self.calls.borrow_mut().pop_front().unwrap_or_else(|| {
+            panic!("The host call {} is made after the end of the trace.", name)
+        });
+        self.remaining.0.borrow_mut().calls.remove(0);
+        let actual = HostCall {
+            name: name.to_string(),
+            args: access.args.clone(),
+            input: access.read_input(),
+            ..HostCall::default()
+        };
+        if (&call.name, &call.args, &call.input) != (&actual.name, &actual.args, &actual.input) {
+            panic!("The host call {:?} does not match the trace, expected {:?}", actual, call);
+        }
+        if let Some(message) = call.panic {
+            panic!("{}", message);
+        }
+        match (access.output, &call.output) {
+            (Some(Output::Register(_, ptr)), Some(data))
+            | (Some(Output::Balance(ptr)), Some(data)) => {
+                core::ptr::copy_nonoverlapping(data.as_ptr(), ptr as *mut u8, data.len());
+            }
+            _ => {}
+        }
+        T::from_result(call.result)
+    }
+}
+
+macro_rules! impl_trace_replayer {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        impl BlockchainInterface for TraceReplayer {
+            $(
+                $(#[$meta])*
+                unsafe fn $name(&self, $($arg: $ty),*) $(-> $ret)? {
+                    self.replay(stringify!($name), &[$($arg),*])
+                }
+            )*
+        }
+    };
+}
+
+for_each_host_function!(impl_trace_replayer);
+
+/// Wraps the current blockchain interface into a `TraceRecorder`.
+///
+/// # Panics
+///
+/// Panics if the blockchain interface is not set or is not a `MockedBlockchain`.
+pub fn record_trace() -> TraceHandle {
+    let inner = env::take_blockchain_interface().expect("Blockchain interface is not set.");
+    let recorder = TraceRecorder::new(inner);
+    let handle = recorder.handle();
+    env::set_blockchain_interface(Box::new(recorder));
+    handle
+}
+
+/// Sets a `TraceReplayer` of the trace as the blockchain interface.
+pub fn replay_trace(trace: Trace) -> TraceHandle {
+    let replayer = TraceReplayer::new(trace);
+    let handle = replayer.handle();
+    env::set_blockchain_interface(Box::new(replayer));
+    handle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::VMContextBuilder;
+    use crate::testing_env;
+
+    fn contract_call() -> (Option<Vec<u8>>, Balance) {
+        env::storage_write(b"trace_key", b"value");
+        env::log(b"written");
+        (env::storage_read(b"trace_key"), env::attached_deposit())
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        testing_env!(VMContextBuilder::new().attached_deposit(7).build());
+        let handle = record_trace();
+        let result = contract_call();
+        let trace = handle.trace();
+        let names: Vec<_> = trace.calls.iter().map(|call| call.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "storage_write",
+                "log_utf8",
+                "storage_read",
+                "register_len",
+                "read_register",
+                "attached_deposit"
+            ]
+        );
+        assert_eq!(trace.calls[0].input, vec![b"trace_key".to_vec(), b"value".to_vec()]);
+        assert_eq!(trace.calls[2].register, Some(b"value".to_vec()));
+        assert_eq!(trace.calls[5].output, Some(7u128.to_le_bytes().to_vec()));
+
+        let json = serde_json::to_string(&trace).unwrap();
+        let trace: Trace = serde_json::from_str(&json).unwrap();
+        let remaining = replay_trace(trace);
+        assert_eq!(contract_call(), result);
+        assert_eq!(remaining.trace(), Trace::default());
+    }
+
+    #[test]
+    fn test_replay_mismatch_and_panic() {
+        testing_env!(VMContextBuilder::new().build());
+        let handle = record_trace();
+        catch_unwind(|| env::panic(b"recorded")).unwrap_err();
+        let trace = handle.trace();
+        assert_eq!(trace.calls[0].panic.as_deref(), Some("Smart contract panicked: recorded"));
+
+        replay_trace(trace.clone());
+        let payload = catch_unwind(|| env::panic(b"recorded")).unwrap_err();
+        assert_eq!(panic_message(&*payload), "Smart contract panicked: recorded");
+
+        replay_trace(trace);
+        let payload = catch_unwind(|| env::log(b"other")).unwrap_err();
+        assert!(panic_message(&*payload).starts_with("The host call HostCall { name: \"log_utf8\""));
+        env::take_blockchain_interface();
+    }
+}
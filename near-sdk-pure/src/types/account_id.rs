@@ -0,0 +1,237 @@
+use alloc::string::{String, ToString};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use core::borrow::Borrow;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Deref;
+use core::str::FromStr;
+use serde::Serialize;
+
+use crate::env::is_valid_account_id;
+
+/// Length of the implicit account ID, the hex encoding of the 32-byte ed25519 public key.
+const IMPLICIT_ACCOUNT_ID_LEN: usize = 64;
+
+/// Account identifier, validated on construction and deserialization, see `is_valid_account_id`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, BorshSerialize, Serialize)]
+#[serde(transparent)]
+pub struct AccountId(String);
+
+/// Error returned when the string is not a valid account ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAccountIdError(String);
+
+impl fmt::Display for ParseAccountIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The account ID {:?} is invalid.", self.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl std::error::Error for ParseAccountIdError {}
+
+impl AccountId {
+    /// Wraps the string that is known to be a valid account ID.
+    pub(crate) fn new_unchecked(account_id: String) -> Self {
+        Self(account_id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the account is a top-level one, like `near` or a 64-character implicit account.
+    pub fn is_top_level(&self) -> bool {
+        !self.0.contains('.')
+    }
+
+    /// Whether the account is an implicit one, named by the hex of its ed25519 public key.
+    pub fn is_implicit(&self) -> bool {
+        self.0.len() == IMPLICIT_ACCOUNT_ID_LEN
+            && self.0.bytes().all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+    }
+
+    /// Whether the account is a direct sub-account of `parent`, like `app.alice.near` of
+    /// `alice.near`.
+    pub fn is_sub_account_of(&self, parent: &AccountId) -> bool {
+        self.parent().as_ref() == Some(parent)
+    }
+
+    /// The account that this one is a direct sub-account of, `None` for the top-level accounts.
+    pub fn parent(&self) -> Option<AccountId> {
+        let (_, parent) = self.0.split_once('.')?;
+        Some(Self(parent.to_string()))
+    }
+}
+
+impl TryFrom<String> for AccountId {
+    type Error = ParseAccountIdError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if is_valid_account_id(value.as_bytes()) {
+            Ok(Self(value))
+        } else {
+            Err(ParseAccountIdError(value))
+        }
+    }
+}
+
+impl TryFrom<&str> for AccountId {
+    type Error = ParseAccountIdError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_string())
+    }
+}
+
+impl FromStr for AccountId {
+    type Err = ParseAccountIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl From<AccountId> for String {
+    fn from(value: AccountId) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Deref for AccountId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for AccountId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for AccountId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for AccountId {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for AccountId {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for AccountId {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for AccountId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as serde::Deserializer<'de>>::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Self::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl BorshDeserialize for AccountId {
+    fn deserialize(buf: &mut &[u8]) -> borsh::maybestd::io::Result<Self> {
+        let s = String::deserialize(buf)?;
+        Self::try_from(s).map_err(|err| {
+            borsh::maybestd::io::Error::new(
+                borsh::maybestd::io::ErrorKind::InvalidData,
+                err.to_string(),
+            )
+        })
+    }
+}
+
+impl BorshSchema for AccountId {
+    fn add_definitions_recursively(
+        definitions: &mut crate::maybestd::collections::HashMap<
+            borsh::schema::Declaration,
+            borsh::schema::Definition,
+        >,
+    ) {
+        String::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> borsh::schema::Declaration {
+        String::declaration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    fn account(account_id: &str) -> AccountId {
+        AccountId::try_from(account_id).unwrap()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(account("alice.near").as_str(), "alice.near");
+        for invalid in ["Alice.near", "a", "alice..near", "alice.near.", "-alice.near", ""] {
+            assert!(AccountId::try_from(invalid).is_err(), "{}", invalid);
+        }
+        assert!("bob.near".parse::<AccountId>().is_ok());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let alice = account("alice.near");
+        assert_eq!(serde_json::to_string(&alice).unwrap(), "\"alice.near\"");
+        assert_eq!(serde_json::from_str::<AccountId>("\"alice.near\"").unwrap(), alice);
+        assert!(serde_json::from_str::<AccountId>("\"Alice.near\"").is_err());
+
+        let bytes = alice.try_to_vec().unwrap();
+        assert_eq!(bytes, "alice.near".to_string().try_to_vec().unwrap());
+        assert_eq!(AccountId::try_from_slice(&bytes).unwrap(), alice);
+        let invalid: Vec<u8> = "alice..near".to_string().try_to_vec().unwrap();
+        assert!(AccountId::try_from_slice(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_helpers() {
+        let near = account("near");
+        let alice = account("alice.near");
+        let app = account("app.alice.near");
+        assert!(near.is_top_level());
+        assert!(!alice.is_top_level());
+
+        assert_eq!(near.parent(), None);
+        assert_eq!(alice.parent(), Some(near.clone()));
+        assert_eq!(app.parent(), Some(alice.clone()));
+        assert!(alice.is_sub_account_of(&near));
+        assert!(app.is_sub_account_of(&alice));
+        assert!(!app.is_sub_account_of(&near));
+        assert!(!near.is_sub_account_of(&near));
+
+        let implicit = account(&"0123456789abcdef".repeat(4));
+        assert!(implicit.is_implicit());
+        assert!(implicit.is_top_level());
+        assert!(!alice.is_implicit());
+        assert!(!account(&"0123456789abcdeg".repeat(4)).is_implicit());
+        assert!(!account(&"0123456789abcdef".repeat(3)).is_implicit());
+    }
+}
@@ -0,0 +1,194 @@
+use alloc::string::ToString;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use core::fmt;
+use core::iter::Sum;
+use core::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+use serde::{Deserialize, Serialize};
+
+/// Amount of gas. The arithmetic operators panic on overflow, the `checked_*` and `saturating_*`
+/// methods do not.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
+)]
+#[serde(transparent)]
+pub struct Gas(pub u64);
+
+impl Gas {
+    pub const ZERO: Gas = Gas(0);
+    /// One Ggas, `10^9` gas.
+    pub const ONE_GIGA: Gas = Gas(1_000_000_000);
+    /// One Tgas, `10^12` gas, roughly a millisecond of the computation.
+    pub const ONE_TERA: Gas = Gas(1_000_000_000_000);
+
+    pub const fn from_gas(gas: u64) -> Self {
+        Self(gas)
+    }
+
+    pub const fn from_ggas(ggas: u64) -> Self {
+        Self(ggas * Self::ONE_GIGA.0)
+    }
+
+    pub const fn from_tgas(tgas: u64) -> Self {
+        Self(tgas * Self::ONE_TERA.0)
+    }
+
+    pub const fn as_gas(self) -> u64 {
+        self.0
+    }
+
+    /// The amount in whole Tgas, rounded down.
+    pub const fn as_tgas(self) -> u64 {
+        self.0 / Self::ONE_TERA.0
+    }
+
+    pub fn checked_add(self, rhs: Gas) -> Option<Gas> {
+        self.0.checked_add(rhs.0).map(Gas)
+    }
+
+    pub fn checked_sub(self, rhs: Gas) -> Option<Gas> {
+        self.0.checked_sub(rhs.0).map(Gas)
+    }
+
+    pub fn checked_mul(self, rhs: u64) -> Option<Gas> {
+        self.0.checked_mul(rhs).map(Gas)
+    }
+
+    pub fn checked_div(self, rhs: u64) -> Option<Gas> {
+        self.0.checked_div(rhs).map(Gas)
+    }
+
+    pub fn saturating_add(self, rhs: Gas) -> Gas {
+        Gas(self.0.saturating_add(rhs.0))
+    }
+
+    pub fn saturating_sub(self, rhs: Gas) -> Gas {
+        Gas(self.0.saturating_sub(rhs.0))
+    }
+
+    pub fn saturating_mul(self, rhs: u64) -> Gas {
+        Gas(self.0.saturating_mul(rhs))
+    }
+}
+
+impl From<u64> for Gas {
+    fn from(gas: u64) -> Self {
+        Self(gas)
+    }
+}
+
+impl From<Gas> for u64 {
+    fn from(gas: Gas) -> Self {
+        gas.0
+    }
+}
+
+impl fmt::Display for Gas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} gas", self.0)
+    }
+}
+
+impl Add for Gas {
+    type Output = Gas;
+
+    fn add(self, rhs: Gas) -> Gas {
+        self.checked_add(rhs).expect("Gas overflow")
+    }
+}
+
+impl AddAssign for Gas {
+    fn add_assign(&mut self, rhs: Gas) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Gas {
+    type Output = Gas;
+
+    fn sub(self, rhs: Gas) -> Gas {
+        self.checked_sub(rhs).expect("Gas underflow")
+    }
+}
+
+impl SubAssign for Gas {
+    fn sub_assign(&mut self, rhs: Gas) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<u64> for Gas {
+    type Output = Gas;
+
+    fn mul(self, rhs: u64) -> Gas {
+        self.checked_mul(rhs).expect("Gas overflow")
+    }
+}
+
+impl Div<u64> for Gas {
+    type Output = Gas;
+
+    fn div(self, rhs: u64) -> Gas {
+        Gas(self.0 / rhs)
+    }
+}
+
+impl Sum for Gas {
+    fn sum<I: Iterator<Item = Gas>>(iter: I) -> Gas {
+        iter.fold(Gas::ZERO, Add::add)
+    }
+}
+
+/// Share of the unused gas that a function call gets on top of its static gas, relative to the
+/// other weighted calls, see `Promise::function_call_weight`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GasWeight(pub u64);
+
+impl Default for GasWeight {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units_and_arithmetic() {
+        assert_eq!(Gas::from_tgas(300), Gas(300_000_000_000_000));
+        assert_eq!(Gas::from_ggas(5).as_gas(), 5_000_000_000);
+        assert_eq!(Gas(2_999_999_999_999).as_tgas(), 2);
+        assert_eq!(Gas::from_tgas(3) + Gas::ONE_TERA, Gas::from_tgas(4));
+        assert_eq!(Gas::from_tgas(3) - Gas::ONE_TERA, Gas::from_tgas(2));
+        assert_eq!(Gas::ONE_TERA * 3 / 2, Gas(1_500_000_000_000));
+        assert_eq!(Gas::ONE_TERA.checked_sub(Gas::from_tgas(2)), None);
+        assert_eq!(Gas::ONE_TERA.saturating_sub(Gas::from_tgas(2)), Gas::ZERO);
+        assert_eq!(Gas(u64::MAX).saturating_mul(2), Gas(u64::MAX));
+        assert_eq!([Gas(1), Gas(2), Gas(3)].iter().copied().sum::<Gas>(), Gas(6));
+
+        let mut gas = Gas::ONE_GIGA;
+        gas += Gas::ONE_GIGA;
+        gas -= Gas(1);
+        assert_eq!(gas, Gas(1_999_999_999));
+        assert_eq!(serde_json::to_string(&gas).unwrap(), "1999999999");
+    }
+
+    #[test]
+    #[should_panic(expected = "Gas underflow")]
+    fn test_underflow() {
+        let _ = Gas::ZERO - Gas(1);
+    }
+}
@@ -1,20 +1,21 @@
-use serde::{Serialize, Deserialize};
 use alloc::{string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
 
+mod account_id;
+pub use account_id::{AccountId, ParseAccountIdError};
+mod gas;
+pub use gas::{Gas, GasWeight};
 
-pub type AccountId = String;
 pub type PublicKey = Vec<u8>;
 pub type BlockHeight = u64;
 pub type EpochHeight = u64;
 pub type Balance = u128;
-pub type Gas = u64;
 pub type PromiseIndex = u64;
 pub type ReceiptIndex = u64;
 pub type IteratorIndex = u64;
 pub type StorageUsage = u64;
 pub type ProtocolVersion = u32;
 
-
 pub mod bytes_as_str {
     use super::*;
 
@@ -52,7 +53,7 @@ pub enum ReturnData {
 
 /// When there is a callback attached to one or more contract calls the execution results of these
 /// calls are available to the contract invoked through the callback.
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub enum PromiseResult {
     /// Current version of the protocol never returns `PromiseResult::NotReady`.
     NotReady,
@@ -61,3 +62,24 @@ pub enum PromiseResult {
     Failed,
 }
 
+/// Point of the alt_bn128 G1 group in the affine coordinates, which are little-endian elements of
+/// the base field. The point at infinity has both coordinates zero.
+#[cfg(feature = "alt-bn128")]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct G1Point {
+    pub x: [u8; 32],
+    pub y: [u8; 32],
+}
+
+/// Point of the alt_bn128 G2 group in the affine coordinates, which are elements `c0 + c1 * i` of
+/// the quadratic extension given as `[c0, c1]`. The point at infinity has both coordinates zero.
+#[cfg(feature = "alt-bn128")]
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct G2Point {
+    pub x: [[u8; 32]; 2],
+    pub y: [[u8; 32]; 2],
+}
+
+/// Element of the alt_bn128 scalar field as a little-endian integer.
+#[cfg(feature = "alt-bn128")]
+pub type Scalar = [u8; 32];
@@ -0,0 +1,319 @@
+//! Upgrading the contract by itself: the `update_contract` method deploys the new code that it
+//! gets as the raw input to the current account, calls the migration of the new code with the
+//! unused gas and checks the result in the `on_update_contract` callback.
+//!
+//! ```
+//! # use near_sdk_pure::borsh::{self, BorshDeserialize, BorshSerialize};
+//! # use near_sdk_pure::upgrade::Upgradable;
+//! # use near_sdk_pure::{env, near_bindgen, upgradable, AccountId};
+//! #[near_bindgen]
+//! #[derive(BorshDeserialize, BorshSerialize)]
+//! pub struct Contract {
+//!     owner_id: AccountId,
+//! }
+//!
+//! impl Upgradable for Contract {
+//!     fn assert_can_upgrade(&self) {
+//!         if env::predecessor_account_id() != self.owner_id {
+//!             env::panic(b"Only the owner can upgrade the contract");
+//!         }
+//!     }
+//! }
+//!
+//! upgradable!(Contract);
+//! ```
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::types::{Gas, GasWeight, PromiseResult};
+use crate::{env, ContractState, Promise};
+
+/// The method of the new code that `update_contract` calls after the deployment by default, the
+/// one generated by `#[near_bindgen(version = N)]`. A contract that renames it with
+/// `migrate_method = "name"` sets the name in `UpgradeConfig::migrate_method`.
+pub const DEFAULT_MIGRATE_METHOD: &str = "migrate";
+
+/// The callback that checks the result of the migration, exported by `upgradable!`.
+pub const ON_UPDATE_CONTRACT_METHOD: &str = "on_update_contract";
+
+/// Gas of the `on_update_contract` callback by default.
+pub const GAS_FOR_ON_UPDATE_CONTRACT: Gas = Gas::from_tgas(5);
+
+/// How the deployed code is migrated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpgradeConfig {
+    /// The method of the new code called right after the deployment, in the same receipt.
+    pub migrate_method: String,
+    /// The raw arguments of the migrate method.
+    pub migrate_args: Vec<u8>,
+    /// The gas of the migrate method on top of its share of the unused gas.
+    pub migrate_gas: Gas,
+    /// The gas of the `on_update_contract` callback.
+    pub callback_gas: Gas,
+}
+
+impl Default for UpgradeConfig {
+    fn default() -> Self {
+        Self {
+            migrate_method: DEFAULT_MIGRATE_METHOD.to_string(),
+            migrate_args: Vec::new(),
+            migrate_gas: Gas::ZERO,
+            callback_gas: GAS_FOR_ON_UPDATE_CONTRACT,
+        }
+    }
+}
+
+/// Contract that can be upgraded with the `update_contract` method exported by `upgradable!`.
+pub trait Upgradable: ContractState {
+    /// Panics unless the predecessor is allowed to upgrade the contract, usually unless it is the
+    /// owner.
+    fn assert_can_upgrade(&self);
+
+    /// How the deployed code is migrated, `UpgradeConfig::default()` by default.
+    fn upgrade_config(&self) -> UpgradeConfig {
+        UpgradeConfig::default()
+    }
+}
+
+/// Deploys the code to the current account and calls the migrate method of the new code with the
+/// unused gas, then the `on_update_contract` callback. The deployment is reverted if the migration
+/// fails, since both are actions of the same receipt.
+pub fn deploy_and_migrate(code: Vec<u8>, config: &UpgradeConfig) -> Promise {
+    let account_id = env::current_account_id();
+    Promise::new(account_id.clone())
+        .deploy_contract(code)
+        .function_call_weight(
+            config.migrate_method.clone().into_bytes(),
+            config.migrate_args.clone(),
+            0,
+            config.migrate_gas,
+            GasWeight::default(),
+        )
+        .then(Promise::new(account_id).function_call(
+            ON_UPDATE_CONTRACT_METHOD.as_bytes().to_vec(),
+            Vec::new(),
+            0,
+            config.callback_gas,
+        ))
+}
+
+/// The `update_contract` method exported by `upgradable!`. Reads the new code from the input as
+/// is, without the JSON arguments, checks that the predecessor can upgrade the contract and
+/// returns the result of `deploy_and_migrate`.
+///
+/// # Panics
+///
+/// Panics if a deposit is attached, the contract is not initialized, the input is empty or the
+/// predecessor can not upgrade the contract.
+pub fn update_contract<T: Upgradable>() {
+    env::reset_context_cache();
+    if env::attached_deposit() != 0 {
+        env::panic(b"Method update_contract doesn't accept deposit");
+    }
+    let contract =
+        T::state_read().unwrap_or_else(|| env::panic(b"The contract state is not initialized."));
+    contract.assert_can_upgrade();
+    let code = match env::input() {
+        Some(code) if !code.is_empty() => code,
+        _ => env::panic(b"Expected the contract code in the input."),
+    };
+    deploy_and_migrate(code, &contract.upgrade_config()).as_return();
+}
+
+/// The `on_update_contract` callback exported by `upgradable!`, which fails if the deployment or
+/// the migration failed.
+pub fn on_update_contract() {
+    env::reset_context_cache();
+    if env::current_account_id() != env::predecessor_account_id() {
+        env::panic(b"Method on_update_contract is private");
+    }
+    if env::promise_results_count() != 1 {
+        env::panic(b"Expected the result of the contract update.");
+    }
+    match env::promise_result(0) {
+        PromiseResult::Successful(_) => {}
+        _ => env::panic(b"The contract update failed."),
+    }
+}
+
+/// Transaction calling `update_contract` on the given account with the code, for the `Simulator`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn update_contract_tx(
+    account_id: &crate::AccountId,
+    code: Vec<u8>,
+) -> crate::PendingContractTx {
+    crate::PendingContractTx {
+        receiver_id: account_id.clone(),
+        method: "update_contract".to_string(),
+        args: code,
+        is_view: false,
+    }
+}
+
+/// Exports the `update_contract` method and the `on_update_contract` callback of the contract,
+/// which implements `Upgradable`. The new code has to export `on_update_contract` too, since the
+/// callback runs after the deployment.
+#[macro_export]
+macro_rules! upgradable {
+    ($contract:ty) => {
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn update_contract() {
+            $crate::upgrade::update_contract::<$contract>();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn update_contract() {
+            $crate::upgrade::update_contract::<$contract>();
+        }
+        #[cfg(target_arch = "wasm32")]
+        #[no_mangle]
+        pub extern "C" fn on_update_contract() {
+            $crate::upgrade::on_update_contract();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        pub fn on_update_contract() {
+            $crate::upgrade::on_update_contract();
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        alice, bob, carol, testing_env_with_promise_results, with_mocked_blockchain,
+        VMContextBuilder,
+    };
+    use crate::{testing_env, AccountId, PromiseAction, PromiseDescription};
+    use alloc::vec;
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct Contract {
+        owner_id: AccountId,
+    }
+
+    impl ContractState for Contract {}
+
+    impl Upgradable for Contract {
+        fn assert_can_upgrade(&self) {
+            if env::predecessor_account_id() != self.owner_id {
+                env::panic(b"Only the owner can upgrade the contract");
+            }
+        }
+    }
+
+    fn setup(predecessor: AccountId, code: &[u8]) {
+        testing_env!(VMContextBuilder::new()
+            .predecessor_account_id(predecessor)
+            .input(code.to_vec())
+            .build());
+        env::state_write(&Contract { owner_id: bob() });
+    }
+
+    #[test]
+    fn test_deploy_and_migrate() {
+        testing_env!(VMContextBuilder::new().build());
+        let config = UpgradeConfig {
+            migrate_method: "migrate_v2".to_string(),
+            migrate_args: b"{}".to_vec(),
+            migrate_gas: Gas::from_tgas(10),
+            ..Default::default()
+        };
+        let promise = deploy_and_migrate(b"code".to_vec(), &config);
+        assert_eq!(
+            promise.describe(),
+            PromiseDescription::new(
+                alice(),
+                vec![
+                    PromiseAction::DeployContract { code: b"code".to_vec() },
+                    PromiseAction::FunctionCallWeight {
+                        method_name: b"migrate_v2".to_vec(),
+                        arguments: b"{}".to_vec(),
+                        amount: 0,
+                        gas: Gas::from_tgas(10),
+                        weight: GasWeight(1),
+                    }
+                ]
+            )
+            .then(PromiseDescription::new(
+                alice(),
+                vec![PromiseAction::FunctionCall {
+                    method_name: b"on_update_contract".to_vec(),
+                    arguments: vec![],
+                    amount: 0,
+                    gas: GAS_FOR_ON_UPDATE_CONTRACT,
+                }]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_update_contract() {
+        setup(bob(), b"code");
+        update_contract::<Contract>();
+        with_mocked_blockchain(|blockchain| {
+            let promises = blockchain.scheduled_promises();
+            assert_eq!(promises.len(), 1);
+            // The callback, after the deployment with the migration that got the unused gas.
+            let deployment = match &promises[0] {
+                PromiseDescription::Single { receiver_id, after: Some(after), .. } => {
+                    assert_eq!(receiver_id, &alice());
+                    after
+                }
+                _ => panic!("Expected the callback"),
+            };
+            match deployment.as_ref() {
+                PromiseDescription::Single { receiver_id, actions, .. } => {
+                    assert_eq!(receiver_id, &alice());
+                    assert_eq!(
+                        actions[0],
+                        PromiseAction::DeployContract { code: b"code".to_vec() }
+                    );
+                    match &actions[1] {
+                        #[cfg(not(feature = "function-call-weight"))]
+                        PromiseAction::FunctionCall { method_name, gas, .. } => {
+                            assert_eq!(method_name, b"migrate");
+                            assert!(*gas > Gas::from_tgas(200));
+                        }
+                        #[cfg(feature = "function-call-weight")]
+                        PromiseAction::FunctionCallWeight { method_name, .. } => {
+                            assert_eq!(method_name, b"migrate");
+                        }
+                        action => panic!("Unexpected action {:?}", action),
+                    }
+                }
+                _ => panic!("Expected the deployment"),
+            }
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can upgrade the contract")]
+    fn test_update_contract_not_owner() {
+        setup(carol(), b"code");
+        update_contract::<Contract>();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected the contract code in the input.")]
+    fn test_update_contract_without_code() {
+        setup(bob(), b"");
+        update_contract::<Contract>();
+    }
+
+    #[test]
+    fn test_on_update_contract() {
+        let context = VMContextBuilder::new().predecessor_account_id(alice()).build();
+        testing_env_with_promise_results(context, vec![PromiseResult::Successful(vec![])]);
+        on_update_contract();
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract update failed.")]
+    fn test_on_update_contract_failed() {
+        let context = VMContextBuilder::new().predecessor_account_id(alice()).build();
+        testing_env_with_promise_results(context, vec![PromiseResult::Failed]);
+        on_update_contract();
+    }
+}
@@ -4,13 +4,95 @@ use alloc::{string::{String, ToString}, vec::Vec};
 #[macro_export]
 macro_rules! log {
     ($arg:tt) => {
-        crate::env::log($arg.as_bytes())
+        $crate::env::log($arg.as_bytes())
     };
     ($($arg:tt)*) => {
-        crate::env::log(alloc::format!($($arg)*).as_bytes())
+        $crate::env::log($crate::maybestd::format!($($arg)*).as_bytes())
     };
 }
 
+/// Declares the global allocator of the contract on `wasm32`: `wee_alloc` by default, or one of
+/// the allocators of the `allocator` module with `setup_alloc!(bump)` and
+/// `setup_alloc!(free_list)`, which need the `bump-allocator` and `free-list-allocator` features.
+#[macro_export]
+macro_rules! setup_alloc {
+    () => {
+        $crate::setup_alloc!(wee_alloc);
+    };
+    (wee_alloc) => {
+        #[cfg(target_arch = "wasm32")]
+        #[global_allocator]
+        static __NEAR_SDK_ALLOC: $crate::wee_alloc::WeeAlloc = $crate::wee_alloc::WeeAlloc::INIT;
+    };
+    (bump) => {
+        #[cfg(target_arch = "wasm32")]
+        #[global_allocator]
+        static __NEAR_SDK_ALLOC: $crate::allocator::BumpAllocator =
+            $crate::allocator::BumpAllocator::new($crate::allocator::WasmMemory);
+    };
+    (free_list) => {
+        #[cfg(target_arch = "wasm32")]
+        #[global_allocator]
+        static __NEAR_SDK_ALLOC: $crate::allocator::FreeListAllocator =
+            $crate::allocator::FreeListAllocator::new($crate::allocator::WasmMemory);
+    };
+}
+
+/// Declares the global allocator and the panic handler that a `#![no_std]` contract needs on
+/// `wasm32`. By default the panic handler aborts the call with the message and the location of the
+/// panic through `env::panic`; `setup_no_std!(abort)` aborts it without a message instead, which
+/// leaves the formatting code out of the contract. The allocator is chosen as in `setup_alloc!`,
+/// for example `setup_no_std!(abort, bump)`.
+///
+/// No `#[alloc_error_handler]` is declared, since the attribute is unstable and needs a feature
+/// gate at the root of the contract crate. The default handler of `alloc` turns the allocation
+/// failures into panics, `memory allocation of N bytes failed`, which the panic handler reports.
+///
+/// The contract has to use the version 2 of the feature resolver of cargo, `resolver = "2"` in
+/// its manifest. With the resolver of edition 2018 the proc-macro dependencies of `near-sdk-pure`
+/// enable the `std` feature of `serde` for the contract too, and `std` declares its own panic
+/// handler.
+#[macro_export]
+macro_rules! setup_no_std {
+    () => {
+        $crate::setup_no_std!(message);
+    };
+    ($panic:ident) => {
+        $crate::setup_no_std!($panic, wee_alloc);
+    };
+    (message, $allocator:ident) => {
+        $crate::setup_alloc!($allocator);
+
+        #[cfg(target_arch = "wasm32")]
+        #[panic_handler]
+        fn __near_sdk_panic_handler(info: &core::panic::PanicInfo) -> ! {
+            $crate::utils::panic_with_info(info)
+        }
+    };
+    (abort, $allocator:ident) => {
+        $crate::setup_alloc!($allocator);
+
+        #[cfg(target_arch = "wasm32")]
+        #[panic_handler]
+        fn __near_sdk_panic_handler(_info: &core::panic::PanicInfo) -> ! {
+            unsafe { $crate::sys::panic() };
+            core::arch::wasm32::unreachable()
+        }
+    };
+}
+
+/// Aborts the call with the message and the location of the panic, see `setup_no_std!`.
+#[doc(hidden)]
+pub fn panic_with_info(info: &core::panic::PanicInfo) -> ! {
+    crate::env::panic(panic_message(info).as_bytes())
+}
+
+/// The message of `panic_with_info`, formatted as `std` prints the panics:
+/// `panicked at <file>:<line>:<column>:` and the message on the next line.
+fn panic_message(info: &dyn core::fmt::Display) -> String {
+    alloc::format!("{}", info)
+}
+
 #[derive(Debug)]
 pub struct PendingContractTx {
     pub receiver_id: AccountId,
@@ -20,9 +102,14 @@ pub struct PendingContractTx {
 }
 
 impl PendingContractTx {
-    pub fn new(receiver_id: &str, method: &str, args: serde_json::Value, is_view: bool) -> Self {
+    pub fn new(
+        receiver_id: &AccountId,
+        method: &str,
+        args: serde_json::Value,
+        is_view: bool,
+    ) -> Self {
         Self {
-            receiver_id: receiver_id.to_string(),
+            receiver_id: receiver_id.clone(),
             method: method.to_string(),
             args: args.to_string().into_bytes(),
             is_view,
@@ -30,3 +117,31 @@ impl PendingContractTx {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::boxed::Box;
+    use core::cell::RefCell;
+    use std::panic::catch_unwind;
+
+    std::thread_local! {
+        static MESSAGE: RefCell<Option<String>> = const { RefCell::new(None) };
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(|info| {
+            MESSAGE.with(|message| *message.borrow_mut() = Some(panic_message(info)))
+        }));
+        let line = line!() + 1;
+        let result = catch_unwind(|| panic!("Failed with {} bytes", 5));
+        std::panic::set_hook(hook);
+        assert!(result.is_err());
+
+        let message = MESSAGE.with(|message| message.take()).unwrap();
+        let location = alloc::format!("panicked at {}:{}:", file!(), line);
+        assert!(message.starts_with(&location), "{}", message);
+        assert!(message.ends_with(":\nFailed with 5 bytes"), "{}", message);
+    }
+}
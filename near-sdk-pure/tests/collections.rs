@@ -0,0 +1,84 @@
+//! Model tests of the collections. Random sequences of operations are applied both to a collection
+//! stored on the mocked trie and to its `std::collections` equivalent, and the results of every
+//! operation and the contents after it have to match.
+
+mod model;
+
+use model::{Collection, Op};
+use near_sdk_pure::collections::{
+    LegacyTreeMap, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector,
+};
+use near_sdk_pure::test_utils::{with_mocked_blockchain, RuntimeFeesConfig, VMContextBuilder};
+use near_sdk_pure::{env, testing_env};
+use quickcheck::{QuickCheck, StdGen};
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+/// Prefix of the collection under test.
+const ID: &[u8] = b"c";
+
+/// Keys of the trie that belong to the collection under test.
+fn collection_keys() -> Vec<Vec<u8>> {
+    with_mocked_blockchain(|blockchain| {
+        blockchain.storage().into_keys().filter(|key| key.starts_with(ID)).collect()
+    })
+}
+
+/// Applies the operations to a new collection on an empty trie and to the model, comparing them
+/// after every operation. Clearing the collection must remove all its keys from the trie.
+fn run<C: Collection>(ops: Vec<Op>) -> bool {
+    env::take_blockchain_interface();
+    testing_env!(VMContextBuilder::new().build(), Vec::new(), RuntimeFeesConfig::free());
+    let mut collection = C::new(ID.to_vec());
+    let mut model = C::Model::default();
+    for op in ops.iter().chain(std::iter::once(&Op::Clear)) {
+        let outcome = collection.apply(op);
+        assert_eq!(outcome, C::apply_model(&mut model, op), "{:?} on {:?}", op, model);
+        collection.check(&model);
+        if let Op::Clear = op {
+            assert_eq!(collection_keys(), Vec::<Vec<u8>>::new(), "Orphaned keys after clear");
+        }
+    }
+    true
+}
+
+fn check<C: Collection>() {
+    // The seed is fixed to make the failures reproducible.
+    let gen = StdGen::new(XorShiftRng::seed_from_u64(42), 100);
+    QuickCheck::with_gen(gen).tests(100).quickcheck(run::<C> as fn(Vec<Op>) -> bool);
+}
+
+#[test]
+fn vector() {
+    check::<Vector<u8>>();
+}
+
+#[test]
+fn lookup_map() {
+    check::<LookupMap<u8, u8>>();
+}
+
+#[test]
+fn lookup_set() {
+    check::<LookupSet<u8>>();
+}
+
+#[test]
+fn unordered_map() {
+    check::<UnorderedMap<u8, u8>>();
+}
+
+#[test]
+fn unordered_set() {
+    check::<UnorderedSet<u8>>();
+}
+
+#[test]
+fn tree_map() {
+    check::<TreeMap<u8, u8>>();
+}
+
+#[test]
+fn legacy_tree_map() {
+    check::<LegacyTreeMap<u8, u8>>();
+}
@@ -0,0 +1,131 @@
+//! Aborts transactions on the collections at random host calls and checks that every collection
+//! stays consistent with a model of the transactions that were committed.
+
+mod model;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use model::{Collection, Op};
+use near_sdk_pure::collections::{
+    LegacyTreeMap, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector,
+};
+use near_sdk_pure::test_utils::{inject_fault, Fault, VMContextBuilder, INJECTED_FAULT};
+use near_sdk_pure::{env, testing_env};
+use quickcheck::{Arbitrary, Gen, QuickCheck};
+use rand::Rng;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Once;
+
+/// Prefix of the collection under test.
+const ID: &[u8] = b"c";
+
+/// A transaction with an optional fault injected at the given host call.
+#[derive(Clone, Debug)]
+struct Tx {
+    op: Op,
+    fault: Option<(u64, Fault)>,
+}
+
+impl Arbitrary for Tx {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let fault = if g.gen_bool(0.7) {
+            let fault = if g.gen() { Fault::Before } else { Fault::After };
+            Some((g.gen_range(1, 150), fault))
+        } else {
+            None
+        };
+        Tx { op: Op::arbitrary(g), fault }
+    }
+}
+
+/// A collection that is stored as the contract state.
+trait Stored: Collection + BorshSerialize + BorshDeserialize {}
+
+impl<C: Collection + BorshSerialize + BorshDeserialize> Stored for C {}
+
+/// Executes the transaction as a contract call that loads the collection from the state, applies
+/// the operation and saves it back. Returns `true` if the call was not aborted.
+fn execute<C: Stored>(tx: &Tx) -> bool {
+    testing_env!(VMContextBuilder::new().build());
+    if let Some((call, fault)) = tx.fault {
+        inject_fault(call, fault);
+    }
+    catch_unwind(AssertUnwindSafe(|| {
+        let mut collection: C = env::state_read().unwrap_or_else(|| C::new(ID.to_vec()));
+        collection.apply(&tx.op);
+        env::state_write(&collection);
+    }))
+    .is_ok()
+}
+
+/// Installs the panic hook, once for all the tests running in parallel, that does not print the
+/// messages of the injected faults, which are expected. Other panics are printed as usual.
+fn silence_injected_faults() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let message = match info.payload().downcast_ref::<String>() {
+                Some(message) => message.as_str(),
+                None => info.payload().downcast_ref::<&str>().copied().unwrap_or_default(),
+            };
+            if !message.starts_with(INJECTED_FAULT) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+fn crash_consistency<C: Stored>(txs: Vec<Tx>) -> bool {
+    // Start from the empty storage.
+    env::take_blockchain_interface();
+    let mut model = C::Model::default();
+    for tx in &txs {
+        if execute::<C>(tx) {
+            C::apply_model(&mut model, &tx.op);
+        }
+        testing_env!(VMContextBuilder::new().build());
+        let collection: C = env::state_read().unwrap_or_else(|| C::new(ID.to_vec()));
+        collection.check(&model);
+    }
+    true
+}
+
+fn check<C: Stored>() {
+    silence_injected_faults();
+    QuickCheck::new().tests(50).quickcheck(crash_consistency::<C> as fn(Vec<Tx>) -> bool);
+}
+
+#[test]
+fn vector() {
+    check::<Vector<u8>>();
+}
+
+#[test]
+fn lookup_map() {
+    check::<LookupMap<u8, u8>>();
+}
+
+#[test]
+fn lookup_set() {
+    check::<LookupSet<u8>>();
+}
+
+#[test]
+fn unordered_map() {
+    check::<UnorderedMap<u8, u8>>();
+}
+
+#[test]
+fn unordered_set() {
+    check::<UnorderedSet<u8>>();
+}
+
+#[test]
+fn tree_map() {
+    check::<TreeMap<u8, u8>>();
+}
+
+#[test]
+fn legacy_tree_map() {
+    check::<LegacyTreeMap<u8, u8>>();
+}
@@ -0,0 +1,66 @@
+//! Emits the events declared with `#[near_event]` and parses them back from the logs.
+
+use near_sdk_pure::serde::{Deserialize, Serialize};
+use near_sdk_pure::test_utils::{
+    assert_event_emitted, get_logs, get_near_events, VMContextBuilder,
+};
+use near_sdk_pure::{near_event, testing_env, AccountId, NearEvent};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk_pure::serde")]
+pub struct NftMintData {
+    owner_id: AccountId,
+    token_ids: Vec<String>,
+}
+
+#[near_event(standard = "nep171", version = "1.0.0")]
+#[derive(Debug, PartialEq)]
+pub enum NftEvent {
+    NftMint(Vec<NftMintData>),
+    NftBurn { token_id: String },
+    ContractPaused,
+}
+
+#[near_event(standard = "nep141", version = "1.0.0")]
+#[derive(Debug, PartialEq)]
+pub enum FtEvent {
+    FtMint { amount: String },
+}
+
+#[test]
+fn test_emit_and_parse() {
+    testing_env!(VMContextBuilder::new().build());
+    let mint = NftEvent::NftMint(vec![NftMintData {
+        owner_id: "alice".parse().unwrap(),
+        token_ids: vec!["1".to_string(), "2".to_string()],
+    }]);
+    mint.emit();
+    NftEvent::NftBurn { token_id: "1".to_string() }.emit();
+    NftEvent::ContractPaused.emit();
+    FtEvent::FtMint { amount: "10".to_string() }.emit();
+
+    assert_eq!(
+        get_logs()[0],
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice","token_ids":["1","2"]}]}"#
+    );
+    assert_event_emitted(json!({
+        "standard": "nep171",
+        "version": "1.0.0",
+        "event": "nft_burn",
+        "data": {"token_id": "1"}
+    }));
+    assert_eq!(
+        get_near_events::<NftEvent>(),
+        vec![mint, NftEvent::NftBurn { token_id: "1".to_string() }, NftEvent::ContractPaused]
+    );
+    assert_eq!(get_near_events::<FtEvent>(), vec![FtEvent::FtMint { amount: "10".to_string() }]);
+}
+
+#[test]
+fn test_other_versions_are_skipped() {
+    let event = json!({"standard": "nep171", "version": "2.0.0", "event": "contract_paused"});
+    assert_eq!(NftEvent::from_event_json(&event), None);
+    let event = json!({"standard": "nep171", "version": "1.0.0", "event": "contract_paused"});
+    assert_eq!(NftEvent::from_event_json(&event), Some(NftEvent::ContractPaused));
+}
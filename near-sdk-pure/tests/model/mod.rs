@@ -0,0 +1,456 @@
+//! Operations on the collections and their models, shared by the tests that apply random sequences
+//! of operations both to a collection stored on the mocked trie and to its `std::collections`
+//! equivalent.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::ops::Bound;
+
+use near_sdk_pure::collections::{
+    LegacyTreeMap, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet, Vector,
+};
+use quickcheck::{Arbitrary, Gen};
+use rand::Rng;
+
+/// Number of distinct keys, small enough for the operations to hit the existing keys.
+pub const KEYS: u8 = 16;
+
+#[derive(Clone, Debug)]
+pub enum Op {
+    Insert(u8, u8),
+    Remove(u8),
+    Get(u8),
+    Iter,
+    Range(Bound<u8>, Bound<u8>),
+    Clear,
+    Extend(Vec<(u8, u8)>),
+    SwapRemove(u8),
+    Pop,
+    Replace(u8, u8),
+}
+
+fn arbitrary_bound<G: Gen>(g: &mut G, key: u8) -> Bound<u8> {
+    match g.gen_range(0, 3) {
+        0 => Bound::Included(key),
+        1 => Bound::Excluded(key),
+        _ => Bound::Unbounded,
+    }
+}
+
+impl Arbitrary for Op {
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let key = g.gen_range(0, KEYS);
+        match g.gen_range(0, 20) {
+            0 => Op::Clear,
+            1 => Op::Iter,
+            2 => {
+                let mut extra = g.gen_range(0, 4);
+                let mut entries = Vec::new();
+                while extra > 0 {
+                    entries.push((g.gen_range(0, KEYS), g.gen()));
+                    extra -= 1;
+                }
+                Op::Extend(entries)
+            }
+            3 | 4 => {
+                let other = g.gen_range(0, KEYS);
+                let (lo, hi) = if key <= other { (key, other) } else { (other, key) };
+                match (arbitrary_bound(g, lo), arbitrary_bound(g, hi)) {
+                    // An empty range with both bounds excluded is invalid.
+                    (Bound::Excluded(_), Bound::Excluded(_)) if lo == hi => {
+                        Op::Range(Bound::Included(lo), Bound::Excluded(hi))
+                    }
+                    (lo, hi) => Op::Range(lo, hi),
+                }
+            }
+            5 | 6 => Op::SwapRemove(key),
+            7 => Op::Pop,
+            8 | 9 => Op::Replace(key, g.gen()),
+            10..=12 => Op::Get(key),
+            13..=15 => Op::Remove(key),
+            _ => Op::Insert(key, g.gen()),
+        }
+    }
+}
+
+/// Observable result of an operation.
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Unsupported,
+    Done,
+    Bool(bool),
+    Value(Option<u8>),
+    Values(Vec<u8>),
+    Entries(Vec<(u8, u8)>),
+}
+
+/// A persistent collection tested against a model.
+pub trait Collection {
+    type Model: Default + Debug;
+
+    fn new(id: Vec<u8>) -> Self;
+    fn apply(&mut self, op: &Op) -> Outcome;
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome;
+    /// Compares the whole contents of the collection with the model.
+    fn check(&self, model: &Self::Model);
+}
+
+/// Index of an existing element of a vector of the given length.
+fn index(index: u8, len: usize) -> Option<usize> {
+    if len == 0 {
+        None
+    } else {
+        Some(index as usize % len)
+    }
+}
+
+impl Collection for Vector<u8> {
+    type Model = Vec<u8>;
+
+    fn new(id: Vec<u8>) -> Self {
+        Vector::new(id)
+    }
+
+    fn apply(&mut self, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(_, value) => {
+                self.push(value);
+                Outcome::Done
+            }
+            Op::Get(index) => Outcome::Value(self.get(*index as u64)),
+            Op::Iter => Outcome::Values(self.iter().collect()),
+            Op::Clear => {
+                self.clear();
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                self.extend(entries.iter().map(|(_, value)| *value));
+                Outcome::Done
+            }
+            Op::SwapRemove(i) => {
+                Outcome::Value(index(*i, self.len() as usize).map(|i| self.swap_remove(i as u64)))
+            }
+            Op::Pop => Outcome::Value(self.pop()),
+            Op::Replace(i, value) => Outcome::Value(
+                index(*i, self.len() as usize).map(|i| self.replace(i as u64, value)),
+            ),
+            Op::Remove(_) | Op::Range(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(_, value) => {
+                model.push(*value);
+                Outcome::Done
+            }
+            Op::Get(index) => Outcome::Value(model.get(*index as usize).cloned()),
+            Op::Iter => Outcome::Values(model.clone()),
+            Op::Clear => {
+                model.clear();
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                model.extend(entries.iter().map(|(_, value)| *value));
+                Outcome::Done
+            }
+            Op::SwapRemove(i) => {
+                Outcome::Value(index(*i, model.len()).map(|i| model.swap_remove(i)))
+            }
+            Op::Pop => Outcome::Value(model.pop()),
+            Op::Replace(i, value) => Outcome::Value(
+                index(*i, model.len()).map(|i| std::mem::replace(&mut model[i], *value)),
+            ),
+            Op::Remove(_) | Op::Range(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn check(&self, model: &Self::Model) {
+        assert_eq!(self.len(), model.len() as u64);
+        assert_eq!(&self.to_vec(), model);
+        assert_eq!(self.get(self.len()), None);
+    }
+}
+
+/// Applies the operation to a map model. The operations that are specific to vectors are not
+/// supported.
+fn apply_map_model(model: &mut BTreeMap<u8, u8>, op: &Op) -> Outcome {
+    match op {
+        Op::Insert(key, value) => Outcome::Value(model.insert(*key, *value)),
+        Op::Remove(key) => Outcome::Value(model.remove(key)),
+        Op::Get(key) => Outcome::Value(model.get(key).cloned()),
+        Op::Iter => Outcome::Entries(model.clone().into_iter().collect()),
+        Op::Range(lo, hi) => {
+            Outcome::Entries(model.range((*lo, *hi)).map(|(k, v)| (*k, *v)).collect())
+        }
+        Op::Clear => {
+            model.clear();
+            Outcome::Done
+        }
+        Op::Extend(entries) => {
+            model.extend(entries.iter().cloned());
+            Outcome::Done
+        }
+        Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+    }
+}
+
+/// Applies the operation to a set model, the values of the operations are ignored.
+fn apply_set_model(model: &mut BTreeSet<u8>, op: &Op) -> Outcome {
+    match op {
+        Op::Insert(key, _) => Outcome::Bool(model.insert(*key)),
+        Op::Remove(key) => Outcome::Bool(model.remove(key)),
+        Op::Get(key) => Outcome::Bool(model.contains(key)),
+        Op::Iter => Outcome::Values(model.iter().cloned().collect()),
+        Op::Clear => {
+            model.clear();
+            Outcome::Done
+        }
+        Op::Extend(entries) => {
+            model.extend(entries.iter().map(|(key, _)| *key));
+            Outcome::Done
+        }
+        Op::Range(..) | Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+    }
+}
+
+impl Collection for LookupMap<u8, u8> {
+    type Model = BTreeMap<u8, u8>;
+
+    fn new(id: Vec<u8>) -> Self {
+        LookupMap::new(id)
+    }
+
+    fn apply(&mut self, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(key, value) => Outcome::Value(self.insert(key, value)),
+            Op::Remove(key) => Outcome::Value(self.remove(key)),
+            Op::Get(key) => Outcome::Value(self.get(key)),
+            // The map is not iterable and cannot be cleared, so all the keys are removed instead.
+            Op::Iter => Outcome::Entries(
+                (0..KEYS).filter_map(|key| self.get(&key).map(|value| (key, value))).collect(),
+            ),
+            Op::Clear => {
+                for key in 0..KEYS {
+                    self.remove(&key);
+                }
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                self.extend(entries.iter().cloned());
+                Outcome::Done
+            }
+            Op::Range(..) | Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+        match op {
+            Op::Range(..) => Outcome::Unsupported,
+            _ => apply_map_model(model, op),
+        }
+    }
+
+    fn check(&self, model: &Self::Model) {
+        for key in 0..KEYS {
+            assert_eq!(self.contains_key(&key), model.contains_key(&key));
+            assert_eq!(self.get(&key), model.get(&key).cloned());
+        }
+    }
+}
+
+impl Collection for LookupSet<u8> {
+    type Model = BTreeSet<u8>;
+
+    fn new(id: Vec<u8>) -> Self {
+        LookupSet::new(id)
+    }
+
+    fn apply(&mut self, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(key, _) => Outcome::Bool(self.insert(key)),
+            Op::Remove(key) => Outcome::Bool(self.remove(key)),
+            Op::Get(key) => Outcome::Bool(self.contains(key)),
+            // The set is not iterable and cannot be cleared, so all the keys are removed instead.
+            Op::Iter => Outcome::Values((0..KEYS).filter(|key| self.contains(key)).collect()),
+            Op::Clear => {
+                for key in 0..KEYS {
+                    self.remove(&key);
+                }
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                self.extend(entries.iter().map(|(key, _)| *key));
+                Outcome::Done
+            }
+            Op::Range(..) | Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+        apply_set_model(model, op)
+    }
+
+    fn check(&self, model: &Self::Model) {
+        for key in 0..KEYS {
+            assert_eq!(self.contains(&key), model.contains(&key));
+        }
+    }
+}
+
+impl Collection for UnorderedMap<u8, u8> {
+    type Model = BTreeMap<u8, u8>;
+
+    fn new(id: Vec<u8>) -> Self {
+        UnorderedMap::new(id)
+    }
+
+    fn apply(&mut self, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(key, value) => Outcome::Value(self.insert(key, value)),
+            Op::Remove(key) => Outcome::Value(self.remove(key)),
+            Op::Get(key) => Outcome::Value(self.get(key)),
+            Op::Iter => {
+                let mut entries = self.to_vec();
+                entries.sort_unstable();
+                Outcome::Entries(entries)
+            }
+            Op::Clear => {
+                self.clear();
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                self.extend(entries.iter().cloned());
+                Outcome::Done
+            }
+            Op::Range(..) | Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+        match op {
+            Op::Range(..) => Outcome::Unsupported,
+            _ => apply_map_model(model, op),
+        }
+    }
+
+    fn check(&self, model: &Self::Model) {
+        assert_eq!(self.len(), model.len() as u64);
+        assert_eq!(self.is_empty(), model.is_empty());
+        let entries: BTreeMap<u8, u8> = self.iter().collect();
+        assert_eq!(&entries, model);
+        let keys: Vec<u8> = self.keys().collect();
+        let values: Vec<u8> = self.values().collect();
+        assert_eq!(keys, self.keys_as_vector().to_vec());
+        assert_eq!(values, self.values_as_vector().to_vec());
+        for key in 0..KEYS {
+            assert_eq!(self.get(&key), model.get(&key).cloned());
+        }
+    }
+}
+
+impl Collection for UnorderedSet<u8> {
+    type Model = BTreeSet<u8>;
+
+    fn new(id: Vec<u8>) -> Self {
+        UnorderedSet::new(id)
+    }
+
+    fn apply(&mut self, op: &Op) -> Outcome {
+        match op {
+            Op::Insert(key, _) => Outcome::Bool(self.insert(key)),
+            Op::Remove(key) => Outcome::Bool(self.remove(key)),
+            Op::Get(key) => Outcome::Bool(self.contains(key)),
+            Op::Iter => {
+                let mut elements = self.to_vec();
+                elements.sort_unstable();
+                Outcome::Values(elements)
+            }
+            Op::Clear => {
+                self.clear();
+                Outcome::Done
+            }
+            Op::Extend(entries) => {
+                self.extend(entries.iter().map(|(key, _)| *key));
+                Outcome::Done
+            }
+            Op::Range(..) | Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+        }
+    }
+
+    fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+        apply_set_model(model, op)
+    }
+
+    fn check(&self, model: &Self::Model) {
+        assert_eq!(self.len(), model.len() as u64);
+        assert_eq!(self.is_empty(), model.is_empty());
+        let elements: BTreeSet<u8> = self.iter().collect();
+        assert_eq!(&elements, model);
+        assert_eq!(self.as_vector().len(), model.len() as u64);
+        for key in 0..KEYS {
+            assert_eq!(self.contains(&key), model.contains(&key));
+        }
+    }
+}
+
+/// Implements `Collection` for the maps that keep the keys ordered.
+macro_rules! impl_ordered_map {
+    ($map:ident) => {
+        impl Collection for $map<u8, u8> {
+            type Model = BTreeMap<u8, u8>;
+
+            fn new(id: Vec<u8>) -> Self {
+                $map::new(id)
+            }
+
+            fn apply(&mut self, op: &Op) -> Outcome {
+                match op {
+                    Op::Insert(key, value) => Outcome::Value(self.insert(key, value)),
+                    Op::Remove(key) => Outcome::Value(self.remove(key)),
+                    Op::Get(key) => Outcome::Value(self.get(key)),
+                    Op::Iter => Outcome::Entries(self.iter().collect()),
+                    Op::Range(lo, hi) => Outcome::Entries(self.range((*lo, *hi)).collect()),
+                    Op::Clear => {
+                        self.clear();
+                        Outcome::Done
+                    }
+                    Op::Extend(entries) => {
+                        for (key, value) in entries {
+                            self.insert(key, value);
+                        }
+                        Outcome::Done
+                    }
+                    Op::SwapRemove(_) | Op::Pop | Op::Replace(..) => Outcome::Unsupported,
+                }
+            }
+
+            fn apply_model(model: &mut Self::Model, op: &Op) -> Outcome {
+                apply_map_model(model, op)
+            }
+
+            fn check(&self, model: &Self::Model) {
+                assert_eq!(self.len(), model.len() as u64);
+                assert_eq!(self.to_vec(), model.clone().into_iter().collect::<Vec<_>>());
+                let reversed: Vec<_> = model.clone().into_iter().rev().collect();
+                assert_eq!(self.iter_rev().collect::<Vec<_>>(), reversed);
+                assert_eq!(self.min(), model.keys().next().cloned());
+                assert_eq!(self.max(), model.keys().next_back().cloned());
+                for key in 0..=KEYS {
+                    assert_eq!(self.contains_key(&key), model.contains_key(&key));
+                    let above = (Bound::Excluded(key), Bound::Unbounded);
+                    let below = (Bound::Unbounded, Bound::Excluded(key));
+                    assert_eq!(self.higher(&key), model.range(above).next().map(|(k, _)| *k));
+                    assert_eq!(self.lower(&key), model.range(below).next_back().map(|(k, _)| *k));
+                    assert_eq!(self.ceil_key(&key), model.range(key..).next().map(|(k, _)| *k));
+                    assert_eq!(
+                        self.floor_key(&key),
+                        model.range(..=key).next_back().map(|(k, _)| *k)
+                    );
+                }
+            }
+        }
+    };
+}
+
+impl_ordered_map!(TreeMap);
+impl_ordered_map!(LegacyTreeMap);
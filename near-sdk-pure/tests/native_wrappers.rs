@@ -0,0 +1,165 @@
+//! Runs the wrappers generated by `#[near_bindgen]` natively against the `MockedBlockchain`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk_pure::test_utils::{
+    alice, bob, with_mocked_blockchain, MockedBlockchain, VMContextBuilder,
+};
+use std::collections::BTreeMap;
+
+use near_sdk_pure::{env, near_bindgen, testing_env, PanicOnDefault, PromiseResult, ReturnData};
+
+#[near_bindgen]
+#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
+pub struct Counter {
+    value: u64,
+}
+
+#[near_bindgen]
+impl Counter {
+    #[init]
+    pub fn new(value: u64) -> Self {
+        Self { value }
+    }
+
+    pub fn inc(&mut self, by: u64) {
+        self.value += by;
+    }
+
+    #[payable]
+    pub fn deposit(&mut self) {
+        self.value += env::attached_deposit() as u64;
+    }
+
+    #[private]
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value
+    }
+
+    #[result_serializer(borsh)]
+    pub fn add_borsh(&self, #[serializer(borsh)] a: u64) -> u64 {
+        self.value + a
+    }
+
+    pub fn on_value(&mut self, #[callback] value: u64) {
+        self.value = value;
+    }
+}
+
+fn call(input: &[u8]) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder.input(input.to_vec());
+    builder
+}
+
+fn returned() -> Vec<u8> {
+    match with_mocked_blockchain(|b| b.return_data()) {
+        ReturnData::Value(value) => value,
+        other => panic!("Expected a value to be returned, got {:?}", other),
+    }
+}
+
+fn init(value: u64) {
+    testing_env!(call(format!("{{\"value\":{}}}", value).as_bytes()).build());
+    new();
+}
+
+#[test]
+fn json_args_and_state() {
+    init(1);
+    testing_env!(call(b"{\"by\":41}").build());
+    inc();
+    testing_env!(call(b"").build());
+    get();
+    assert_eq!(returned(), b"42".to_vec());
+}
+
+#[test]
+fn borsh_args_and_result() {
+    init(2);
+    testing_env!(call(&3u64.try_to_vec().unwrap()).build());
+    add_borsh();
+    assert_eq!(returned(), 5u64.try_to_vec().unwrap());
+}
+
+#[test]
+fn payable() {
+    init(0);
+    testing_env!(call(b"").attached_deposit(10).build());
+    deposit();
+    testing_env!(call(b"").build());
+    get();
+    assert_eq!(returned(), b"10".to_vec());
+}
+
+#[test]
+#[should_panic(expected = "Method inc doesn't accept deposit")]
+fn not_payable() {
+    init(0);
+    testing_env!(call(b"{\"by\":1}").attached_deposit(1).build());
+    inc();
+}
+
+#[test]
+fn private() {
+    init(5);
+    testing_env!(call(b"").predecessor_account_id(alice()).build());
+    reset();
+    testing_env!(call(b"").build());
+    get();
+    assert_eq!(returned(), b"0".to_vec());
+}
+
+#[test]
+#[should_panic(expected = "Method reset is private")]
+fn private_called_by_other() {
+    init(5);
+    testing_env!(call(b"").predecessor_account_id(bob()).build());
+    reset();
+}
+
+#[test]
+fn callback() {
+    init(0);
+    testing_env!(call(b"").build(), vec![PromiseResult::Successful(b"7".to_vec())]);
+    on_value();
+    testing_env!(call(b"").build());
+    get();
+    assert_eq!(returned(), b"7".to_vec());
+}
+
+#[test]
+#[should_panic(expected = "Failed to deserialize input from JSON.")]
+fn bad_input() {
+    init(0);
+    testing_env!(call(b"{\"by\":\"one\"}").build());
+    inc();
+}
+
+fn init_with_balance(balance: u128) {
+    let context = call(b"{\"value\":1}").account_balance(balance).build();
+    env::set_blockchain_interface(Box::new(
+        MockedBlockchain::new(context, BTreeMap::new(), vec![]).with_balance_tracking(),
+    ));
+    new();
+}
+
+#[test]
+fn storage_stake() {
+    // The state record: the "STATE" key and the borsh serialized `u64` value.
+    init_with_balance(10u128.pow(19) * (5 + 8 + 40));
+    testing_env!(call(b"").build());
+    get();
+    assert_eq!(returned(), b"1".to_vec());
+}
+
+#[test]
+#[should_panic(
+    expected = "The account alice.near wouldn't have enough balance to cover storage, required to have 10000000000000000000 yoctoNEAR more"
+)]
+fn lack_balance_for_storage() {
+    init_with_balance(10u128.pow(19) * (5 + 8 + 40 - 1));
+}
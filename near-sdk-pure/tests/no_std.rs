@@ -0,0 +1,30 @@
+//! Builds a contract with every panic handler of `setup_no_std!` for `wasm32` and checks how its
+//! panics and allocation failures abort the call.
+
+mod wasm;
+
+#[test]
+fn setup_no_std() {
+    for features in [&[][..], &["message"], &["abort"]] {
+        let wasm = wasm::build("no_std", features);
+        let outcome = wasm::call(&wasm, "echo", b"hello");
+        assert_eq!(outcome.result, Ok(()));
+        assert_eq!(outcome.store.data().return_value.as_deref(), Some(&b"hello"[..]));
+
+        let fail = wasm::call(&wasm, "fail", b"hello").result.unwrap_err();
+        let out_of_memory = wasm::call(&wasm, "out_of_memory", b"").result.unwrap_err();
+        if features == ["abort"] {
+            assert!(fail.contains("explicit guest panic"), "{}", fail);
+            assert!(out_of_memory.contains("explicit guest panic"), "{}", out_of_memory);
+        } else {
+            assert!(fail.contains("panicked at src/lib.rs:"), "{}", fail);
+            assert!(fail.contains("Failed with 5 bytes"), "{}", fail);
+            // The default handler of `alloc` panics.
+            assert!(
+                out_of_memory.contains("memory allocation of 1073741824 bytes failed"),
+                "{}",
+                out_of_memory
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Contract of the `no_std` test, declaring its allocator and panic handler with
+//! `setup_no_std!`.
+#![no_std]
+extern crate alloc;
+
+use alloc::vec::Vec;
+use near_sdk_pure::env;
+
+#[cfg(not(any(feature = "message", feature = "abort")))]
+near_sdk_pure::setup_no_std!();
+#[cfg(feature = "message")]
+near_sdk_pure::setup_no_std!(message);
+#[cfg(feature = "abort")]
+near_sdk_pure::setup_no_std!(abort);
+
+/// Returns the input.
+#[no_mangle]
+pub extern "C" fn echo() {
+    let input = env::input().unwrap_or_else(|| env::panic(b"Expected the input"));
+    env::value_return(&input);
+}
+
+/// Panics with a formatted message.
+#[no_mangle]
+pub extern "C" fn fail() {
+    let input = env::input().unwrap_or_default();
+    panic!("Failed with {} bytes", input.len());
+}
+
+/// Allocates more memory than the contract can have.
+#[no_mangle]
+pub extern "C" fn out_of_memory() {
+    let buf: Vec<u8> = Vec::with_capacity(1 << 30);
+    env::value_return(&buf);
+}
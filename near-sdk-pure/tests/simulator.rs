@@ -0,0 +1,286 @@
+//! Runs cross-contract calls between two contracts in the `Simulator`.
+
+use near_sdk_pure::test_utils::{alice, bob, carol, ExecutionStatus, Simulator, DEFAULT_GAS};
+use near_sdk_pure::{Balance, Gas};
+
+mod counter {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk_pure::{env, near_bindgen};
+
+    #[near_bindgen]
+    #[derive(Default, BorshDeserialize, BorshSerialize)]
+    pub struct Counter {
+        pub value: u64,
+    }
+
+    #[near_bindgen]
+    impl Counter {
+        pub fn inc(&mut self, by: u64) -> u64 {
+            self.value += by;
+            self.value
+        }
+
+        pub fn inc_and_fail(&mut self, by: u64) {
+            self.value += by;
+            env::panic(b"Increment failed");
+        }
+
+        pub fn get(&self) -> u64 {
+            self.value
+        }
+    }
+
+    pub const METHODS: near_sdk_pure::test_utils::ContractMethods =
+        &[("inc", inc), ("inc_and_fail", inc_and_fail), ("get", get)];
+}
+
+mod caller {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk_pure::test_utils::DEFAULT_GAS;
+    use near_sdk_pure::{env, near_bindgen, AccountId, Promise, PromiseResult};
+
+    #[near_bindgen]
+    #[derive(Default, BorshDeserialize, BorshSerialize)]
+    pub struct Caller {
+        pub last: Option<u64>,
+        pub failed: bool,
+        pub notes: Vec<String>,
+    }
+
+    #[near_bindgen]
+    impl Caller {
+        pub fn call_counter(&mut self, counter: AccountId, method: String, by: u64) {
+            let args = format!("{{\"by\":{}}}", by).into_bytes();
+            Promise::new(counter)
+                .function_call(method.into_bytes(), args, 0, DEFAULT_GAS / 3)
+                .then(Promise::new(env::current_account_id()).function_call(
+                    b"on_counter".to_vec(),
+                    vec![],
+                    0,
+                    DEFAULT_GAS / 3,
+                ))
+                .as_return();
+        }
+
+        pub fn call_counter_twice(&mut self, counter: AccountId, by: u64) {
+            let args = format!("{{\"by\":{}}}", by).into_bytes();
+            Promise::new(counter.clone())
+                .function_call(b"inc".to_vec(), args.clone(), 0, DEFAULT_GAS / 3)
+                .and(Promise::new(counter).function_call(b"inc".to_vec(), args, 0, DEFAULT_GAS / 3))
+                .as_return();
+        }
+
+        #[payable]
+        pub fn forward(&mut self, receiver_id: AccountId) {
+            Promise::new(receiver_id).transfer(env::attached_deposit());
+        }
+
+        #[payable(storage)]
+        pub fn add_note(&mut self, note: String) {
+            self.notes.push(note);
+        }
+
+        #[private]
+        pub fn on_counter(&mut self) -> Option<u64> {
+            assert_eq!(env::promise_results_count(), 1);
+            match env::promise_result(0) {
+                PromiseResult::Successful(value) => {
+                    self.last = Some(serde_json::from_slice(&value).unwrap());
+                }
+                _ => self.failed = true,
+            }
+            self.last
+        }
+    }
+
+    pub const METHODS: near_sdk_pure::test_utils::ContractMethods = &[
+        ("call_counter", call_counter),
+        ("call_counter_twice", call_counter_twice),
+        ("forward", forward),
+        ("add_note", add_note),
+        ("on_counter", on_counter),
+    ];
+}
+
+use caller::{Caller, CallerContract};
+use counter::{Counter, CounterContract};
+
+const NEAR: u128 = 10u128.pow(24);
+
+fn setup() -> Simulator {
+    let mut sim = Simulator::new();
+    sim.create_account(alice(), NEAR);
+    sim.create_account(bob(), NEAR);
+    sim.deploy(alice(), counter::METHODS);
+    sim.deploy(bob(), caller::METHODS);
+    sim.create_account(carol(), NEAR);
+    sim
+}
+
+#[test]
+fn direct_call_and_view() {
+    let mut sim = setup();
+    let counter = CounterContract { account_id: alice() };
+    let res = sim.call(&carol(), counter.inc(5), DEFAULT_GAS, 0);
+    assert_eq!(res.unwrap_json::<u64>(), 5);
+    assert_eq!(res.outcomes.len(), 1);
+    assert_eq!(sim.view(counter.get()), Ok(b"5".to_vec()));
+    assert_eq!(sim.state::<Counter>(&alice()).unwrap().value, 5);
+}
+
+#[test]
+fn cross_contract_call_with_callback() {
+    let mut sim = setup();
+    let caller = CallerContract { account_id: bob() };
+    let res =
+        sim.call(&carol(), caller.call_counter(alice(), "inc".to_string(), 3), DEFAULT_GAS, 0);
+    assert_eq!(res.unwrap_json::<Option<u64>>(), Some(3));
+    let executors: Vec<_> = res.outcomes.iter().map(|o| o.executor_id.clone()).collect();
+    assert_eq!(executors, vec![bob(), alice(), bob()]);
+    assert_eq!(res.outcomes[1].predecessor_id, bob());
+    assert_eq!(sim.state::<Counter>(&alice()).unwrap().value, 3);
+    assert_eq!(sim.state::<Caller>(&bob()).unwrap().last, Some(3));
+}
+
+#[test]
+fn failed_receipt_is_reverted() {
+    let mut sim = setup();
+    let counter = CounterContract { account_id: alice() };
+    sim.call(&carol(), counter.inc(1), DEFAULT_GAS, 0);
+
+    let caller = CallerContract { account_id: bob() };
+    let res = sim.call(
+        &carol(),
+        caller.call_counter(alice(), "inc_and_fail".to_string(), 10),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(res.is_ok());
+    assert_eq!(
+        res.outcomes[1].status,
+        ExecutionStatus::Failure("Smart contract panicked: Increment failed".to_string())
+    );
+    assert_eq!(sim.state::<Counter>(&alice()).unwrap().value, 1);
+    let caller_state = sim.state::<Caller>(&bob()).unwrap();
+    assert!(caller_state.failed);
+    assert_eq!(caller_state.last, None);
+}
+
+#[test]
+fn joint_promise_cannot_be_returned() {
+    let mut sim = setup();
+    let caller = CallerContract { account_id: bob() };
+    let res = sim.call(&carol(), caller.call_counter_twice(alice(), 2), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure("Returning joint promise is currently prohibited".to_string())
+    );
+    assert_eq!(res.outcomes.len(), 1);
+    assert!(sim.state::<Counter>(&alice()).is_none());
+}
+
+#[test]
+fn unknown_method() {
+    let mut sim = setup();
+    let caller = CallerContract { account_id: bob() };
+    let res =
+        sim.call(&carol(), caller.call_counter(alice(), "dec".to_string(), 1), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.outcomes[1].status,
+        ExecutionStatus::Failure(format!("Method dec is not found on {}", alice()))
+    );
+    assert!(sim.state::<Caller>(&bob()).unwrap().failed);
+}
+
+#[test]
+fn gas_and_storage_usage() {
+    let mut sim = setup();
+    let counter = CounterContract { account_id: alice() };
+    let res = sim.call(&carol(), counter.inc(1), DEFAULT_GAS, 0);
+    assert!(res.outcomes[0].gas_used > Gas::ZERO);
+    // The state record: the "STATE" key and the borsh serialized `u64` value.
+    assert_eq!(sim.account(&alice()).unwrap().storage_usage, 5 + 8 + 40);
+
+    let res = sim.call(&carol(), counter.inc(1), Gas::ONE_GIGA, 0);
+    assert_eq!(res.status, ExecutionStatus::Failure("Exceeded the prepaid gas.".to_string()));
+    assert_eq!(res.outcomes[0].gas_used, Gas::ONE_GIGA);
+    assert_eq!(sim.state::<Counter>(&alice()).unwrap().value, 1);
+}
+
+#[test]
+fn deposits_move_balances() {
+    let mut sim = setup();
+    let caller = CallerContract { account_id: bob() };
+    let res = sim.call(&carol(), caller.forward(alice()), DEFAULT_GAS, 100);
+    assert!(res.is_ok());
+    assert_eq!(sim.account(&carol()).unwrap().amount, NEAR - 100);
+    assert_eq!(sim.account(&bob()).unwrap().amount, NEAR);
+    assert_eq!(sim.account(&alice()).unwrap().amount, NEAR + 100);
+
+    // The transfer to a missing account fails and the tokens are refunded to the predecessor.
+    let res = sim.call(&carol(), caller.forward("dave.near".parse().unwrap()), DEFAULT_GAS, 100);
+    assert_eq!(
+        res.outcomes[1].status,
+        ExecutionStatus::Failure("Account dave.near does not exist".to_string())
+    );
+    assert_eq!(sim.account(&carol()).unwrap().amount, NEAR - 200);
+    assert_eq!(sim.account(&bob()).unwrap().amount, NEAR + 100);
+
+    let res = sim.call(&carol(), caller.forward(alice()), DEFAULT_GAS, NEAR);
+    assert!(!res.is_ok());
+    assert!(res.outcomes.is_empty());
+}
+
+#[test]
+fn lack_balance_for_storage() {
+    let mut sim = setup();
+    sim.deploy("poor.near".parse().unwrap(), counter::METHODS);
+    let counter = CounterContract { account_id: "poor.near".parse().unwrap() };
+    let res = sim.call(&carol(), counter.inc(1), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure(
+            "The account poor.near wouldn't have enough balance to cover storage, required to have 530000000000000000000 yoctoNEAR more"
+                .to_string()
+        )
+    );
+    assert!(sim.state::<Counter>("poor.near").is_none());
+}
+
+#[test]
+fn storage_is_paid_from_deposit() {
+    let mut sim = setup();
+    let caller = CallerContract { account_id: bob() };
+    let byte_cost = sim.fees_config.storage_amount_per_byte;
+    let res = sim.call(&carol(), caller.add_note("hello".to_string()), DEFAULT_GAS, NEAR / 10);
+    assert!(res.is_ok());
+    // The state record: the "STATE" key and the value with `None`, `false` and one note.
+    let bytes = 5 + (1 + 1 + 4 + 4 + 5) + 40;
+    assert_eq!(sim.account(&bob()).unwrap().storage_usage, bytes);
+    assert_eq!(sim.account(&carol()).unwrap().amount, NEAR - byte_cost * Balance::from(bytes));
+    assert_eq!(sim.state::<Caller>(&bob()).unwrap().notes, vec!["hello".to_string()]);
+
+    let res = sim.call(&carol(), caller.add_note("world".to_string()), DEFAULT_GAS, byte_cost);
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure(format!(
+            "Smart contract panicked: The deposit of {} yoctoNEAR does not cover the storage of 9 bytes, {} yoctoNEAR is required.",
+            byte_cost,
+            byte_cost * 9
+        ))
+    );
+    assert_eq!(sim.state::<Caller>(&bob()).unwrap().notes.len(), 1);
+}
+
+#[test]
+fn storage_is_paid_at_the_configured_price() {
+    let mut sim = setup();
+    let byte_cost = NEAR / 1000;
+    sim.fees_config.storage_amount_per_byte = byte_cost;
+    let caller = CallerContract { account_id: bob() };
+    let res = sim.call(&carol(), caller.add_note("hello".to_string()), DEFAULT_GAS, NEAR / 10);
+    assert!(res.is_ok());
+    let bytes: u64 = 5 + (1 + 1 + 4 + 4 + 5) + 40;
+    assert_eq!(sim.account(&carol()).unwrap().amount, NEAR - byte_cost * Balance::from(bytes));
+    assert_eq!(sim.account(&bob()).unwrap().amount, NEAR + byte_cost * Balance::from(bytes));
+}
@@ -0,0 +1,190 @@
+//! Upgrades the contract and the layout of its state in the `Simulator`.
+
+use near_sdk_pure::state::STATE_VERSION_KEY;
+use near_sdk_pure::test_utils::{alice, carol, ExecutionStatus, Simulator, DEFAULT_GAS};
+use near_sdk_pure::upgrade::update_contract_tx;
+
+mod v1 {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk_pure::upgrade::Upgradable;
+    use near_sdk_pure::{env, near_bindgen, upgradable};
+
+    #[near_bindgen]
+    #[derive(Default, BorshDeserialize, BorshSerialize)]
+    pub struct Counter {
+        pub value: u32,
+    }
+
+    // Only the account itself can upgrade the contract.
+    impl Upgradable for Counter {
+        fn assert_can_upgrade(&self) {
+            if env::predecessor_account_id() != env::current_account_id() {
+                env::panic(b"Only the owner can upgrade the contract");
+            }
+        }
+    }
+
+    upgradable!(Counter);
+
+    #[near_bindgen]
+    impl Counter {
+        pub fn inc(&mut self, by: u32) -> u32 {
+            self.value += by;
+            self.value
+        }
+    }
+
+    pub const METHODS: near_sdk_pure::test_utils::ContractMethods = &[
+        ("inc", inc),
+        ("update_contract", update_contract),
+        ("on_update_contract", on_update_contract),
+    ];
+}
+
+mod v2 {
+    use borsh::{BorshDeserialize, BorshSerialize};
+    use near_sdk_pure::upgrade::Upgradable;
+    use near_sdk_pure::{env, near_bindgen, upgradable};
+
+    // The state stored by `v1`, without a version.
+    #[near_bindgen(version = 2, migrate_from(super::v1::Counter = 0))]
+    #[derive(Default, BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+    pub struct Counter {
+        pub value: u64,
+        pub calls: u64,
+    }
+
+    impl From<super::v1::Counter> for Counter {
+        fn from(old: super::v1::Counter) -> Self {
+            Self { value: old.value.into(), calls: 0 }
+        }
+    }
+
+    impl Upgradable for Counter {
+        fn assert_can_upgrade(&self) {
+            if env::predecessor_account_id() != env::current_account_id() {
+                env::panic(b"Only the owner can upgrade the contract");
+            }
+        }
+    }
+
+    upgradable!(Counter);
+
+    #[near_bindgen]
+    impl Counter {
+        pub fn inc(&mut self, by: u64) -> u64 {
+            self.value += by;
+            self.calls += 1;
+            self.value
+        }
+
+        pub fn get(&self) -> (u64, u64) {
+            (self.value, self.calls)
+        }
+    }
+
+    pub const METHODS: near_sdk_pure::test_utils::ContractMethods = &[
+        ("inc", inc),
+        ("get", get),
+        (near_sdk_pure::upgrade::DEFAULT_MIGRATE_METHOD, migrate),
+        ("update_contract", update_contract),
+        ("on_update_contract", on_update_contract),
+    ];
+}
+
+const NEAR: u128 = 10u128.pow(24);
+
+const V2_CODE: &[u8] = b"counter v2";
+
+fn setup_v1() -> Simulator {
+    let mut sim = Simulator::new();
+    sim.create_account(alice(), NEAR);
+    sim.create_account(carol(), NEAR);
+    sim.deploy(alice(), v1::METHODS);
+    let counter = v1::CounterContract { account_id: alice() };
+    assert!(sim.call(&carol(), counter.inc(5), DEFAULT_GAS, 0).is_ok());
+    sim
+}
+
+fn setup() -> Simulator {
+    let mut sim = setup_v1();
+    sim.deploy(alice(), v2::METHODS);
+    sim
+}
+
+fn stored_version(sim: &Simulator) -> Option<Vec<u8>> {
+    sim.account(&alice()).unwrap().storage.get(STATE_VERSION_KEY).cloned()
+}
+
+#[test]
+fn state_is_migrated_on_read() {
+    let mut sim = setup();
+    let counter = v2::CounterContract { account_id: alice() };
+    // The view reads the old state without writing it back.
+    assert_eq!(sim.view(counter.get()), Ok(b"[5,0]".to_vec()));
+    assert_eq!(stored_version(&sim), None);
+
+    let res = sim.call(&carol(), counter.inc(2), DEFAULT_GAS, 0);
+    assert_eq!(res.unwrap_json::<u64>(), 7);
+    assert_eq!(stored_version(&sim), Some(2u32.to_le_bytes().to_vec()));
+    assert_eq!(sim.state::<v2::Counter>(&alice()), Some(v2::Counter { value: 7, calls: 1 }));
+}
+
+#[test]
+fn explicit_migration() {
+    let mut sim = setup();
+    let counter = v2::CounterContract { account_id: alice() };
+    let res = sim.call(&carol(), counter.migrate(), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure("Smart contract panicked: Method migrate is private".to_string())
+    );
+    assert_eq!(stored_version(&sim), None);
+
+    assert!(sim.call(&alice(), counter.migrate(), DEFAULT_GAS, 0).is_ok());
+    assert_eq!(stored_version(&sim), Some(2u32.to_le_bytes().to_vec()));
+    assert_eq!(sim.state::<v2::Counter>(&alice()), Some(v2::Counter { value: 5, calls: 0 }));
+}
+
+#[test]
+fn update_contract() {
+    let mut sim = setup_v1();
+    sim.register_code(V2_CODE.to_vec(), v2::METHODS);
+    let res = sim.call(&carol(), update_contract_tx(&alice(), V2_CODE.to_vec()), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure(
+            "Smart contract panicked: Only the owner can upgrade the contract".to_string()
+        )
+    );
+
+    let res = sim.call(&alice(), update_contract_tx(&alice(), V2_CODE.to_vec()), DEFAULT_GAS, 0);
+    assert!(res.is_ok());
+    // The update, the deployment with the migration and the callback.
+    assert_eq!(res.outcomes.len(), 3);
+    assert_eq!(sim.account(&alice()).unwrap().code, V2_CODE.to_vec());
+    assert_eq!(stored_version(&sim), Some(2u32.to_le_bytes().to_vec()));
+    let counter = v2::CounterContract { account_id: alice() };
+    assert_eq!(sim.view(counter.get()), Ok(b"[5,0]".to_vec()));
+}
+
+#[test]
+fn failed_migration_reverts_the_deployment() {
+    let mut sim = setup_v1();
+    // The code without the `migrate` method.
+    sim.register_code(b"counter v3".to_vec(), v1::METHODS);
+    let res =
+        sim.call(&alice(), update_contract_tx(&alice(), b"counter v3".to_vec()), DEFAULT_GAS, 0);
+    assert_eq!(
+        res.outcomes[1].status,
+        ExecutionStatus::Failure(format!("Method migrate is not found on {}", alice()))
+    );
+    assert_eq!(
+        res.status,
+        ExecutionStatus::Failure(
+            "Smart contract panicked: The contract update failed.".to_string()
+        )
+    );
+    assert!(sim.account(&alice()).unwrap().code.is_empty());
+    assert_eq!(sim.state::<v1::Counter>(&alice()).unwrap().value, 5);
+}
@@ -0,0 +1,167 @@
+//! Compares the gas and the code size of a contract using `env`, which calls the host functions
+//! through the statically dispatched `NearBlockchain` on `wasm32`, with the same contract built
+//! with the `dynamic-dispatch` feature, which calls them through a boxed `BlockchainInterface` as
+//! `env` did before.
+//!
+//! The host functions charge the same gas either way, so the difference is in the wasm
+//! instructions, which the runtime charges one by one. The contracts are instrumented to count
+//! the executed instructions and run in `wasmi`. Run with
+//! `cargo test --test static_dispatch -- --nocapture` to see the numbers.
+
+mod wasm;
+
+use std::convert::Infallible;
+
+use wasm_encoder::reencode::{self, Reencode};
+use wasm_encoder::{
+    CodeSection, ConstExpr, ExportKind, ExportSection, GlobalSection, GlobalType, Instruction,
+    ValType,
+};
+use wasmi::Value;
+use wasmparser::{Parser, Payload, TypeRef};
+
+/// Gas that the runtime charges for every wasm instruction, `wasm_regular_op_cost`.
+const REGULAR_OP_COST: u64 = 822_756;
+
+fn read_leb128(bytes: &[u8], pos: &mut usize) -> usize {
+    let mut result = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        result |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return result;
+        }
+        shift += 7;
+    }
+}
+
+/// Returns the size of the code section of the wasm module.
+fn code_size(wasm: &[u8]) -> usize {
+    // Skip the magic number and the version.
+    let mut pos = 8;
+    while pos < wasm.len() {
+        let id = wasm[pos];
+        pos += 1;
+        let len = read_leb128(wasm, &mut pos);
+        if id == 10 {
+            return len;
+        }
+        pos += len;
+    }
+    panic!("The module has no code section")
+}
+
+/// Reencodes the contract with an exported `ops` global that counts the executed wasm
+/// instructions, incremented before each of them.
+struct CountOps {
+    /// Index of the `ops` global, after all the globals of the contract.
+    ops_global: u32,
+}
+
+impl Reencode for CountOps {
+    type Error = Infallible;
+
+    fn parse_global_section(
+        &mut self,
+        globals: &mut GlobalSection,
+        section: wasmparser::GlobalSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        reencode::utils::parse_global_section(self, globals, section)?;
+        let ty = GlobalType { val_type: ValType::I64, mutable: true, shared: false };
+        globals.global(ty, &ConstExpr::i64_const(0));
+        Ok(())
+    }
+
+    fn parse_export_section(
+        &mut self,
+        exports: &mut ExportSection,
+        section: wasmparser::ExportSectionReader<'_>,
+    ) -> Result<(), reencode::Error> {
+        reencode::utils::parse_export_section(self, exports, section)?;
+        exports.export("ops", ExportKind::Global, self.ops_global);
+        Ok(())
+    }
+
+    fn parse_function_body(
+        &mut self,
+        code: &mut CodeSection,
+        func: wasmparser::FunctionBody<'_>,
+    ) -> Result<(), reencode::Error> {
+        let mut function = self.new_function_with_parsed_locals(&func)?;
+        let mut reader = func.get_operators_reader()?;
+        while !reader.eof() {
+            let instruction = self.parse_instruction(&mut reader)?;
+            function
+                .instruction(&Instruction::GlobalGet(self.ops_global))
+                .instruction(&Instruction::I64Const(1))
+                .instruction(&Instruction::I64Add)
+                .instruction(&Instruction::GlobalSet(self.ops_global))
+                .instruction(&instruction);
+        }
+        code.function(&function);
+        Ok(())
+    }
+}
+
+fn count_ops(wasm: &[u8]) -> Vec<u8> {
+    let mut globals = 0;
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.expect("Failed to parse the contract") {
+            Payload::ImportSection(section) => {
+                for import in section.into_imports() {
+                    if let TypeRef::Global(_) = import.unwrap().ty {
+                        globals += 1;
+                    }
+                }
+            }
+            Payload::GlobalSection(section) => globals += section.count(),
+            _ => {}
+        }
+    }
+    let mut module = wasm_encoder::Module::new();
+    CountOps { ops_global: globals }
+        .parse_core_module(&mut module, Parser::new(0), wasm)
+        .expect("Failed to instrument the contract");
+    module.finish()
+}
+
+/// Calls the `run` method of the contract with the input and returns the number of executed wasm
+/// instructions.
+fn run(wasm: &[u8], input: &[u8]) -> u64 {
+    let outcome = wasm::call(&count_ops(wasm), "run", input);
+    assert_eq!(outcome.result, Ok(()));
+    let host = outcome.store.data();
+    assert_eq!(host.return_value.as_deref(), Some(input));
+    assert_eq!(host.storage.len(), 100);
+    match outcome.instance.get_global(&outcome.store, "ops").unwrap().get(&outcome.store) {
+        Value::I64(ops) => ops as u64,
+        value => panic!("Unexpected value of the counter {:?}", value),
+    }
+}
+
+#[test]
+fn static_dispatch_saves_gas() {
+    let static_wasm = wasm::build("static_dispatch", &[]);
+    let dynamic_wasm = wasm::build("static_dispatch", &["dynamic"]);
+    let input = [7u8; 64];
+    let static_ops = run(&static_wasm, &input);
+    let dynamic_ops = run(&dynamic_wasm, &input);
+    println!(
+        "wasm instructions: static {}, dynamic {}; gas: static {}, dynamic {}, saved {}",
+        static_ops,
+        dynamic_ops,
+        static_ops * REGULAR_OP_COST,
+        dynamic_ops * REGULAR_OP_COST,
+        (dynamic_ops - static_ops) * REGULAR_OP_COST
+    );
+    println!("wasm size: static {} bytes, dynamic {} bytes", static_wasm.len(), dynamic_wasm.len());
+    println!(
+        "code section: static {} bytes, dynamic {} bytes",
+        code_size(&static_wasm),
+        code_size(&dynamic_wasm)
+    );
+    assert!(static_ops < dynamic_ops);
+    assert!(code_size(&static_wasm) < code_size(&dynamic_wasm));
+}
@@ -0,0 +1,30 @@
+//! Contract of the `static_dispatch` benchmark. It runs the same workload through `env`, which on
+//! `wasm32` calls the host functions directly through `NearBlockchain`, or with the `dynamic`
+//! feature through a `Box<dyn BlockchainInterface>` that is set at the entry point, as `env` did
+//! before the static dispatch.
+#![no_std]
+extern crate alloc;
+
+use near_sdk_pure::env;
+
+near_sdk_pure::setup_no_std!(abort);
+
+/// Reads the call context, writes the input under 100 keys, reads each of them back and returns
+/// the input.
+#[no_mangle]
+pub extern "C" fn run() {
+    #[cfg(feature = "dynamic")]
+    env::set_blockchain_interface(alloc::boxed::Box::new(near_sdk_pure::NearBlockchain));
+    if env::attached_deposit() != 0 || env::predecessor_account_id() == env::current_account_id() {
+        env::panic(b"Unexpected context");
+    }
+    let input = env::input().unwrap_or_else(|| env::panic(b"Expected the input"));
+    for i in 0u8..100 {
+        let key = [i];
+        env::storage_write(&key, &input);
+        if env::storage_read(&key).as_deref() != Some(&input[..]) {
+            env::panic(b"Unexpected value");
+        }
+    }
+    env::value_return(&input);
+}
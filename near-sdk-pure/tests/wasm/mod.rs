@@ -0,0 +1,175 @@
+//! Builds the test contracts in the subdirectories of `tests` for `wasm32` and calls them in
+//! `wasmi` with the host functions that they import. Requires the `wasm32-unknown-unknown` target.
+// Not every test uses all of the outcome.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use wasmi::core::Trap;
+use wasmi::{
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder,
+};
+
+/// Memory that the contracts can grow to, so that the allocations fail well before the limit of
+/// `wasm32`.
+const MEMORY_LIMIT: usize = 64 << 20;
+
+/// Builds the contract in `tests/<name>` with the features and returns its wasm code.
+pub fn build(name: &str, features: &[&str]) -> Vec<u8> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(name);
+    // The contracts share the dependencies, built once for all of them.
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("wasm");
+    let status = Command::new(env!("CARGO"))
+        .current_dir(&dir)
+        // The runtime only supports the instructions of the MVP of wasm.
+        .env("RUSTFLAGS", "-C target-cpu=mvp")
+        .args(["build", "--release", "--target", "wasm32-unknown-unknown", "--target-dir"])
+        .arg(&target_dir)
+        .args(["--features", &features.join(",")])
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Failed to build the contract {}", name);
+    let wasm = target_dir.join("wasm32-unknown-unknown/release").join(format!("{}.wasm", name));
+    std::fs::read(wasm).unwrap()
+}
+
+/// State of the host functions that the contract imports.
+pub struct Host {
+    pub input: Vec<u8>,
+    pub registers: HashMap<u64, Vec<u8>>,
+    pub storage: HashMap<Vec<u8>, Vec<u8>>,
+    pub return_value: Option<Vec<u8>>,
+    limits: StoreLimits,
+}
+
+fn memory(caller: &Caller<'_, Host>) -> Memory {
+    caller.get_export("memory").and_then(Extern::into_memory).expect("The contract has no memory")
+}
+
+fn read_memory(caller: &Caller<'_, Host>, ptr: u64, len: u64) -> Vec<u8> {
+    let mut buf = vec![0u8; len as usize];
+    memory(caller).read(caller, ptr as usize, &mut buf).expect("Out of bounds read");
+    buf
+}
+
+fn write_memory(caller: &mut Caller<'_, Host>, ptr: u64, data: &[u8]) {
+    memory(caller).write(caller, ptr as usize, data).expect("Out of bounds write");
+}
+
+fn linker(engine: &Engine) -> Linker<Host> {
+    let mut linker = Linker::new(engine);
+    linker
+        .func_wrap("env", "read_register", |mut caller: Caller<'_, Host>, id: u64, ptr: u64| {
+            let data = caller.data().registers[&id].clone();
+            write_memory(&mut caller, ptr, &data);
+        })
+        .unwrap()
+        .func_wrap("env", "register_len", |caller: Caller<'_, Host>, id: u64| {
+            caller.data().registers.get(&id).map_or(u64::MAX, |data| data.len() as u64)
+        })
+        .unwrap()
+        .func_wrap("env", "input", |mut caller: Caller<'_, Host>, id: u64| {
+            let input = caller.data().input.clone();
+            caller.data_mut().registers.insert(id, input);
+        })
+        .unwrap()
+        .func_wrap("env", "current_account_id", |mut caller: Caller<'_, Host>, id: u64| {
+            caller.data_mut().registers.insert(id, b"alice.near".to_vec());
+        })
+        .unwrap()
+        .func_wrap("env", "predecessor_account_id", |mut caller: Caller<'_, Host>, id: u64| {
+            caller.data_mut().registers.insert(id, b"bob.near".to_vec());
+        })
+        .unwrap()
+        .func_wrap("env", "attached_deposit", |mut caller: Caller<'_, Host>, ptr: u64| {
+            write_memory(&mut caller, ptr, &0u128.to_le_bytes());
+        })
+        .unwrap()
+        .func_wrap(
+            "env",
+            "storage_write",
+            |mut caller: Caller<'_, Host>,
+             key_len: u64,
+             key_ptr: u64,
+             value_len: u64,
+             value_ptr: u64,
+             id: u64| {
+                let key = read_memory(&caller, key_ptr, key_len);
+                let value = read_memory(&caller, value_ptr, value_len);
+                match caller.data_mut().storage.insert(key, value) {
+                    Some(evicted) => {
+                        caller.data_mut().registers.insert(id, evicted);
+                        1u64
+                    }
+                    None => 0,
+                }
+            },
+        )
+        .unwrap()
+        .func_wrap(
+            "env",
+            "storage_read",
+            |mut caller: Caller<'_, Host>, key_len: u64, key_ptr: u64, id: u64| {
+                let key = read_memory(&caller, key_ptr, key_len);
+                match caller.data().storage.get(&key).cloned() {
+                    Some(value) => {
+                        caller.data_mut().registers.insert(id, value);
+                        1u64
+                    }
+                    None => 0,
+                }
+            },
+        )
+        .unwrap()
+        .func_wrap("env", "value_return", |mut caller: Caller<'_, Host>, len: u64, ptr: u64| {
+            caller.data_mut().return_value = Some(read_memory(&caller, ptr, len));
+        })
+        .unwrap()
+        .func_wrap("env", "panic", |_: Caller<'_, Host>| -> Result<(), Trap> {
+            Err(Trap::new("Smart contract panicked: explicit guest panic"))
+        })
+        .unwrap()
+        .func_wrap("env", "panic_utf8", |caller: Caller<'_, Host>, len: u64, ptr: u64| {
+            let message = read_memory(&caller, ptr, len);
+            Err::<(), _>(Trap::new(format!(
+                "Smart contract panicked: {}",
+                String::from_utf8_lossy(&message)
+            )))
+        })
+        .unwrap();
+    linker
+}
+
+/// Result of calling a method of the contract.
+pub struct Outcome {
+    pub store: Store<Host>,
+    pub instance: Instance,
+    /// The message of the trap if the call failed.
+    pub result: Result<(), String>,
+}
+
+/// Calls the method of the contract with the input.
+pub fn call(wasm: &[u8], method: &str, input: &[u8]) -> Outcome {
+    let engine = Engine::new(&Config::default());
+    let module = Module::new(&engine, wasm).unwrap();
+    let host = Host {
+        input: input.to_vec(),
+        registers: HashMap::new(),
+        storage: HashMap::new(),
+        return_value: None,
+        limits: StoreLimitsBuilder::new().memory_size(MEMORY_LIMIT).build(),
+    };
+    let mut store = Store::new(&engine, host);
+    store.limiter(|host| &mut host.limits);
+    let instance =
+        linker(&engine).instantiate(&mut store, &module).unwrap().start(&mut store).unwrap();
+    let result = instance
+        .get_typed_func::<(), ()>(&store, method)
+        .unwrap()
+        .call(&mut store, ())
+        .map_err(|err| err.to_string());
+    Outcome { store, instance, result }
+}